@@ -0,0 +1,493 @@
+//! In-memory HTTP caching
+//!
+//! A client built with [`ClientBuilder::cache`](crate::ClientBuilder::cache)
+//! keeps the responses to its `GET` requests and serves them again while they
+//! are fresh, as told by their `Cache-Control: max-age`, `Expires` or, for
+//! lack of both, `Last-Modified` headers. Stale responses with an `ETag` or a
+//! `Last-Modified` header are revalidated with a conditional request
+//! (`If-None-Match`, `If-Modified-Since`), and a `304 Not Modified` answer is
+//! served from the cache. [`Response::from_cache`](crate::Response::from_cache)
+//! tells whether a response came from the cache.
+//!
+//! The cache is private to the client, so `Cache-Control: private` responses
+//! are kept as well. Responses with `Cache-Control: no-store` or `Vary: *` are
+//! not kept, and `Cache-Control: no-cache` ones are revalidated every time.
+//! A request with `Cache-Control: no-store` bypasses the cache, one with
+//! `no-cache` or `max-age` revalidates. Requests carrying their own
+//! conditional or `Range` headers are sent as they are. A successful unsafe
+//! request, such as a `POST`, evicts the response of its URL.
+//!
+//! Responses are kept as received, so compressed ones are decoded again each
+//! time they are served. Only the latest variant of a response with a `Vary`
+//! header is kept.
+
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime};
+
+use antidote::Mutex;
+use bytes::{Bytes, BytesMut};
+use http::header::{
+    AGE, CACHE_CONTROL, CONTENT_LENGTH, DATE, ETAG, EXPIRES, IF_MATCH, IF_MODIFIED_SINCE,
+    IF_NONE_MATCH, IF_RANGE, IF_UNMODIFIED_SINCE, LAST_MODIFIED, RANGE, VARY,
+};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Version};
+use http_body::{Body as _, Frame, SizeHint};
+use http_body_util::{BodyExt, Full};
+use lru::LruCache;
+
+use crate::client::body::{boxed, ResponseBody};
+use crate::error::BoxError;
+use crate::util::client::InnerRequest;
+
+/// How many responses a client caches and how large they may be.
+///
+/// ```
+/// # fn run() -> Result<(), rquest::Error> {
+/// let client = rquest::Client::builder()
+///     .cache(rquest::cache::CacheConfig::new().max_entries(10_000))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CacheConfig {
+    max_entries: NonZeroUsize,
+    max_body_size: usize,
+}
+
+impl CacheConfig {
+    /// Creates a configuration keeping up to 1024 responses of up to 10 MiB each.
+    pub fn new() -> CacheConfig {
+        CacheConfig {
+            max_entries: NonZeroUsize::new(1024).unwrap(),
+            max_body_size: 10 * 1024 * 1024,
+        }
+    }
+
+    /// Sets how many responses are kept, the least recently used being
+    /// evicted first. Zero is taken as one.
+    pub fn max_entries(mut self, max_entries: usize) -> CacheConfig {
+        self.max_entries = NonZeroUsize::new(max_entries).unwrap_or(NonZeroUsize::MIN);
+        self
+    }
+
+    /// Sets the largest body, in bytes as received, of a response to keep.
+    pub fn max_body_size(mut self, max_body_size: usize) -> CacheConfig {
+        self.max_body_size = max_body_size;
+        self
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> CacheConfig {
+        CacheConfig::new()
+    }
+}
+
+/// Marks a response served from the cache, in its extensions.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FromCache;
+
+struct Entry {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Bytes,
+    /// The request headers named by `Vary`, with their values.
+    vary: Vec<(HeaderName, Option<HeaderValue>)>,
+    /// When the response was received, or last revalidated.
+    received: Instant,
+    /// The age of the response when it was received.
+    age: Duration,
+    /// How long the response is fresh for.
+    lifetime: Duration,
+}
+
+pub(crate) struct Cache {
+    max_body_size: usize,
+    entries: Mutex<LruCache<String, Entry>>,
+}
+
+impl Cache {
+    pub(crate) fn new(config: CacheConfig) -> Cache {
+        Cache {
+            max_body_size: config.max_body_size,
+            entries: Mutex::new(LruCache::new(config.max_entries)),
+        }
+    }
+
+    /// Serves the response to `req` from the cache, or calls `send`, possibly
+    /// with a conditional request, and keeps its response.
+    pub(crate) fn call<F>(
+        self: &std::sync::Arc<Self>,
+        req: InnerRequest<crate::Body>,
+        send: F,
+    ) -> Pin<Box<dyn Future<Output = Result<http::Response<ResponseBody>, BoxError>> + Send>>
+    where
+        F: FnOnce(
+            InnerRequest<crate::Body>,
+        ) -> Pin<
+            Box<dyn Future<Output = Result<http::Response<ResponseBody>, BoxError>> + Send>,
+        >,
+    {
+        let method = req.request().method().clone();
+        let key = req.request().uri().to_string();
+
+        if method != Method::GET {
+            if is_safe(&method) {
+                return send(req);
+            }
+            let sending = send(req);
+            let cache = self.clone();
+            return Box::pin(async move {
+                let res = sending.await?;
+                if res.status().is_success() || res.status().is_redirection() {
+                    cache.entries.lock().pop(&key);
+                }
+                Ok(res)
+            });
+        }
+
+        let directives = Directives::parse(req.request().headers());
+        if directives.no_store || bypasses(req.request().headers()) {
+            return send(req);
+        }
+
+        let mut revalidating = false;
+        let req = {
+            let mut entries = self.entries.lock();
+            match entries.get(&key) {
+                Some(entry) if entry.matches(req.request().headers()) => {
+                    let age = entry.current_age();
+                    let fresh = !directives.no_cache
+                        && age < entry.lifetime
+                        && directives.max_age.map_or(true, |max_age| age <= max_age);
+                    if fresh {
+                        let res = entry.response(age);
+                        return Box::pin(async move { Ok(res) });
+                    }
+
+                    let (mut request, network_scheme, alpn_protos) = req.pieces();
+                    if let Some(etag) = entry.headers.get(ETAG) {
+                        request.headers_mut().insert(IF_NONE_MATCH, etag.clone());
+                        revalidating = true;
+                    }
+                    if let Some(last_modified) = entry.headers.get(LAST_MODIFIED) {
+                        request
+                            .headers_mut()
+                            .insert(IF_MODIFIED_SINCE, last_modified.clone());
+                        revalidating = true;
+                    }
+                    InnerRequest::from_pieces(request, network_scheme, alpn_protos)
+                }
+                _ => req,
+            }
+        };
+
+        let request_headers = req.request().headers().clone();
+        let sending = send(req);
+        let cache = self.clone();
+        Box::pin(async move {
+            let res = sending.await?;
+
+            if revalidating && res.status() == StatusCode::NOT_MODIFIED {
+                let mut entries = cache.entries.lock();
+                if let Some(entry) = entries.get_mut(&key) {
+                    entry.revalidated(res.headers());
+                    return Ok(entry.response(entry.current_age()));
+                }
+                return Ok(res);
+            }
+
+            let (Some(lifetime), Some(vary)) = (storable(&res), vary(res.headers())) else {
+                cache.entries.lock().pop(&key);
+                return Ok(res);
+            };
+            let vary = vary
+                .into_iter()
+                .map(|name| {
+                    let value = request_headers.get(&name).cloned();
+                    (name, value)
+                })
+                .collect();
+
+            let (parts, body) = res.into_parts();
+            let body = match collect(body, cache.max_body_size).await? {
+                Ok(body) => body,
+                Err(body) => {
+                    cache.entries.lock().pop(&key);
+                    return Ok(http::Response::from_parts(parts, body));
+                }
+            };
+
+            let entry = Entry {
+                status: parts.status,
+                version: parts.version,
+                headers: parts.headers.clone(),
+                body: body.clone(),
+                vary,
+                received: Instant::now(),
+                age: age(&parts.headers),
+                lifetime,
+            };
+            cache.entries.lock().put(key, entry);
+
+            Ok(http::Response::from_parts(parts, boxed(Full::new(body))))
+        })
+    }
+}
+
+impl std::fmt::Debug for Cache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let entries = self.entries.lock();
+        f.debug_struct("Cache")
+            .field("max_entries", &entries.cap())
+            .field("max_body_size", &self.max_body_size)
+            .field("len", &entries.len())
+            .finish()
+    }
+}
+
+impl Entry {
+    fn current_age(&self) -> Duration {
+        self.age + self.received.elapsed()
+    }
+
+    /// Whether the request headers named by `Vary` match those of the
+    /// request the response was kept for.
+    fn matches(&self, headers: &HeaderMap) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, value)| headers.get(name) == value.as_ref())
+    }
+
+    /// Updates the entry with the headers of a `304 Not Modified` response.
+    fn revalidated(&mut self, headers: &HeaderMap) {
+        for name in headers.keys() {
+            if name == CONTENT_LENGTH {
+                continue;
+            }
+            self.headers.remove(name);
+            for value in headers.get_all(name) {
+                self.headers.append(name.clone(), value.clone());
+            }
+        }
+        self.received = Instant::now();
+        self.age = age(headers);
+        self.lifetime = lifetime(&self.headers).unwrap_or_default();
+    }
+
+    fn response(&self, age: Duration) -> http::Response<ResponseBody> {
+        let mut res = http::Response::new(boxed(Full::new(self.body.clone())));
+        *res.status_mut() = self.status;
+        *res.version_mut() = self.version;
+        *res.headers_mut() = self.headers.clone();
+        res.headers_mut()
+            .insert(AGE, HeaderValue::from(age.as_secs()));
+        res.extensions_mut().insert(FromCache);
+        res
+    }
+}
+
+/// The `Cache-Control` directives the cache acts on.
+#[derive(Default)]
+struct Directives {
+    no_store: bool,
+    no_cache: bool,
+    max_age: Option<Duration>,
+}
+
+impl Directives {
+    fn parse(headers: &HeaderMap) -> Directives {
+        let mut directives = Directives::default();
+        let values = headers
+            .get_all(CACHE_CONTROL)
+            .into_iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+        for directive in values {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive.trim(), None),
+            };
+            if name.eq_ignore_ascii_case("no-store") {
+                directives.no_store = true;
+            } else if name.eq_ignore_ascii_case("no-cache") {
+                directives.no_cache = true;
+            } else if name.eq_ignore_ascii_case("max-age") {
+                directives.max_age = value
+                    .and_then(|value| value.parse().ok())
+                    .map(Duration::from_secs);
+            }
+        }
+        directives
+    }
+}
+
+/// Methods that do not change the state of the server.
+fn is_safe(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+/// Whether a request asks for something the cache cannot answer itself.
+fn bypasses(headers: &HeaderMap) -> bool {
+    [
+        IF_NONE_MATCH,
+        IF_MODIFIED_SINCE,
+        IF_MATCH,
+        IF_UNMODIFIED_SINCE,
+        IF_RANGE,
+        RANGE,
+    ]
+    .iter()
+    .any(|name| headers.contains_key(name))
+}
+
+/// How long `res` is fresh for, if it may be kept.
+fn storable(res: &http::Response<ResponseBody>) -> Option<Duration> {
+    // The status codes that are cacheable by default.
+    let cacheable = matches!(
+        res.status().as_u16(),
+        200 | 203 | 204 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501
+    );
+    if !cacheable || Directives::parse(res.headers()).no_store {
+        return None;
+    }
+    let lifetime = lifetime(res.headers());
+    let validated = res.headers().contains_key(ETAG) || res.headers().contains_key(LAST_MODIFIED);
+    match lifetime {
+        Some(lifetime) if !lifetime.is_zero() => Some(lifetime),
+        _ if validated => Some(Duration::ZERO),
+        _ => None,
+    }
+}
+
+/// The freshness lifetime of a response, if any can be told from its headers.
+fn lifetime(headers: &HeaderMap) -> Option<Duration> {
+    let directives = Directives::parse(headers);
+    if directives.no_cache {
+        return Some(Duration::ZERO);
+    }
+    if directives.max_age.is_some() {
+        return directives.max_age;
+    }
+
+    let date = http_date(headers, DATE).unwrap_or_else(SystemTime::now);
+    if headers.contains_key(EXPIRES) {
+        // An invalid date, such as "0", means the response has expired.
+        return Some(
+            http_date(headers, EXPIRES)
+                .and_then(|expires| expires.duration_since(date).ok())
+                .unwrap_or_default(),
+        );
+    }
+
+    // Heuristic freshness, a tenth of the time since the last modification,
+    // up to a day.
+    let last_modified = http_date(headers, LAST_MODIFIED)?;
+    let modified_for = date.duration_since(last_modified).ok()?;
+    Some((modified_for / 10).min(Duration::from_secs(24 * 60 * 60)))
+}
+
+fn http_date(headers: &HeaderMap, name: HeaderName) -> Option<SystemTime> {
+    let value = headers.get(name)?.to_str().ok()?;
+    httpdate::parse_http_date(value).ok()
+}
+
+fn age(headers: &HeaderMap) -> Duration {
+    headers
+        .get(AGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_default()
+}
+
+/// The request headers named by the `Vary` header, `None` for `Vary: *`.
+fn vary(headers: &HeaderMap) -> Option<Vec<HeaderName>> {
+    let mut names = Vec::new();
+    let values = headers
+        .get_all(VARY)
+        .into_iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    for name in values {
+        if name == "*" {
+            return None;
+        }
+        if let Ok(name) = HeaderName::try_from(name) {
+            names.push(name);
+        }
+    }
+    Some(names)
+}
+
+/// Collects `body` if it is no larger than `limit`, or returns a body
+/// yielding the same bytes otherwise.
+async fn collect(
+    mut body: ResponseBody,
+    limit: usize,
+) -> Result<Result<Bytes, ResponseBody>, BoxError> {
+    if body.size_hint().lower() > limit as u64 {
+        return Ok(Err(body));
+    }
+    let mut collected = BytesMut::new();
+    while let Some(frame) = body.frame().await {
+        let Ok(data) = frame?.into_data() else {
+            continue;
+        };
+        collected.extend_from_slice(&data);
+        if collected.len() > limit {
+            let body = Prefixed {
+                prefix: Some(collected.freeze()),
+                rest: body,
+            };
+            return Ok(Err(boxed(body)));
+        }
+    }
+    Ok(Ok(collected.freeze()))
+}
+
+/// A body yielding `prefix`, then what is left of `rest`.
+struct Prefixed {
+    prefix: Option<Bytes>,
+    rest: ResponseBody,
+}
+
+impl http_body::Body for Prefixed {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        if let Some(prefix) = self.prefix.take() {
+            return Poll::Ready(Some(Ok(Frame::data(prefix))));
+        }
+        Pin::new(&mut self.rest).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.prefix.is_none() && self.rest.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let prefix = self.prefix.as_ref().map_or(0, |prefix| prefix.len() as u64);
+        let rest = self.rest.size_hint();
+        let mut hint = SizeHint::new();
+        hint.set_lower(rest.lower() + prefix);
+        if let Some(upper) = rest.upper() {
+            hint.set_upper(upper + prefix);
+        }
+        hint
+    }
+}
@@ -0,0 +1,256 @@
+//! Recording and replaying HTTP interactions
+//!
+//! A cassette is a JSON file holding the responses a client received, so
+//! tests can run again without the network. It is enabled with
+//! [`ClientBuilder::cassette`](crate::ClientBuilder::cassette).
+//!
+//! ```json
+//! {
+//!   "interactions": [
+//!     {
+//!       "request": {
+//!         "method": "GET",
+//!         "url": "https://example.com/",
+//!         "headers": [["accept", "text/html"]]
+//!       },
+//!       "response": {
+//!         "status": 200,
+//!         "version": "HTTP/1.1",
+//!         "headers": [["content-type", "text/html"]],
+//!         "body": "PGh0bWw+"
+//!       }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Bodies are base64 encoded, as received, so compressed responses are
+//! recorded compressed and decoded again on replay. Header values that are
+//! not valid UTF-8 are recorded lossily.
+//!
+//! A request matches an interaction when its method, its URL and the values
+//! of the headers set with
+//! [`ClientBuilder::cassette_match_headers`](crate::ClientBuilder::cassette_match_headers)
+//! are equal to the recorded ones. Only those headers are recorded for the
+//! request, others such as `authorization` never reach the file. When
+//! several interactions match, the first one recorded is replayed.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use antidote::Mutex;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode, Version};
+use http_body_util::{BodyExt, Full};
+use serde::{Deserialize, Serialize};
+
+use crate::client::body::{boxed, ResponseBody};
+use crate::error::BoxError;
+use crate::util::client::InnerRequest;
+
+/// When a cassette records and when it replays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Sends every request and records the responses, replacing what the
+    /// cassette held.
+    Record,
+    /// Replays the recorded responses, failing the requests that match none.
+    Replay,
+    /// Replays the recorded responses and records the responses to the
+    /// requests that match none. The cassette is created if it does not
+    /// exist.
+    RecordOrReplay,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct File {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    version: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+pub(crate) struct Cassette {
+    path: PathBuf,
+    mode: Mode,
+    match_headers: Vec<HeaderName>,
+    interactions: Mutex<Vec<Interaction>>,
+    save: tokio::sync::Mutex<()>,
+}
+
+impl Cassette {
+    /// Opens the cassette at `path`, which must exist to be replayed.
+    pub(crate) fn open(
+        path: PathBuf,
+        mode: Mode,
+        match_headers: Vec<HeaderName>,
+    ) -> crate::Result<Cassette> {
+        let file = match mode {
+            Mode::Record => File::default(),
+            Mode::RecordOrReplay if !path.exists() => File::default(),
+            Mode::Replay | Mode::RecordOrReplay => {
+                let json = std::fs::read(&path).map_err(crate::error::builder)?;
+                serde_json::from_slice(&json).map_err(crate::error::builder)?
+            }
+        };
+        Ok(Cassette {
+            path,
+            mode,
+            match_headers,
+            interactions: Mutex::new(file.interactions),
+            save: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Replays the response to `req`, or calls `send` and records its
+    /// response, depending on the mode.
+    pub(crate) fn call<F, Fut, B, E>(
+        self: &Arc<Self>,
+        req: InnerRequest<crate::Body>,
+        send: F,
+    ) -> Pin<Box<dyn Future<Output = Result<http::Response<ResponseBody>, BoxError>> + Send>>
+    where
+        F: FnOnce(InnerRequest<crate::Body>) -> Fut,
+        Fut: Future<Output = Result<http::Response<B>, E>> + Send + 'static,
+        B: http_body::Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<BoxError>,
+        E: Into<BoxError>,
+    {
+        let recorded = self.record_request(req.request());
+
+        if self.mode != Mode::Record {
+            let replayed = self
+                .interactions
+                .lock()
+                .iter()
+                .find(|interaction| interaction.request == recorded)
+                .map(|interaction| interaction.response.clone());
+            match replayed {
+                Some(response) => return Box::pin(async move { replay(response) }),
+                None if self.mode == Mode::Replay => {
+                    let err = format!(
+                        "no interaction in the cassette matches {} {}",
+                        recorded.method, recorded.url
+                    );
+                    return Box::pin(async move { Err(err.into()) });
+                }
+                None => {}
+            }
+        }
+
+        let sending = send(req);
+        let cassette = self.clone();
+        Box::pin(async move {
+            let res = sending.await.map_err(Into::into)?;
+            let (parts, body) = res.into_parts();
+            let body = body.collect().await.map_err(Into::into)?.to_bytes();
+
+            let response = RecordedResponse {
+                status: parts.status.as_u16(),
+                version: format!("{:?}", parts.version),
+                headers: record_headers(parts.headers.iter()),
+                body: STANDARD.encode(&body),
+            };
+            cassette
+                .record(Interaction {
+                    request: recorded,
+                    response,
+                })
+                .await?;
+
+            Ok(http::Response::from_parts(parts, boxed(Full::new(body))))
+        })
+    }
+
+    fn record_request(&self, req: &http::Request<crate::Body>) -> RecordedRequest {
+        RecordedRequest {
+            method: req.method().to_string(),
+            url: req.uri().to_string(),
+            headers: record_headers(self.match_headers.iter().flat_map(|name| {
+                req.headers()
+                    .get_all(name)
+                    .into_iter()
+                    .map(move |value| (name, value))
+            })),
+        }
+    }
+
+    /// Adds an interaction and writes the cassette.
+    async fn record(&self, interaction: Interaction) -> Result<(), BoxError> {
+        let _save = self.save.lock().await;
+        let json = {
+            let mut interactions = self.interactions.lock();
+            interactions.push(interaction);
+            serde_json::to_vec_pretty(&File {
+                interactions: interactions.clone(),
+            })?
+        };
+        tokio::fs::write(&self.path, json).await?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for Cassette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cassette")
+            .field("path", &self.path)
+            .field("mode", &self.mode)
+            .field("match_headers", &self.match_headers)
+            .finish()
+    }
+}
+
+fn record_headers<'a>(
+    headers: impl Iterator<Item = (&'a HeaderName, &'a HeaderValue)>,
+) -> Vec<(String, String)> {
+    headers
+        .map(|(name, value)| {
+            (
+                name.as_str().to_owned(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+fn replay(response: RecordedResponse) -> Result<http::Response<ResponseBody>, BoxError> {
+    let mut headers = HeaderMap::with_capacity(response.headers.len());
+    for (name, value) in response.headers {
+        headers.append(HeaderName::try_from(name)?, HeaderValue::try_from(value)?);
+    }
+    let version = match response.version.as_str() {
+        "HTTP/0.9" => Version::HTTP_09,
+        "HTTP/1.0" => Version::HTTP_10,
+        "HTTP/2.0" => Version::HTTP_2,
+        "HTTP/3.0" => Version::HTTP_3,
+        _ => Version::HTTP_11,
+    };
+    let body = STANDARD.decode(response.body)?;
+
+    let mut res = http::Response::new(boxed(Full::new(Bytes::from(body))));
+    *res.status_mut() = StatusCode::from_u16(response.status)?;
+    *res.version_mut() = version;
+    *res.headers_mut() = headers;
+    Ok(res)
+}
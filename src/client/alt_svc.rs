@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use http::header::ALT_SVC;
+use http::HeaderMap;
+
+use super::response::link_param;
+
+/// The freshness of an alternative service without an `ma` parameter.
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The alternative services a server advertised in `Alt-Svc` headers
+/// (RFC 7838).
+///
+/// Returned by [`Response::alt_svc`](crate::Response::alt_svc).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AltSvc {
+    clear: bool,
+    services: Vec<AltService>,
+}
+
+/// One alternative service of an `Alt-Svc` header, such as `h3=":443"; ma=3600`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AltService {
+    protocol: String,
+    authority: String,
+    max_age: Duration,
+    persist: bool,
+}
+
+impl AltSvc {
+    /// Parses all `Alt-Svc` headers, skipping alternatives that are malformed.
+    ///
+    /// Returns `None` if there is no valid `Alt-Svc` header.
+    pub(super) fn parse(headers: &HeaderMap) -> Option<AltSvc> {
+        let mut alt_svc = AltSvc {
+            clear: false,
+            services: Vec::new(),
+        };
+
+        for value in headers.get_all(ALT_SVC) {
+            let Ok(mut value) = value.to_str() else {
+                continue;
+            };
+            if value.trim().eq_ignore_ascii_case("clear") {
+                alt_svc.clear = true;
+                continue;
+            }
+
+            loop {
+                let (protocol, authority, rest) = link_param(value);
+                let mut service = AltService {
+                    protocol: percent_encoding::percent_decode_str(protocol)
+                        .decode_utf8_lossy()
+                        .into_owned(),
+                    authority: authority.to_owned(),
+                    max_age: DEFAULT_MAX_AGE,
+                    persist: false,
+                };
+
+                value = rest;
+                while let Some(params) = value.trim_start().strip_prefix(';') {
+                    let (name, param, rest) = link_param(params);
+                    if name.eq_ignore_ascii_case("ma") {
+                        if let Ok(secs) = param.parse() {
+                            service.max_age = Duration::from_secs(secs);
+                        }
+                    } else if name.eq_ignore_ascii_case("persist") {
+                        service.persist = param == "1";
+                    }
+                    value = rest;
+                }
+
+                if !service.protocol.is_empty() && service.port().is_some() {
+                    alt_svc.services.push(service);
+                }
+
+                match value.find(',') {
+                    Some(comma) => value = &value[comma + 1..],
+                    None => break,
+                }
+            }
+        }
+
+        (alt_svc.clear || !alt_svc.services.is_empty()).then_some(alt_svc)
+    }
+
+    /// Returns `true` if the server asked to forget all alternative services
+    /// of its origin, with `Alt-Svc: clear`.
+    pub fn is_clear(&self) -> bool {
+        self.clear
+    }
+
+    /// Returns the advertised alternative services, in order of the server's
+    /// preference.
+    pub fn services(&self) -> &[AltService] {
+        &self.services
+    }
+
+    /// Returns the most preferred alternative service for `protocol`, such as
+    /// `"h3"`.
+    pub fn find(&self, protocol: &str) -> Option<&AltService> {
+        self.services
+            .iter()
+            .find(|service| service.protocol == protocol)
+    }
+}
+
+impl AltService {
+    /// Returns the ALPN protocol id of the alternative service, such as `h3`.
+    pub fn protocol(&self) -> &str {
+        &self.protocol
+    }
+
+    /// Returns the authority of the alternative service, such as `:443` or
+    /// `alt.example.com:443`.
+    ///
+    /// The host is empty when the alternative service is on the same host as
+    /// the origin.
+    pub fn authority(&self) -> &str {
+        &self.authority
+    }
+
+    /// Returns the host of the alternative service, or `None` if it is on the
+    /// same host as the origin.
+    pub fn host(&self) -> Option<&str> {
+        let (host, _) = self.authority.rsplit_once(':')?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        (!host.is_empty()).then_some(host)
+    }
+
+    /// Returns the port of the alternative service.
+    pub fn port(&self) -> Option<u16> {
+        let (_, port) = self.authority.rsplit_once(':')?;
+        port.parse().ok()
+    }
+
+    /// Returns how long the alternative service may be used, the `ma`
+    /// parameter. Defaults to 24 hours.
+    pub fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    /// Returns `true` if the alternative service should survive a network
+    /// change, the `persist=1` parameter.
+    pub fn persist(&self) -> bool {
+        self.persist
+    }
+}
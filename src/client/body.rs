@@ -1,9 +1,13 @@
+use std::collections::VecDeque;
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
 use std::time::Duration;
 
+use antidote::Mutex;
 use bytes::Bytes;
 use http_body::Body as HttpBody;
 use http_body_util::combinators::BoxBody;
@@ -63,6 +67,11 @@ impl Body {
 
     /// Wrap a futures `Stream` in a box inside `Body`.
     ///
+    /// The length of such a body is unknown, so no `Content-Length` header is
+    /// sent: each chunk is sent as it comes, with `Transfer-Encoding: chunked`
+    /// over HTTP/1.1, and as DATA frames over HTTP/2. A `Content-Length` header
+    /// set on the request is sent as is instead.
+    ///
     /// # Example
     ///
     /// ```
@@ -95,6 +104,55 @@ impl Body {
         Body::stream(stream)
     }
 
+    /// Stream a body from an [`AsyncRead`](tokio::io::AsyncRead).
+    ///
+    /// The length is unknown, so HTTP/1.1 requests are sent with
+    /// `Transfer-Encoding: chunked`. Use [`Body::from_reader_with_len`] if the
+    /// length is known up front.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn from_reader<R>(reader: R) -> Body
+    where
+        R: tokio::io::AsyncRead + Send + 'static,
+    {
+        Body::stream(ReaderStream::new(reader))
+    }
+
+    /// Stream a body of exactly `len` bytes from an
+    /// [`AsyncRead`](tokio::io::AsyncRead).
+    ///
+    /// The body reports `len` as its exact size, so it is sent with a
+    /// `Content-Length`. The length is trusted: at most `len` bytes are read,
+    /// even if the reader has more, and the body fails with an
+    /// [`UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) error if the
+    /// reader ends early.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn from_reader_with_len<R>(reader: R, len: u64) -> Body
+    where
+        R: tokio::io::AsyncRead + Send + 'static,
+    {
+        use http_body_util::BodyExt;
+        use tokio::io::AsyncReadExt;
+
+        let body = SizedReaderBody {
+            inner: sync_wrapper::SyncStream::new(ReaderStream::new(reader.take(len))),
+            remaining: len,
+            len,
+        };
+        Body {
+            inner: Inner::Streaming(body.map_err(Into::into).boxed()),
+        }
+    }
+
     #[cfg(any(feature = "stream", feature = "multipart"))]
     pub(crate) fn stream<S>(stream: S) -> Body
     where
@@ -170,6 +228,32 @@ impl Body {
         }
     }
 
+    /// Keeps a copy of a streaming body as it is sent, up to `max` bytes, so
+    /// it can be sent again.
+    ///
+    /// Reusable bodies are returned as they are, without a recording.
+    pub(crate) fn record(self, max: usize) -> (Body, Option<Arc<Mutex<Recording>>>) {
+        use http_body_util::BodyExt;
+
+        match self.inner {
+            Inner::Reusable(_) => (self, None),
+            Inner::Streaming(inner) => {
+                let recording = Arc::new(Mutex::new(Recording {
+                    chunks: Some(Vec::new()),
+                    len: 0,
+                    max,
+                    complete: false,
+                }));
+                let body = RecordBody {
+                    inner,
+                    recording: recording.clone(),
+                }
+                .boxed();
+                (body.into(), Some(recording))
+            }
+        }
+    }
+
     #[cfg(feature = "multipart")]
     pub(crate) fn into_stream(self) -> DataStream<Body> {
         DataStream(self)
@@ -244,6 +328,55 @@ impl From<File> for Body {
     }
 }
 
+#[cfg(feature = "stream")]
+pin_project! {
+    struct SizedReaderBody<R> {
+        #[pin]
+        inner: sync_wrapper::SyncStream<ReaderStream<tokio::io::Take<R>>>,
+        remaining: u64,
+        len: u64,
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<R> HttpBody for SizedReaderBody<R>
+where
+    R: tokio::io::AsyncRead,
+{
+    type Data = Bytes;
+    type Error = std::io::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<hyper2::body::Frame<Self::Data>, Self::Error>>> {
+        use futures_util::Stream;
+
+        let this = self.project();
+        match futures_util::ready!(this.inner.poll_next(cx)) {
+            Some(Ok(chunk)) => {
+                *this.remaining -= chunk.len() as u64;
+                Poll::Ready(Some(Ok(hyper2::body::Frame::data(chunk))))
+            }
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None if *this.remaining > 0 => {
+                let read = *this.len - *this.remaining;
+                let err = std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    format!("body reader ended after {read} of {} bytes", this.len),
+                );
+                *this.remaining = 0;
+                Poll::Ready(Some(Err(err)))
+            }
+            None => Poll::Ready(None),
+        }
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        http_body::SizeHint::with_exact(self.remaining)
+    }
+}
+
 impl fmt::Debug for Body {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Body").finish()
@@ -419,6 +552,224 @@ where
     }
 }
 
+// ===== impl CountingBody =====
+
+/// Counts the bytes of the data frames read from a response body, before
+/// they are decoded.
+pub(crate) fn counting(body: ResponseBody, read: Arc<AtomicU64>) -> ResponseBody {
+    use http_body_util::BodyExt;
+
+    CountingBody { body, read }.boxed()
+}
+
+struct CountingBody {
+    body: ResponseBody,
+    read: Arc<AtomicU64>,
+}
+
+impl hyper2::body::Body for CountingBody {
+    type Data = Bytes;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<hyper2::body::Frame<Self::Data>, Self::Error>>> {
+        let frame = futures_util::ready!(Pin::new(&mut self.body).poll_frame(cx));
+        if let Some(data) = frame
+            .as_ref()
+            .and_then(|frame| frame.as_ref().ok())
+            .and_then(|frame| frame.data_ref())
+        {
+            self.read.fetch_add(data.len() as u64, Ordering::Relaxed);
+        }
+        Poll::Ready(frame)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.body.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+}
+
+// ===== impl BufferedBody =====
+
+/// Reads a response body ahead of its consumer, in a background task.
+///
+/// Reading stops once `high` bytes are buffered, applying backpressure on
+/// the transport, and resumes once the consumer drains the buffer down to
+/// `low` bytes.
+pub(crate) fn buffered(body: ResponseBody, low: usize, high: usize) -> ResponseBody {
+    use http_body_util::BodyExt;
+
+    let shared = Arc::new(Mutex::new(Buffer {
+        frames: VecDeque::new(),
+        buffered: 0,
+        remaining: body.size_hint(),
+        end_stream: body.is_end_stream(),
+        paused: false,
+        done: false,
+        closed: false,
+        consumer: None,
+        producer: None,
+    }));
+    tokio::spawn(Fill {
+        body,
+        low,
+        high,
+        shared: shared.clone(),
+    });
+    BufferedBody { low, shared }.boxed()
+}
+
+type BufferedFrame = Result<hyper2::body::Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>;
+
+struct Buffer {
+    frames: VecDeque<BufferedFrame>,
+    buffered: usize,
+    /// The size hint of what is left to read from the body.
+    remaining: http_body::SizeHint,
+    /// Whether nothing is left to read from the body.
+    end_stream: bool,
+    paused: bool,
+    done: bool,
+    closed: bool,
+    consumer: Option<Waker>,
+    producer: Option<Waker>,
+}
+
+struct Fill {
+    body: ResponseBody,
+    low: usize,
+    high: usize,
+    shared: Arc<Mutex<Buffer>>,
+}
+
+struct BufferedBody {
+    low: usize,
+    shared: Arc<Mutex<Buffer>>,
+}
+
+fn frame_len(frame: &BufferedFrame) -> usize {
+    frame
+        .as_ref()
+        .ok()
+        .and_then(|frame| frame.data_ref())
+        .map_or(0, Bytes::len)
+}
+
+impl Future for Fill {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        loop {
+            {
+                let mut buf = self.shared.lock();
+                if buf.closed {
+                    return Poll::Ready(());
+                }
+                if buf.paused {
+                    if buf.buffered > self.low {
+                        buf.producer = Some(cx.waker().clone());
+                        return Poll::Pending;
+                    }
+                    buf.paused = false;
+                }
+            }
+
+            let item = match Pin::new(&mut self.body).poll_frame(cx) {
+                Poll::Ready(item) => item,
+                Poll::Pending => {
+                    // be woken up if the consumer goes away in the meantime
+                    let mut buf = self.shared.lock();
+                    if buf.closed {
+                        return Poll::Ready(());
+                    }
+                    buf.producer = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            };
+            let mut buf = self.shared.lock();
+            buf.remaining = self.body.size_hint();
+            buf.end_stream = self.body.is_end_stream();
+            match item {
+                Some(frame) => {
+                    buf.done = frame.is_err();
+                    buf.buffered += frame_len(&frame);
+                    buf.paused = buf.buffered >= self.high;
+                    buf.frames.push_back(frame);
+                }
+                None => buf.done = true,
+            }
+            if let Some(waker) = buf.consumer.take() {
+                waker.wake();
+            }
+            if buf.done {
+                return Poll::Ready(());
+            }
+        }
+    }
+}
+
+impl hyper2::body::Body for BufferedBody {
+    type Data = Bytes;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<hyper2::body::Frame<Self::Data>, Self::Error>>> {
+        let mut buf = self.shared.lock();
+        match buf.frames.pop_front() {
+            Some(frame) => {
+                buf.buffered -= frame_len(&frame);
+                if buf.paused && buf.buffered <= self.low {
+                    if let Some(waker) = buf.producer.take() {
+                        waker.wake();
+                    }
+                }
+                Poll::Ready(Some(frame))
+            }
+            None if buf.done => Poll::Ready(None),
+            None => {
+                buf.consumer = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        let buf = self.shared.lock();
+        buf.frames.is_empty() && (buf.done || buf.end_stream)
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        let buf = self.shared.lock();
+        let buffered = buf.buffered as u64;
+        let mut hint = http_body::SizeHint::new();
+        hint.set_lower(buf.remaining.lower() + buffered);
+        if let Some(upper) = buf.remaining.upper() {
+            hint.set_upper(upper + buffered);
+        }
+        hint
+    }
+}
+
+impl Drop for BufferedBody {
+    fn drop(&mut self) {
+        let mut buf = self.shared.lock();
+        buf.closed = true;
+        if let Some(waker) = buf.producer.take() {
+            waker.wake();
+        }
+    }
+}
+
 fn box_err<E>(err: E) -> Box<dyn std::error::Error + Send + Sync>
 where
     E: Into<Box<dyn std::error::Error + Send + Sync>>,
@@ -453,6 +804,85 @@ where
     }
 }
 
+// ===== impl Recording =====
+
+/// The copy of a streaming body kept by [`Body::record`].
+#[derive(Debug)]
+pub(crate) struct Recording {
+    /// `None` once the body went over `max`.
+    chunks: Option<Vec<Bytes>>,
+    len: usize,
+    max: usize,
+    complete: bool,
+}
+
+impl Recording {
+    /// Returns the whole body, if it was sent to the end and fit in `max`.
+    pub(crate) fn replay(&self) -> Option<Bytes> {
+        if !self.complete {
+            return None;
+        }
+        let chunks = self.chunks.as_ref()?;
+        let mut body = bytes::BytesMut::with_capacity(self.len);
+        for chunk in chunks {
+            body.extend_from_slice(chunk);
+        }
+        Some(body.freeze())
+    }
+}
+
+pin_project! {
+    struct RecordBody<B> {
+        #[pin]
+        inner: B,
+        recording: Arc<Mutex<Recording>>,
+    }
+}
+
+impl<B> hyper2::body::Body for RecordBody<B>
+where
+    B: hyper2::body::Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<hyper2::body::Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        let frame = futures_util::ready!(this.inner.as_mut().poll_frame(cx));
+        let mut recording = this.recording.lock();
+        match frame {
+            Some(Ok(ref frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    recording.len += data.len();
+                    if recording.len > recording.max {
+                        recording.chunks = None;
+                    } else if let Some(chunks) = recording.chunks.as_mut() {
+                        chunks.push(data.clone());
+                    }
+                }
+                // the end may not be polled for once it is known
+                recording.complete = this.inner.is_end_stream();
+            }
+            Some(Err(_)) => recording.chunks = None,
+            None => recording.complete = true,
+        }
+        Poll::Ready(frame)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
 // ===== impl IntoBytesBody =====
 pin_project! {
     struct IntoBytesBody<B> {
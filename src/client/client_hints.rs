@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use antidote::Mutex;
+use http::header::{HeaderName, HeaderValue};
+use http::HeaderMap;
+use url::{Host, Url};
+
+const ACCEPT_CH: HeaderName = HeaderName::from_static("accept-ch");
+const SEC_CH_UA: HeaderName = HeaderName::from_static("sec-ch-ua");
+const SEC_CH_UA_MOBILE: HeaderName = HeaderName::from_static("sec-ch-ua-mobile");
+const SEC_CH_UA_PLATFORM: HeaderName = HeaderName::from_static("sec-ch-ua-platform");
+
+/// The client hints each origin asked for in its last `Accept-CH` header.
+#[derive(Debug, Default)]
+pub(crate) struct ClientHints {
+    origins: Mutex<HashMap<String, Vec<HeaderName>>>,
+}
+
+impl ClientHints {
+    /// Remembers the hints requested by the `Accept-CH` headers of a response
+    /// from `url`, replacing those its origin asked for before.
+    ///
+    /// Like browsers, only secure origins, and `localhost`, are listened to.
+    pub(crate) fn store(&self, url: &Url, headers: &HeaderMap) {
+        if !headers.contains_key(ACCEPT_CH) || !is_secure(url) {
+            return;
+        }
+
+        let mut hints = Vec::new();
+        for value in headers.get_all(ACCEPT_CH) {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+            for hint in value.split(',') {
+                if let Ok(name) = HeaderName::from_bytes(hint.trim().as_bytes()) {
+                    if !hints.contains(&name) {
+                        hints.push(name);
+                    }
+                }
+            }
+        }
+
+        let origin = url.origin().ascii_serialization();
+        let mut origins = self.origins.lock();
+        if hints.is_empty() {
+            origins.remove(&origin);
+        } else {
+            origins.insert(origin, hints);
+        }
+    }
+
+    /// Adds the high-entropy hints the origin of `url` asked for to `headers`,
+    /// without overwriting those already set, and returns those it added.
+    ///
+    /// The hints are derived from the `sec-ch-ua` header, so nothing is added
+    /// for browsers that do not send it.
+    pub(crate) fn apply(&self, url: &Url, headers: &mut HeaderMap) -> Vec<HeaderName> {
+        let mut added = Vec::new();
+        if !headers.contains_key(SEC_CH_UA) || !is_secure(url) {
+            return added;
+        }
+
+        let origin = url.origin().ascii_serialization();
+        let origins = self.origins.lock();
+        let Some(hints) = origins.get(&origin) else {
+            return added;
+        };
+
+        for hint in hints {
+            if headers.contains_key(hint) {
+                continue;
+            }
+            if let Some(value) = hint_value(hint.as_str(), headers) {
+                headers.insert(hint.clone(), value);
+                added.push(hint.clone());
+            }
+        }
+        added
+    }
+
+    /// Removes the hints [`apply`](Self::apply) added from `headers`, before
+    /// they are added again for the next URL of a redirect.
+    pub(crate) fn clear(headers: &mut HeaderMap, added: &[HeaderName]) {
+        for hint in added {
+            headers.remove(hint);
+        }
+    }
+}
+
+/// Returns the value of a high-entropy hint, consistent with the low-entropy
+/// hints in `headers`.
+///
+/// The full versions and the platform version are not known from any
+/// profile, so they are never sent.
+fn hint_value(hint: &str, headers: &HeaderMap) -> Option<HeaderValue> {
+    let platform = headers
+        .get(SEC_CH_UA_PLATFORM)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("\"\"");
+    let mobile = headers.get(SEC_CH_UA_MOBILE).map(HeaderValue::as_bytes) == Some(b"?1");
+
+    let value = match hint {
+        "sec-ch-ua-arch" => match platform {
+            _ if mobile => "\"\"",
+            "\"macOS\"" => "\"arm\"",
+            _ => "\"x86\"",
+        }
+        .to_owned(),
+        "sec-ch-ua-bitness" if mobile => "\"\"".to_owned(),
+        "sec-ch-ua-bitness" => "\"64\"".to_owned(),
+        "sec-ch-ua-form-factors" if mobile => "\"Mobile\"".to_owned(),
+        "sec-ch-ua-form-factors" => "\"Desktop\"".to_owned(),
+        "sec-ch-ua-model" => "\"\"".to_owned(),
+        "sec-ch-ua-wow64" => "?0".to_owned(),
+        _ => return None,
+    };
+    HeaderValue::try_from(value).ok()
+}
+
+fn is_secure(url: &Url) -> bool {
+    match url.host() {
+        _ if url.scheme() == "https" => true,
+        Some(Host::Domain(domain)) => domain == "localhost" || domain.ends_with(".localhost"),
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
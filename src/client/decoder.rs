@@ -76,6 +76,8 @@ pub(super) struct Accepts {
 /// The inner decoder may be constructed asynchronously.
 pub(crate) struct Decoder {
     inner: Inner,
+    /// How many more decoded bytes may be read, if the body size is limited.
+    remaining: Option<usize>,
 }
 
 #[cfg(any(
@@ -172,9 +174,16 @@ impl Decoder {
     fn plain_text(body: ResponseBody) -> Decoder {
         Decoder {
             inner: Inner::PlainText(body),
+            remaining: None,
         }
     }
 
+    /// Limits the size of the decoded body to `limit` bytes, see
+    /// [`RequestBuilder::bytes_limit`](crate::RequestBuilder::bytes_limit).
+    pub(super) fn set_bytes_limit(&mut self, limit: Option<usize>) {
+        self.remaining = limit;
+    }
+
     /// A gzip decoder.
     ///
     /// This decoder will buffer and decompress chunks that are gzipped.
@@ -187,6 +196,7 @@ impl Decoder {
                 IoStream(body).peekable(),
                 DecoderType::Gzip,
             ))),
+            remaining: None,
         }
     }
 
@@ -202,6 +212,7 @@ impl Decoder {
                 IoStream(body).peekable(),
                 DecoderType::Brotli,
             ))),
+            remaining: None,
         }
     }
 
@@ -217,6 +228,7 @@ impl Decoder {
                 IoStream(body).peekable(),
                 DecoderType::Zstd,
             ))),
+            remaining: None,
         }
     }
 
@@ -232,6 +244,7 @@ impl Decoder {
                 IoStream(body).peekable(),
                 DecoderType::Deflate,
             ))),
+            remaining: None,
         }
     }
 
@@ -246,14 +259,8 @@ impl Decoder {
         use log::warn;
 
         let mut is_content_encoded = {
-            headers
-                .get_all(CONTENT_ENCODING)
-                .iter()
-                .any(|enc| enc == encoding_str)
-                || headers
-                    .get_all(TRANSFER_ENCODING)
-                    .iter()
-                    .any(|enc| enc == encoding_str)
+            is_coded_with(headers.get_all(CONTENT_ENCODING), encoding_str)
+                || is_coded_with(headers.get_all(TRANSFER_ENCODING), encoding_str)
         };
         if is_content_encoded {
             if let Some(content_length) = headers.get(CONTENT_LENGTH) {
@@ -277,50 +284,83 @@ impl Decoder {
     ///
     /// Uses the correct variant by inspecting the Content-Encoding header.
     pub(super) fn detect(
-        _headers: &mut HeaderMap,
+        headers: &mut HeaderMap,
         body: ResponseBody,
         _accepts: Accepts,
     ) -> Decoder {
         #[cfg(feature = "gzip")]
         {
-            if _accepts.gzip && Decoder::detect_encoding(_headers, "gzip") {
+            if _accepts.gzip && Decoder::detect_encoding(headers, "gzip") {
                 return Decoder::gzip(body);
             }
         }
 
         #[cfg(feature = "brotli")]
         {
-            if _accepts.brotli && Decoder::detect_encoding(_headers, "br") {
+            if _accepts.brotli && Decoder::detect_encoding(headers, "br") {
                 return Decoder::brotli(body);
             }
         }
 
         #[cfg(feature = "zstd")]
         {
-            if _accepts.zstd && Decoder::detect_encoding(_headers, "zstd") {
+            if _accepts.zstd && Decoder::detect_encoding(headers, "zstd") {
                 return Decoder::zstd(body);
             }
         }
 
         #[cfg(feature = "deflate")]
         {
-            if _accepts.deflate && Decoder::detect_encoding(_headers, "deflate") {
+            if _accepts.deflate && Decoder::detect_encoding(headers, "deflate") {
                 return Decoder::deflate(body);
             }
         }
 
+        if let Some(encoding) = headers.get(http::header::CONTENT_ENCODING) {
+            if encoding != "identity" {
+                log::debug!(
+                    "content-encoding {:?} not decoded, passing through the raw body",
+                    encoding
+                );
+            }
+        }
+
         Decoder::plain_text(body)
     }
 }
 
-impl HttpBody for Decoder {
-    type Data = Bytes;
-    type Error = crate::Error;
+/// Whether `values` apply `coding` alone, ignoring case, whitespace,
+/// parameters, and the `identity` and `chunked` codings.
+///
+/// Several codings, such as `gzip, br`, are left undecoded.
+#[cfg(any(
+    feature = "brotli",
+    feature = "zstd",
+    feature = "gzip",
+    feature = "deflate"
+))]
+fn is_coded_with(values: http::header::GetAll<'_, http::HeaderValue>, coding: &str) -> bool {
+    let mut codings = values
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|token| token.split(';').next().unwrap_or_default().trim())
+        .filter(|token| {
+            !token.is_empty()
+                && !token.eq_ignore_ascii_case("identity")
+                && !token.eq_ignore_ascii_case("chunked")
+        });
+    matches!(
+        (codings.next(), codings.next()),
+        (Some(token), None) if token.eq_ignore_ascii_case(coding)
+    )
+}
 
-    fn poll_frame(
+impl Decoder {
+    fn poll_decoded(
         mut self: Pin<&mut Self>,
         cx: &mut Context,
-    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+    ) -> Poll<Option<Result<Frame<Bytes>, crate::Error>>> {
         match self.inner {
             #[cfg(any(
                 feature = "brotli",
@@ -331,7 +371,7 @@ impl HttpBody for Decoder {
             Inner::Pending(ref mut future) => match Pin::new(future).poll(cx) {
                 Poll::Ready(Ok(inner)) => {
                     self.inner = inner;
-                    self.poll_frame(cx)
+                    self.poll_decoded(cx)
                 }
                 Poll::Ready(Err(e)) => Poll::Ready(Some(Err(crate::error::decode_io(e)))),
                 Poll::Pending => Poll::Pending,
@@ -401,6 +441,37 @@ impl HttpBody for Decoder {
             }
         }
     }
+}
+
+impl HttpBody for Decoder {
+    type Data = Bytes;
+    type Error = crate::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let Some(remaining) = self.remaining else {
+            return self.poll_decoded(cx);
+        };
+
+        // Fail before reading a body announced to be too large.
+        if self.size_hint().lower() > remaining as u64 {
+            return Poll::Ready(Some(Err(crate::error::body(crate::error::BodyTooLarge))));
+        }
+
+        let frame = futures_util::ready!(self.as_mut().poll_decoded(cx));
+        if let Some(Ok(ref frame)) = frame {
+            let len = frame.data_ref().map_or(0, Bytes::len);
+            match remaining.checked_sub(len) {
+                Some(remaining) => self.remaining = Some(remaining),
+                None => {
+                    return Poll::Ready(Some(Err(crate::error::body(crate::error::BodyTooLarge))))
+                }
+            }
+        }
+        Poll::Ready(frame)
+    }
 
     fn size_hint(&self) -> http_body::SizeHint {
         match self.inner {
@@ -0,0 +1,113 @@
+//! Request body compression.
+
+use std::io;
+
+#[cfg(feature = "brotli")]
+use async_compression::tokio::bufread::BrotliEncoder;
+#[cfg(feature = "gzip")]
+use async_compression::tokio::bufread::GzipEncoder;
+#[cfg(feature = "deflate")]
+use async_compression::tokio::bufread::ZlibEncoder;
+#[cfg(feature = "zstd")]
+use async_compression::tokio::bufread::ZstdEncoder;
+use futures_util::TryStreamExt;
+use http::HeaderValue;
+use http_body_util::{BodyExt, StreamBody};
+use hyper2::body::Frame;
+use tokio::io::AsyncRead;
+use tokio_util::io::{ReaderStream, StreamReader};
+
+use super::body::Body;
+
+/// Bodies shorter than this are sent uncompressed, the encoding overhead
+/// outweighs the savings.
+pub(super) const MIN_COMPRESS_LEN: u64 = 1024;
+
+/// A content coding used to compress request bodies.
+///
+/// See [`RequestBuilder::body_compressed`](crate::RequestBuilder::body_compressed).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContentEncoding {
+    /// `gzip`
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// `br`
+    #[cfg(feature = "brotli")]
+    Brotli,
+    /// `zstd`
+    #[cfg(feature = "zstd")]
+    Zstd,
+    /// `deflate`, as a zlib stream.
+    #[cfg(feature = "deflate")]
+    Deflate,
+}
+
+impl ContentEncoding {
+    pub(super) fn header_value(self) -> HeaderValue {
+        HeaderValue::from_static(match self {
+            #[cfg(feature = "gzip")]
+            ContentEncoding::Gzip => "gzip",
+            #[cfg(feature = "brotli")]
+            ContentEncoding::Brotli => "br",
+            #[cfg(feature = "zstd")]
+            ContentEncoding::Zstd => "zstd",
+            #[cfg(feature = "deflate")]
+            ContentEncoding::Deflate => "deflate",
+        })
+    }
+
+    /// Compresses `body` as it is streamed, without buffering it.
+    pub(super) fn encode(self, body: Body) -> Body {
+        let reader = StreamReader::new(
+            body.into_data_stream()
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err)),
+        );
+        match self {
+            #[cfg(feature = "gzip")]
+            ContentEncoding::Gzip => encoded(GzipEncoder::new(reader)),
+            #[cfg(feature = "brotli")]
+            ContentEncoding::Brotli => encoded(BrotliEncoder::new(reader)),
+            #[cfg(feature = "zstd")]
+            ContentEncoding::Zstd => encoded(ZstdEncoder::new(reader)),
+            #[cfg(feature = "deflate")]
+            ContentEncoding::Deflate => encoded(ZlibEncoder::new(reader)),
+        }
+    }
+}
+
+fn encoded<R>(encoder: R) -> Body
+where
+    R: AsyncRead + Send + 'static,
+{
+    let stream = ReaderStream::new(encoder).map_ok(Frame::data);
+    Body::wrap(StreamBody::new(sync_wrapper::SyncStream::new(stream)))
+}
+
+/// Whether a body of this media type is already compressed, so compressing it
+/// again would only cost time.
+pub(super) fn is_compressed_media_type(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match essence.split_once('/') {
+        Some(("image", subtype)) => subtype != "svg+xml" && subtype != "bmp",
+        Some(("audio" | "video", _)) => true,
+        Some(("font", subtype)) => subtype == "woff" || subtype == "woff2",
+        Some(("application", subtype)) => matches!(
+            subtype,
+            "zip"
+                | "gzip"
+                | "x-gzip"
+                | "zstd"
+                | "x-bzip2"
+                | "x-xz"
+                | "x-7z-compressed"
+                | "vnd.rar"
+                | "x-rar-compressed"
+        ),
+        _ => false,
+    }
+}
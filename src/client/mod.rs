@@ -1,14 +1,38 @@
+pub use self::alt_svc::{AltService, AltSvc};
 pub use self::body::Body;
-pub use self::http::{Client, ClientBuilder, ClientMut};
-pub use self::request::{Request, RequestBuilder};
+#[cfg(any(
+    feature = "gzip",
+    feature = "brotli",
+    feature = "zstd",
+    feature = "deflate"
+))]
+pub use self::encoder::ContentEncoding;
+pub use self::http::{Client, ClientBuilder, ClientMut, DefaultHeadersMode};
+pub use self::paginate::Paginate;
+pub use self::pinned::PinnedConnection;
+pub use self::request::{FetchMode, ReloadKind, Request, RequestBuilder};
 pub use self::response::Response;
+#[cfg(feature = "stream")]
+pub use self::response::SaveOptions;
 pub use self::upgrade::Upgraded;
 
+mod alt_svc;
 pub mod body;
+mod client_hints;
 pub mod decoder;
+#[cfg(any(
+    feature = "gzip",
+    feature = "brotli",
+    feature = "zstd",
+    feature = "deflate"
+))]
+mod encoder;
 pub mod http;
 #[cfg(feature = "multipart")]
 pub mod multipart;
+mod paginate;
+mod pinned;
+mod rate_limit;
 pub(crate) mod request;
 mod response;
 mod upgrade;
@@ -0,0 +1,114 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_util::Stream;
+use http::Method;
+use tokio::time::Sleep;
+use url::Url;
+
+use super::http::{Client, Pending};
+use super::request::Request;
+use super::response::Response;
+
+/// A stream of pages, following `Link: <...>; rel="next"` headers.
+///
+/// Created by [`Client::paginate`]. Each page is requested with `GET` and yielded
+/// as soon as its headers arrive. The stream ends after a page without a `next`
+/// link, or after the first error.
+#[must_use = "streams do nothing unless polled"]
+pub struct Paginate {
+    client: Client,
+    delay: Option<Duration>,
+    state: State,
+}
+
+enum State {
+    Next(Url),
+    Waiting(Pin<Box<Sleep>>, Url),
+    Sending(Pin<Box<Pending>>),
+    Error(crate::Error),
+    Done,
+}
+
+impl Paginate {
+    pub(super) fn new(client: Client, url: crate::Result<Url>) -> Paginate {
+        let state = match url {
+            Ok(url) => State::Next(url),
+            Err(err) => State::Error(err),
+        };
+        Paginate {
+            client,
+            delay: None,
+            state,
+        }
+    }
+
+    /// Wait for `delay` before requesting each page after the first.
+    ///
+    /// Useful to stay under an API's rate limit.
+    pub fn delay(mut self, delay: Duration) -> Paginate {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+impl Stream for Paginate {
+    type Item = crate::Result<Response>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            match std::mem::replace(&mut this.state, State::Done) {
+                State::Next(url) => {
+                    this.state = State::Sending(Box::pin(
+                        this.client.execute_request(Request::new(Method::GET, url)),
+                    ));
+                }
+                State::Waiting(mut sleep, url) => {
+                    if sleep.as_mut().poll(cx).is_pending() {
+                        this.state = State::Waiting(sleep, url);
+                        return Poll::Pending;
+                    }
+                    this.state = State::Next(url);
+                }
+                State::Sending(mut pending) => {
+                    let res = match pending.as_mut().poll(cx) {
+                        Poll::Ready(res) => res,
+                        Poll::Pending => {
+                            this.state = State::Sending(pending);
+                            return Poll::Pending;
+                        }
+                    };
+
+                    // A page linking to itself would never end.
+                    let next = res
+                        .as_ref()
+                        .ok()
+                        .and_then(|res| res.next_page_url().filter(|next| next != res.url()));
+                    if let Some(next) = next {
+                        this.state = match this.delay {
+                            Some(delay) => {
+                                State::Waiting(Box::pin(tokio::time::sleep(delay)), next)
+                            }
+                            None => State::Next(next),
+                        };
+                    }
+                    return Poll::Ready(Some(res));
+                }
+                State::Error(err) => return Poll::Ready(Some(Err(err))),
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Paginate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Paginate")
+            .field("delay", &self.delay)
+            .finish()
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+use std::future::Future;
+
+use http::Method;
+
+use super::body::Body;
+use super::http::Client;
+use super::request::{Request, RequestBuilder};
+use super::response::Response;
+use crate::util::client::PinnedConnection as Conn;
+use crate::IntoUrl;
+
+/// A connection that consecutive requests are sent on, one after another.
+///
+/// Created by [`Client::acquire_connection`]. Requests to another origin, such as
+/// redirects elsewhere, are sent on pooled connections as usual. If the connection
+/// is closed, requests fail instead of opening a new one.
+///
+/// Over HTTP/1, the connection is taken out of the pool, so no other request uses
+/// it while it is pinned, and goes back to the pool when the `PinnedConnection` is
+/// dropped. A request waits for the response body of the one before it to be read
+/// to the end.
+///
+/// Over HTTP/2, the connection stays shared: other requests of the client to the
+/// same origin are still multiplexed on it, alongside those sent here.
+pub struct PinnedConnection {
+    client: Client,
+    pinned: Pinned,
+}
+
+/// The connection a request is pinned to, with the profile it was opened with.
+#[derive(Clone)]
+pub(crate) struct Pinned {
+    pub(super) conn: Conn<Body>,
+    pub(super) profile: Option<usize>,
+}
+
+impl PinnedConnection {
+    pub(super) fn new(client: Client, pinned: Pinned) -> PinnedConnection {
+        PinnedConnection { client, pinned }
+    }
+
+    /// Convenience method to make a `GET` request to a URL on this connection.
+    pub fn get<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    /// Convenience method to make a `POST` request to a URL on this connection.
+    pub fn post<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    /// Start building a `Request` with the `Method` and `Url`, sent on this connection.
+    ///
+    /// The request is otherwise built and sent like one from [`Client::request`].
+    pub fn request<U: IntoUrl>(&self, method: Method, url: U) -> RequestBuilder {
+        let mut builder = self.client.request(method, url);
+        if let Some(req) = builder.request_mut() {
+            *req.pinned_mut() = Some(self.pinned.clone());
+        }
+        builder
+    }
+
+    /// Executes a `Request` on this connection.
+    pub fn execute(
+        &self,
+        mut request: Request,
+    ) -> impl Future<Output = Result<Response, crate::Error>> {
+        *request.pinned_mut() = Some(self.pinned.clone());
+        self.client.execute_request(request)
+    }
+}
+
+impl fmt::Debug for PinnedConnection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PinnedConnection").finish()
+    }
+}
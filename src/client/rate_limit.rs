@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use antidote::Mutex;
+use url::Url;
+
+use crate::error::RateLimited;
+
+/// A token bucket limiting the rate at which a client sends requests, see
+/// [`ClientBuilder::rate_limit`](super::ClientBuilder::rate_limit).
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// The tokens added per second.
+    rate: f64,
+    /// The most tokens a bucket holds.
+    burst: f64,
+    per_host: bool,
+    /// The most requests that may wait for a token of a bucket.
+    max_queue: Option<usize>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant, rate: f64, burst: f64) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(burst);
+        self.updated = now;
+    }
+}
+
+impl RateLimiter {
+    pub(crate) fn new(requests_per_sec: u32, burst: u32, per_host: bool) -> RateLimiter {
+        RateLimiter {
+            rate: f64::from(requests_per_sec),
+            burst: f64::from(burst),
+            per_host,
+            max_queue: None,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn max_queue(mut self, max_queue: Option<usize>) -> RateLimiter {
+        self.max_queue = max_queue;
+        self
+    }
+
+    /// Takes a token for a request to `url`, returning how long the request
+    /// has to wait for it, if at all.
+    ///
+    /// Tokens are handed out in the order they are asked for, so a request
+    /// may take a token that is yet to be added, and waits until it is. Fails
+    /// if that would queue more requests than `max_queue`.
+    pub(crate) fn reserve(&self, url: &Url) -> Result<Option<Duration>, RateLimited> {
+        let key = if self.per_host {
+            format!(
+                "{}:{}",
+                url.host_str().unwrap_or_default(),
+                url.port_or_known_default().unwrap_or_default()
+            )
+        } else {
+            String::new()
+        };
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock();
+        if !buckets.contains_key(&key) {
+            // forget the hosts that have been idle for long enough to be full again
+            buckets.retain(|_, bucket| {
+                bucket.refill(now, self.rate, self.burst);
+                bucket.tokens < self.burst
+            });
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        bucket.refill(now, self.rate, self.burst);
+        if let Some(max_queue) = self.max_queue {
+            // every missing token is a request waiting for it
+            if bucket.tokens - 1.0 < -(max_queue as f64) {
+                return Err(RateLimited);
+            }
+        }
+        bucket.tokens -= 1.0;
+
+        Ok((bucket.tokens < 0.0).then(|| Duration::from_secs_f64(-bucket.tokens / self.rate)))
+    }
+}
@@ -1,6 +1,8 @@
+use std::borrow::Cow;
 use std::convert::TryFrom;
 use std::fmt;
 use std::future::Future;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::time::Duration;
 
@@ -11,11 +13,17 @@ use super::body::Body;
 use super::http::{Client, Pending};
 #[cfg(feature = "multipart")]
 use super::multipart;
+use super::pinned::Pinned;
 use super::response::Response;
 #[cfg(feature = "cookies")]
 use crate::cookie;
-use crate::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
-use crate::util::client::{NetworkScheme, NetworkSchemeBuilder};
+use crate::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT, CACHE_CONTROL, CONTENT_TYPE, PRAGMA,
+    UPGRADE_INSECURE_REQUESTS,
+};
+use crate::util::client::{
+    ConnectTo, NetworkScheme, NetworkSchemeBuilder, RequestTarget, ServerName,
+};
 use crate::{cfg_bindable_device, redirect, IntoUrl, Method, Proxy, Url};
 #[cfg(feature = "cookies")]
 use std::sync::Arc;
@@ -32,6 +40,9 @@ type PiecesWithCookieStore = (
     Option<redirect::Policy>,
     (),
     NetworkScheme,
+    Vec<HeaderName>,
+    Option<usize>,
+    Option<Cow<'static, [HeaderName]>>,
 );
 
 #[cfg(feature = "cookies")]
@@ -46,6 +57,9 @@ type PiecesWithCookieStore = (
     Option<redirect::Policy>,
     Option<Arc<dyn cookie::CookieStore>>,
     NetworkScheme,
+    Vec<HeaderName>,
+    Option<usize>,
+    Option<Cow<'static, [HeaderName]>>,
 );
 
 /// A request which can be executed with `Client::execute()`.
@@ -56,11 +70,49 @@ pub struct Request {
     body: Option<Body>,
     timeout: Option<Duration>,
     read_timeout: Option<Duration>,
+    bytes_limit: Option<usize>,
+    decompress: bool,
+    hedge: Option<Duration>,
     version: Option<Version>,
     redirect: Option<redirect::Policy>,
     #[cfg(feature = "cookies")]
     cookie_store: Option<Arc<dyn cookie::CookieStore>>,
     network_scheme: NetworkSchemeBuilder,
+    removed_headers: Vec<HeaderName>,
+    headers_order: Option<Cow<'static, [HeaderName]>>,
+    pinned: Option<Pinned>,
+    pool_tag: Option<u64>,
+    server_name: Option<ServerName>,
+    connect_to: Option<ConnectTo>,
+    request_target: Option<RequestTarget>,
+}
+
+/// The kind of page load a browser performs, see [`RequestBuilder::reload`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReloadKind {
+    /// A regular navigation, which sends no cache directives.
+    Normal,
+    /// A reload, which revalidates cached responses with
+    /// `cache-control: max-age=0`.
+    Reload,
+    /// A hard reload, which bypasses caches with `pragma: no-cache` and
+    /// `cache-control: no-cache`.
+    HardReload,
+}
+
+/// The mode of a request, as sent in the `sec-fetch-mode` header, see
+/// [`RequestBuilder::fetch_mode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FetchMode {
+    /// A top-level navigation, such as following a link or typing a URL.
+    Navigate,
+    /// A cross-origin `fetch()` or XHR request that uses CORS.
+    Cors,
+    /// A request for a subresource without CORS, such as `fetch()` with
+    /// `mode: "no-cors"`.
+    NoCors,
+    /// A `fetch()` or XHR request restricted to the page's origin.
+    SameOrigin,
 }
 
 /// A builder to construct the properties of a `Request`.
@@ -83,11 +135,21 @@ impl Request {
             body: None,
             timeout: None,
             read_timeout: None,
+            bytes_limit: None,
+            decompress: true,
+            hedge: None,
             version: None,
             redirect: None,
             #[cfg(feature = "cookies")]
             cookie_store: None,
             network_scheme: NetworkScheme::builder(),
+            removed_headers: Vec::new(),
+            headers_order: None,
+            pinned: None,
+            pool_tag: None,
+            server_name: None,
+            connect_to: None,
+            request_target: None,
         }
     }
 
@@ -127,6 +189,18 @@ impl Request {
         &mut self.headers
     }
 
+    /// Get the headers order, overriding the client's for this request.
+    #[inline]
+    pub fn headers_order(&self) -> Option<&[HeaderName]> {
+        self.headers_order.as_deref()
+    }
+
+    /// Get a mutable reference to the headers order.
+    #[inline]
+    pub fn headers_order_mut(&mut self) -> &mut Option<Cow<'static, [HeaderName]>> {
+        &mut self.headers_order
+    }
+
     /// Get a mutable reference to the redirect policy.
     #[inline]
     pub fn redirect_mut(&mut self) -> &mut Option<redirect::Policy> {
@@ -182,6 +256,42 @@ impl Request {
         &mut self.read_timeout
     }
 
+    /// Get the response body size limit.
+    #[inline]
+    pub fn bytes_limit(&self) -> Option<usize> {
+        self.bytes_limit
+    }
+
+    /// Get a mutable reference to the response body size limit.
+    #[inline]
+    pub fn bytes_limit_mut(&mut self) -> &mut Option<usize> {
+        &mut self.bytes_limit
+    }
+
+    /// Get whether the response body is decompressed.
+    #[inline]
+    pub fn decompress(&self) -> bool {
+        self.decompress
+    }
+
+    /// Get a mutable reference to whether the response body is decompressed.
+    #[inline]
+    pub fn decompress_mut(&mut self) -> &mut bool {
+        &mut self.decompress
+    }
+
+    /// Get the delay after which the request is hedged.
+    #[inline]
+    pub fn hedge(&self) -> Option<Duration> {
+        self.hedge
+    }
+
+    /// Get a mutable reference to the delay after which the request is hedged.
+    #[inline]
+    pub fn hedge_mut(&mut self) -> &mut Option<Duration> {
+        &mut self.hedge
+    }
+
     /// Get the http version.
     #[inline]
     pub fn version(&self) -> Option<Version> {
@@ -194,6 +304,89 @@ impl Request {
         &mut self.version
     }
 
+    /// Returns a `curl` command line that sends the same request.
+    ///
+    /// It includes the method, the HTTP version if set, the headers, in the
+    /// order of [`headers_order`](Request::headers_order) if set, and the body
+    /// with `--data-raw`, so that a body starting with `@` is not read from a
+    /// file. The method is always given when there is a body, as curl would
+    /// send a `POST` otherwise. Values are quoted for POSIX shells; those with
+    /// control characters or invalid UTF-8 use the `$'...'` quoting of bash and
+    /// zsh.
+    ///
+    /// Only the request itself is reproduced: the headers the client adds, such
+    /// as default headers, cookies or `Accept-Encoding`, and its TLS and HTTP/2
+    /// fingerprint are not. A streaming body is left out.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn run() -> Result<(), rquest::Error> {
+    /// let req = rquest::Client::new()
+    ///     .post("https://example.com/api")
+    ///     .header("content-type", "application/json")
+    ///     .body(r#"{"it's":1}"#)
+    ///     .build()?;
+    /// assert_eq!(
+    ///     req.to_curl(),
+    ///     r#"curl -X POST -H 'content-type: application/json' --data-raw '{"it'\''s":1}' 'https://example.com/api'"#
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_curl(&self) -> String {
+        let mut args = vec![Cow::Borrowed("curl")];
+        let body = self.body.as_ref().and_then(Body::as_bytes);
+
+        match self.method {
+            Method::GET if body.is_none() => {}
+            Method::HEAD if body.is_none() => args.push("--head".into()),
+            ref method => {
+                args.push("-X".into());
+                if method.as_str().bytes().all(|b| b.is_ascii_alphanumeric()) {
+                    args.push(method.as_str().to_owned().into());
+                } else {
+                    args.push(shell_quote(method.as_str().as_bytes()));
+                }
+            }
+        }
+
+        match self.version {
+            Some(Version::HTTP_10) => args.push("--http1.0".into()),
+            Some(Version::HTTP_11) => args.push("--http1.1".into()),
+            // without TLS, HTTP/2 is spoken with prior knowledge, not upgraded to
+            Some(Version::HTTP_2) if self.url.scheme() == "http" => {
+                args.push("--http2-prior-knowledge".into())
+            }
+            Some(Version::HTTP_2) => args.push("--http2".into()),
+            _ => {}
+        }
+
+        let order = self.headers_order.as_deref().unwrap_or_default();
+        let ordered = order.iter().filter(|name| self.headers.contains_key(*name));
+        let rest = self.headers.keys().filter(|name| !order.contains(name));
+        for name in ordered.chain(rest) {
+            for value in self.headers.get_all(name) {
+                // `name:` would remove the header, `name;` sends it empty
+                let header = if value.is_empty() {
+                    format!("{name};").into_bytes()
+                } else {
+                    [format!("{name}: ").as_bytes(), value.as_bytes()].concat()
+                };
+                args.push("-H".into());
+                args.push(shell_quote(&header));
+            }
+        }
+
+        if let Some(body) = body {
+            args.push("--data-raw".into());
+            args.push(shell_quote(body));
+        }
+
+        args.push(shell_quote(self.url.as_str().as_bytes()));
+        args.join(" ")
+    }
+
     /// Attempt to clone the request.
     ///
     /// `None` is returned if the request can not be cloned, i.e. if the body is a stream.
@@ -205,10 +398,20 @@ impl Request {
         let mut req = Request::new(self.method().clone(), self.url().clone());
         *req.timeout_mut() = self.timeout().copied();
         *req.read_timeout_mut() = self.read_timeout().copied();
+        *req.bytes_limit_mut() = self.bytes_limit();
+        *req.decompress_mut() = self.decompress();
+        *req.hedge_mut() = self.hedge();
         *req.headers_mut() = self.headers().clone();
         *req.version_mut() = self.version();
         *req.redirect_mut() = self.redirect.clone();
         *req.network_scheme_mut() = self.network_scheme.clone();
+        req.removed_headers = self.removed_headers.clone();
+        req.headers_order = self.headers_order.clone();
+        req.pinned = self.pinned.clone();
+        req.pool_tag = self.pool_tag;
+        req.server_name = self.server_name.clone();
+        req.connect_to = self.connect_to.clone();
+        req.request_target = self.request_target.clone();
         #[cfg(feature = "cookies")]
         {
             *req.cookie_store_mut() = self.cookie_store.clone();
@@ -217,6 +420,43 @@ impl Request {
         Some(req)
     }
 
+    /// A copy of the request to hedge it with, which is kept off the
+    /// connections of other requests, see [`RequestBuilder::hedge`].
+    pub(super) fn try_clone_hedge(&self) -> Option<Request> {
+        let mut hedged = self.try_clone()?;
+        let mut hasher = DefaultHasher::new();
+        ("hedge", self.pool_tag).hash(&mut hasher);
+        hedged.pool_tag = Some(hasher.finish());
+        hedged.pinned = None;
+        Some(hedged)
+    }
+
+    /// The connection the request is sent on, see [`PinnedConnection`](super::PinnedConnection).
+    pub(super) fn pinned_mut(&mut self) -> &mut Option<Pinned> {
+        &mut self.pinned
+    }
+
+    /// The tag partitioning the connection pool, see [`RequestBuilder::pool_key_tag`].
+    pub(super) fn pool_tag(&self) -> Option<u64> {
+        self.pool_tag
+    }
+
+    /// The TLS server name override, see [`RequestBuilder::sni`].
+    pub(super) fn server_name(&self) -> Option<&ServerName> {
+        self.server_name.as_ref()
+    }
+
+    /// The endpoint to connect to, see [`RequestBuilder::connect_to`].
+    pub(super) fn connect_to(&self) -> Option<&ConnectTo> {
+        self.connect_to.as_ref()
+    }
+
+    /// The overridden scheme and path of the request target, see
+    /// [`RequestBuilder::danger_raw_path`].
+    pub(super) fn request_target(&self) -> Option<&RequestTarget> {
+        self.request_target.as_ref()
+    }
+
     pub(super) fn pieces(self) -> PiecesWithCookieStore {
         (
             self.method,
@@ -232,6 +472,9 @@ impl Request {
             #[cfg(not(feature = "cookies"))]
             (),
             self.network_scheme.build(),
+            self.removed_headers,
+            self.bytes_limit,
+            self.headers_order,
         )
     }
 }
@@ -253,6 +496,10 @@ impl RequestBuilder {
         }
     }
 
+    pub(super) fn request_mut(&mut self) -> Option<&mut Request> {
+        self.request.as_mut().ok()
+    }
+
     /// Assemble a builder starting from an existing `Client` and a `Request`.
     pub fn from_parts(client: Client, request: Request) -> RequestBuilder {
         RequestBuilder {
@@ -272,6 +519,115 @@ impl RequestBuilder {
         self.header_operation(key, value, false, true, false)
     }
 
+    /// Remove a header from this Request, including one the client's default
+    /// headers or impersonation profile would otherwise add.
+    ///
+    /// Unlike setting the header to an empty value, the header is not sent at
+    /// all. Setting the header again afterwards sends it as usual.
+    pub fn remove_header<K>(mut self, key: K) -> RequestBuilder
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+    {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match <HeaderName as TryFrom<K>>::try_from(key) {
+                Ok(key) => {
+                    if self.client.preserve_header_order() {
+                        remove_header_ordered(req.headers_mut(), &key);
+                    } else {
+                        req.headers_mut().remove(&key);
+                    }
+                    if !req.removed_headers.contains(&key) {
+                        req.removed_headers.push(key);
+                    }
+                }
+                Err(e) => error = Some(crate::error::builder(e.into())),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Set the order in which the headers of this Request are sent.
+    ///
+    /// This overrides [`ClientBuilder::headers_order`](crate::ClientBuilder::headers_order)
+    /// for this request only. Headers not listed are sent after the listed ones.
+    pub fn headers_order(mut self, order: impl Into<Cow<'static, [HeaderName]>>) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.headers_order = Some(order.into());
+        }
+        self
+    }
+
+    /// Set the `cache-control` and `pragma` headers a browser sends for this kind
+    /// of page load.
+    ///
+    /// This matches Chrome: a [`ReloadKind::Reload`] sends
+    /// `cache-control: max-age=0`, a [`ReloadKind::HardReload`] sends
+    /// `pragma: no-cache` followed by `cache-control: no-cache`, and a
+    /// [`ReloadKind::Normal`] load sends neither, removing any set before.
+    pub fn reload(mut self, kind: ReloadKind) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            let headers = req.headers_mut();
+            if self.client.preserve_header_order() {
+                remove_header_ordered(headers, &PRAGMA);
+                remove_header_ordered(headers, &CACHE_CONTROL);
+            } else {
+                headers.remove(PRAGMA);
+                headers.remove(CACHE_CONTROL);
+            }
+            match kind {
+                ReloadKind::Normal => {}
+                ReloadKind::Reload => {
+                    headers.insert(CACHE_CONTROL, HeaderValue::from_static("max-age=0"));
+                }
+                ReloadKind::HardReload => {
+                    headers.insert(PRAGMA, HeaderValue::from_static("no-cache"));
+                    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+                }
+            }
+        }
+        self
+    }
+
+    /// Set the `sec-fetch-*` metadata headers a browser sends for this mode of
+    /// request.
+    ///
+    /// A [`FetchMode::Navigate`] request is sent as a user-initiated page load:
+    /// `sec-fetch-site: none`, `sec-fetch-mode: navigate`,
+    /// `sec-fetch-dest: document` and `sec-fetch-user: ?1`. The other modes are
+    /// sent as requests made by a script of the page, with
+    /// `sec-fetch-site: same-origin`, `sec-fetch-dest: empty`, and without
+    /// `sec-fetch-user` and `upgrade-insecure-requests`. They also accept `*/*`
+    /// unless an `accept` header was set on the request.
+    ///
+    /// Requests without a fetch mode send the headers of the impersonation
+    /// profile, which are those of a navigation. For a request to another
+    /// site, set `sec-fetch-site` to `same-site` or `cross-site` after calling
+    /// this.
+    pub fn fetch_mode(self, mode: FetchMode) -> RequestBuilder {
+        let (site, mode_value, dest) = match mode {
+            FetchMode::Navigate => ("none", "navigate", "document"),
+            FetchMode::Cors => ("same-origin", "cors", "empty"),
+            FetchMode::NoCors => ("same-origin", "no-cors", "empty"),
+            FetchMode::SameOrigin => ("same-origin", "same-origin", "empty"),
+        };
+        let builder = self
+            .header("sec-fetch-site", site)
+            .header("sec-fetch-mode", mode_value)
+            .header("sec-fetch-dest", dest);
+        match mode {
+            FetchMode::Navigate => builder.header("sec-fetch-user", "?1"),
+            _ => builder
+                .remove_header("sec-fetch-user")
+                .remove_header(UPGRADE_INSECURE_REQUESTS)
+                .header_operation(ACCEPT, "*/*", false, false, true),
+        }
+    }
+
     /// Add a `Header` to append to the request.
     pub fn header_append<K, V>(self, key: K, value: V) -> RequestBuilder
     where
@@ -314,6 +670,9 @@ impl RequestBuilder {
                             value.set_sensitive(true);
                         }
 
+                        // A header set explicitly is no longer removed.
+                        req.removed_headers.retain(|name| *name != key);
+
                         // If or_insert is true, we want to skip the insertion if the header already exists
                         if or_insert {
                             req.headers_mut().entry(key).or_insert(value);
@@ -405,11 +764,125 @@ impl RequestBuilder {
         self
     }
 
+    /// Set the request body to stream the file at `path`, without reading it
+    /// into memory.
+    ///
+    /// The file's size when it is opened is sent as the `Content-Length`. If
+    /// the file grows while it is being sent, only that many bytes are sent;
+    /// if it shrinks, sending the body fails.
+    ///
+    /// An error opening the file is returned when the request is sent.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run() -> Result<(), rquest::Error> {
+    /// let client = rquest::Client::new();
+    /// let res = client
+    ///     .put("http://httpbin.org/put")
+    ///     .body_from_file("upload.bin")
+    ///     .await
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub async fn body_from_file<P>(mut self, path: P) -> RequestBuilder
+    where
+        P: AsRef<std::path::Path>,
+    {
+        if self.request.is_err() {
+            return self;
+        }
+
+        let opened = async {
+            let file = tokio::fs::File::open(path).await?;
+            let len = file.metadata().await?.len();
+            Ok::<_, std::io::Error>((file, len))
+        }
+        .await;
+
+        match opened {
+            Ok((file, len)) => self.body(Body::from_reader_with_len(file, len)),
+            Err(err) => {
+                self.request = Err(crate::error::builder(err));
+                self
+            }
+        }
+    }
+
+    /// Set a request body compressed with `encoding`.
+    ///
+    /// The body is compressed as it is sent and the `Content-Encoding` header is
+    /// set accordingly. Compression is skipped, and the body sent as-is, when
+    /// the body is known to be shorter than 1 KiB, when the `Content-Type` is an
+    /// already compressed format (images, audio, video, archives), or when a
+    /// `Content-Encoding` header is already present. Set the `Content-Type`
+    /// before calling this method for that check to apply.
+    ///
+    /// A compressed body has no known length, so any `Content-Length` header
+    /// is removed with it.
+    ///
+    /// # Optional
+    ///
+    /// This requires at least one of the optional `gzip`, `brotli`, `zstd` or
+    /// `deflate` features enabled.
+    #[cfg(any(
+        feature = "gzip",
+        feature = "brotli",
+        feature = "zstd",
+        feature = "deflate"
+    ))]
+    pub fn body_compressed<T: Into<Body>>(
+        mut self,
+        body: T,
+        encoding: super::encoder::ContentEncoding,
+    ) -> RequestBuilder {
+        use super::encoder;
+        use crate::header::{CONTENT_ENCODING, CONTENT_LENGTH};
+
+        if let Ok(ref mut req) = self.request {
+            let body = body.into();
+            let too_small = http_body::Body::size_hint(&body)
+                .exact()
+                .is_some_and(|len| len < encoder::MIN_COMPRESS_LEN);
+            let already_compressed = req.headers().contains_key(CONTENT_ENCODING)
+                || req
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .is_some_and(encoder::is_compressed_media_type);
+
+            *req.body_mut() = Some(if too_small || already_compressed {
+                body
+            } else {
+                req.headers_mut()
+                    .insert(CONTENT_ENCODING, encoding.header_value());
+                req.headers_mut().remove(CONTENT_LENGTH);
+                encoding.encode(body)
+            });
+        }
+        self
+    }
+
     /// Enables a request timeout.
     ///
     /// The timeout is applied from when the request starts connecting until the
     /// response body has finished. It affects only this request and overrides
     /// the timeout configured using `ClientBuilder::timeout()`.
+    ///
+    /// This is a hard upper bound on the whole exchange: DNS resolution, connecting,
+    /// the TLS handshake, following redirects and reading the body all share the
+    /// same deadline, which is not reset by progress on the body. Use
+    /// `read_timeout()` to bound the time between chunks instead. Once elapsed,
+    /// the request is canceled and fails with an error for which
+    /// [`Error::is_timeout`](crate::Error::is_timeout) returns `true`.
     pub fn timeout(mut self, timeout: Duration) -> RequestBuilder {
         if let Ok(ref mut req) = self.request {
             *req.timeout_mut() = Some(timeout);
@@ -429,6 +902,175 @@ impl RequestBuilder {
         self
     }
 
+    /// Limits the size of the response body to `limit` bytes.
+    ///
+    /// The limit applies to the decoded body and is enforced while it is
+    /// streamed, whatever `Content-Length` announces, so reading a larger
+    /// body fails with an error for which
+    /// [`Error::is_body_too_large`](crate::Error::is_body_too_large) returns
+    /// `true` without buffering more than the limit. It affects only this
+    /// request and overrides the limit configured using
+    /// `ClientBuilder::bytes_limit()`.
+    pub fn bytes_limit(mut self, limit: usize) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.bytes_limit_mut() = Some(limit);
+        }
+        self
+    }
+
+    /// Only reuses pooled connections opened by requests with the same `tag`.
+    ///
+    /// Requests with different tags, or without a tag, never share a connection,
+    /// even to the same host. This keeps connections apart between sessions,
+    /// such as an authenticated and an anonymous one, when the server keeps
+    /// state per connection.
+    pub fn pool_key_tag<T: Hash>(mut self, tag: T) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            let mut hasher = DefaultHasher::new();
+            tag.hash(&mut hasher);
+            req.pool_tag = Some(hasher.finish());
+        }
+        self
+    }
+
+    /// Sets the TLS server name sent in the SNI extension, independently of the
+    /// `Host` header, or disables SNI with `None`.
+    ///
+    /// The server certificate is verified against this name rather than the
+    /// host of the URL. With `None`, it is still verified against the host of
+    /// the URL. The name also applies to redirects and retries of this request,
+    /// which only reuse connections opened with the same name.
+    ///
+    /// It overrides the name set with [`ClientBuilder::sni`](crate::ClientBuilder::sni).
+    pub fn sni(mut self, server_name: Option<&str>) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.server_name = Some(ServerName(server_name.map(|name| name.to_owned().into())));
+        }
+        self
+    }
+
+    /// Connects to `endpoint`, a host or IP address with an optional port,
+    /// instead of the host of the URL, like curl's `--connect-to`.
+    ///
+    /// The host of the URL is still sent in the `Host` header and SNI, and the
+    /// server certificate is verified against it, unless overridden with
+    /// [`RequestBuilder::sni`]. Without a port, the port of the URL is used.
+    /// This is useful to test a specific edge of a CDN.
+    ///
+    /// HTTPS requests through an HTTP proxy tunnel to `endpoint`. It applies
+    /// to retries of this request, and to redirects to the same host and port,
+    /// which only reuse connections made to the same endpoint. It is ignored
+    /// for other proxied requests, and with a custom transport.
+    pub fn connect_to(mut self, endpoint: &str) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match endpoint.parse() {
+                Ok(endpoint) => req.connect_to = Some(ConnectTo(endpoint)),
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Sends `path` as the `:path` pseudo-header over HTTP/2, or as the path of
+    /// the request target over HTTP/1, instead of the path and query of the URL.
+    ///
+    /// The path is sent as-is, without the normalization the URL goes through,
+    /// such as resolving `..` segments. It must start with `/`, or be `*`. It
+    /// applies to retries of this request, but not to redirects.
+    ///
+    /// # Warning
+    ///
+    /// Servers and proxies may route or authorize the request by a different
+    /// path than the one sent. This is meant to test how servers and gateways
+    /// handle such requests.
+    pub fn danger_raw_path(mut self, path: &str) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match path.parse::<http::uri::PathAndQuery>() {
+                Ok(path) if path.as_str().starts_with('/') || path.as_str() == "*" => {
+                    req.request_target.get_or_insert_with(Default::default).path = Some(path);
+                }
+                Ok(path) => {
+                    error = Some(crate::error::builder(format!(
+                        "raw path must start with '/' or be '*': {path}"
+                    )))
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Sends `scheme` as the `:scheme` pseudo-header over HTTP/2, instead of the
+    /// scheme of the URL.
+    ///
+    /// Over HTTP/1, the scheme is only sent in the absolute-form request target
+    /// used with an HTTP proxy. The connection is still made for the scheme of
+    /// the URL. It applies to retries of this request, but not to redirects.
+    ///
+    /// # Warning
+    ///
+    /// Servers may reject the request or treat it as sent over another
+    /// protocol. This is meant to test how servers and gateways handle such
+    /// requests.
+    pub fn danger_raw_scheme(mut self, scheme: &str) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match scheme.parse() {
+                Ok(scheme) => {
+                    req.request_target
+                        .get_or_insert_with(Default::default)
+                        .scheme = Some(scheme);
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Returns the response body as the server sent it, without decompressing it.
+    ///
+    /// The `Accept-Encoding` header is sent as usual, and the `Content-Encoding`
+    /// and `Content-Length` headers of the response are left untouched, so the
+    /// compressed body can be stored or forwarded as-is.
+    pub fn no_decompress(mut self) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.decompress_mut() = false;
+        }
+        self
+    }
+
+    /// Hedges the request after `after`.
+    ///
+    /// If no response arrived `after` the request was sent, an identical
+    /// request is sent, and the response that arrives first is returned while
+    /// the other request is canceled. If one of them fails, the other one is
+    /// awaited instead.
+    ///
+    /// The hedged request never shares a connection with other requests, even
+    /// over HTTP/2: it is sent over a connection kept for hedged requests,
+    /// opened if none is idle. That connection is made to the same host, so
+    /// which server answers is up to DNS and the load balancers on the way.
+    ///
+    /// Only requests with an idempotent method and a body that can be cloned,
+    /// i.e. not a stream, are hedged; others are sent once.
+    pub fn hedge(mut self, after: Duration) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.hedge_mut() = Some(after);
+        }
+        self
+    }
+
     /// Sends a multipart/form-data body.
     ///
     /// ```
@@ -511,6 +1153,12 @@ impl RequestBuilder {
     }
 
     /// Set HTTP version
+    ///
+    /// With `Version::HTTP_10`, the request line reads `HTTP/1.0` and the
+    /// connection is closed after the response, whose body may then run until
+    /// the end of the connection. To keep the connection alive, send a
+    /// `Connection: keep-alive` header; it is only reused if the server answers
+    /// with one as well.
     pub fn version(mut self, version: Version) -> RequestBuilder {
         if let Ok(ref mut req) = self.request {
             req.version = Some(version);
@@ -768,6 +1416,50 @@ fn fmt_request_fields<'a, 'b>(
         .field("headers", &req.headers)
 }
 
+/// Remove all values of a header, keeping the order of the remaining headers.
+///
+/// `HeaderMap::remove` moves the last header into the place of the removed one.
+pub(super) fn remove_header_ordered(headers: &mut HeaderMap, name: &HeaderName) {
+    if !headers.contains_key(name) {
+        return;
+    }
+
+    let mut remaining = HeaderMap::with_capacity(headers.keys_len());
+    let mut current = None;
+    for (key, value) in headers.drain() {
+        if let Some(key) = key {
+            current = (key != *name).then_some(key);
+        }
+        if let Some(key) = &current {
+            remaining.append(key, value);
+        }
+    }
+
+    std::mem::swap(headers, &mut remaining);
+}
+
+/// Quotes `value` as a single shell word.
+fn shell_quote(value: &[u8]) -> Cow<'static, str> {
+    match std::str::from_utf8(value) {
+        Ok(value) if !value.chars().any(char::is_control) => {
+            format!("'{}'", value.replace('\'', r"'\''")).into()
+        }
+        _ => {
+            let mut quoted = String::from("$'");
+            for &byte in value {
+                match byte {
+                    b'\\' => quoted.push_str(r"\\"),
+                    b'\'' => quoted.push_str(r"\'"),
+                    b' '..=b'~' => quoted.push(byte as char),
+                    _ => quoted.push_str(&format!("\\x{byte:02x}")),
+                }
+            }
+            quoted.push('\'');
+            quoted.into()
+        }
+    }
+}
+
 /// Check the request URL for a "username:password" type authority, and if
 /// found, remove it from the URL and return it.
 pub(crate) fn extract_authority(url: &mut Url) -> Option<(String, Option<String>)> {
@@ -818,12 +1510,22 @@ where
             body: Some(body.into()),
             timeout: None,
             read_timeout: None,
+            bytes_limit: None,
+            decompress: true,
+            hedge: None,
             // TODO: Add version
             version: None,
             redirect: None,
             #[cfg(feature = "cookies")]
             cookie_store: None,
             network_scheme: NetworkScheme::builder(),
+            removed_headers: Vec::new(),
+            headers_order: None,
+            pinned: None,
+            pool_tag: None,
+            server_name: None,
+            connect_to: None,
+            request_target: None,
         })
     }
 }
@@ -1,17 +1,22 @@
 use std::fmt;
 use std::net::SocketAddr;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use bytes::Bytes;
+use http::header::LINK;
 use http_body_util::BodyExt;
-use hyper2::{HeaderMap, StatusCode, Version};
+use hyper2::{HeaderMap, Method, StatusCode, Version};
 #[cfg(feature = "json")]
 use serde::de::DeserializeOwned;
 use tokio::time::Sleep;
 use url::Url;
 use util::client::connect::HttpInfo;
+use util::client::ConnectionReused;
 
+use super::alt_svc::AltSvc;
 use super::body::Body;
 use super::body::ResponseBody;
 use super::decoder::{Accepts, Decoder};
@@ -31,17 +36,32 @@ pub struct Response {
     // Boxed to save space (11 words to 1 word), and it's not accessed
     // frequently internally.
     url: Box<Url>,
+    redirect_history: Vec<Url>,
+    encoded_read: Arc<AtomicU64>,
+    is_head: bool,
 }
 
 impl Response {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         res: hyper2::Response<ResponseBody>,
+        method: Method,
         url: Url,
+        redirect_history: Vec<Url>,
         accepts: Accepts,
         total_timeout: Option<Pin<Box<Sleep>>>,
         read_timeout: Option<Duration>,
+        buffer_watermarks: Option<(usize, usize)>,
     ) -> Response {
         let (mut parts, body) = res.into_parts();
+        // The body of a response to HEAD is always empty, the headers
+        // describe the body a GET would have received.
+        let is_head = method == Method::HEAD;
+        let encoded_read = Arc::new(AtomicU64::new(0));
+        let mut body = super::body::counting(body, encoded_read.clone());
+        if let Some((low, high)) = buffer_watermarks {
+            body = super::body::buffered(body, low, high);
+        }
         let decoder = Decoder::detect(
             &mut parts.headers,
             super::body::response(body, total_timeout, read_timeout),
@@ -52,6 +72,9 @@ impl Response {
         Response {
             res,
             url: Box::new(url),
+            redirect_history,
+            encoded_read,
+            is_head,
         }
     }
 
@@ -67,6 +90,22 @@ impl Response {
         self.res.version()
     }
 
+    /// Get the reason phrase of the status line of this `Response`, exactly as
+    /// the server sent it.
+    ///
+    /// Returns `None` for HTTP/2 responses, which have no reason phrase, and
+    /// for a reason phrase that isn't valid UTF-8.
+    pub fn reason(&self) -> Option<&str> {
+        if self.version() >= Version::HTTP_2 {
+            return None;
+        }
+        match self.res.extensions().get::<hyper2::ext::ReasonPhrase>() {
+            Some(reason) => std::str::from_utf8(reason.as_bytes()).ok(),
+            // the reason phrase is only kept when it isn't the canonical one
+            None => self.status().canonical_reason(),
+        }
+    }
+
     /// Get the `Headers` of this `Response`.
     #[inline]
     pub fn headers(&self) -> &HeaderMap {
@@ -81,17 +120,39 @@ impl Response {
 
     /// Get the content-length of this response, if known.
     ///
+    /// This is the length of the body as it is read, after decompression.
     /// Reasons it may not be known:
     ///
     /// - The server didn't send a `content-length` header.
     /// - The response is compressed and automatically decoded (thus changing
     ///   the actual decoded length).
+    ///
+    /// For a response to a `HEAD` request, this is the length announced by
+    /// the `content-length` header, although the body itself is empty.
+    ///
+    /// See [`Response::encoded_length`] for the bytes read off the connection.
     pub fn content_length(&self) -> Option<u64> {
         use hyper2::body::Body;
 
+        if self.is_head {
+            return header_content_length(self.headers());
+        }
         Body::size_hint(self.res.body()).exact()
     }
 
+    /// Get the number of body bytes read off the connection so far, before
+    /// any decompression.
+    ///
+    /// Once the body is read to the end, this is the length of the body as the
+    /// server sent it, whether or not it announced a `content-length`, such as
+    /// for chunked responses. Bytes read ahead by
+    /// [`ClientBuilder::stream_buffer_watermarks`](crate::ClientBuilder::stream_buffer_watermarks)
+    /// are counted as they arrive. Use [`Response::chunk`] to read the body
+    /// while keeping hold of the response.
+    pub fn encoded_length(&self) -> u64 {
+        self.encoded_read.load(Ordering::Relaxed)
+    }
+
     /// Retrieve the cookies contained in the response.
     ///
     /// Note that invalid 'Set-Cookie' headers will be ignored.
@@ -106,11 +167,52 @@ impl Response {
     }
 
     /// Get the final `Url` of this `Response`.
+    ///
+    /// After redirects, this is the `Url` of the last request sent, which is
+    /// the base relative links in the response resolve against. See
+    /// [`Response::original_url`] for the `Url` that was requested.
     #[inline]
     pub fn url(&self) -> &Url {
         &self.url
     }
 
+    /// Get the `Url` that was requested, before any redirect was followed.
+    ///
+    /// Equal to [`Response::url`] if no redirect was followed.
+    pub fn original_url(&self) -> &Url {
+        self.redirect_history.first().unwrap_or(&self.url)
+    }
+
+    /// Get the URLs that redirected to this `Response`, in the order they were requested.
+    ///
+    /// The final `Url` is not included, see [`Response::url`]. Empty if no
+    /// redirect was followed.
+    pub fn redirect_history(&self) -> &[Url] {
+        &self.redirect_history
+    }
+
+    /// Get the `Url` of the next page, as advertised by a `Link` header.
+    ///
+    /// Looks for a link with `rel="next"` (RFC 8288) and resolves it against
+    /// the final `Url` of this `Response`.
+    pub fn next_page_url(&self) -> Option<Url> {
+        self.res
+            .headers()
+            .get_all(LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(next_link)
+            .and_then(|link| self.url.join(link).ok())
+    }
+
+    /// Get the alternative services advertised by `Alt-Svc` headers, such as
+    /// an HTTP/3 endpoint of the same origin.
+    ///
+    /// Returns `None` if there is no valid `Alt-Svc` header.
+    pub fn alt_svc(&self) -> Option<AltSvc> {
+        AltSvc::parse(self.res.headers())
+    }
+
     /// Get the remote address used to get this `Response`.
     pub fn remote_addr(&self) -> Option<SocketAddr> {
         self.res
@@ -119,6 +221,45 @@ impl Response {
             .map(|info| info.remote_addr())
     }
 
+    /// Get the local address of the connection used to get this `Response`.
+    ///
+    /// This shows which source address and port the connection was bound to,
+    /// such as one set with [`ClientBuilder::local_address`](crate::ClientBuilder::local_address).
+    /// It is `None` for connections over a custom transport.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.res
+            .extensions()
+            .get::<HttpInfo>()
+            .map(|info| info.local_addr())
+    }
+
+    /// Returns whether this `Response` was received over a connection taken
+    /// from the pool, rather than one established for its request.
+    ///
+    /// With HTTP/2, every request but the one that established a connection
+    /// is sent over a reused connection, including concurrent ones.
+    pub fn connection_reused(&self) -> bool {
+        self.res
+            .extensions()
+            .get::<ConnectionReused>()
+            .is_some_and(|reused| reused.0)
+    }
+
+    /// Returns whether this `Response` was served from the client's cache,
+    /// either fresh or after a `304 Not Modified` revalidation.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cache` feature to be enabled.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn from_cache(&self) -> bool {
+        self.res
+            .extensions()
+            .get::<crate::cache::FromCache>()
+            .is_some()
+    }
+
     /// Returns a reference to the associated extensions.
     pub fn extensions(&self) -> &http::Extensions {
         self.res.extensions()
@@ -261,15 +402,112 @@ impl Response {
     /// or it cannot be properly deserialized to target type `T`. For more
     /// details please see [`serde_json::from_reader`].
     ///
+    /// With the `charset` feature, a body declaring a `charset` other than
+    /// UTF-8 in its `Content-Type` is transcoded to UTF-8 before being
+    /// deserialized.
+    ///
     /// [`serde_json::from_reader`]: https://docs.serde.rs/serde_json/fn.from_reader.html
     #[cfg(feature = "json")]
     #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
     pub async fn json<T: DeserializeOwned>(self) -> crate::Result<T> {
+        #[cfg(feature = "charset")]
+        let encoding = self
+            .headers()
+            .get(crate::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Mime>().ok())
+            .and_then(|mime| Encoding::for_label(mime.get_param("charset")?.as_str().as_bytes()))
+            .filter(|encoding| *encoding != UTF_8);
+
         let full = self.bytes().await?;
 
+        #[cfg(feature = "charset")]
+        if let Some(encoding) = encoding {
+            let (text, _) = encoding.decode_with_bom_removal(&full);
+            return serde_json::from_str(&text).map_err(crate::error::decode);
+        }
+
         serde_json::from_slice(&full).map_err(crate::error::decode)
     }
 
+    /// Stream the response body as newline-delimited JSON (NDJSON), one item
+    /// per line.
+    ///
+    /// Each line is deserialized as soon as it is complete, whatever the chunk
+    /// boundaries. Blank lines are skipped. A line that fails to deserialize
+    /// yields an error for that item and the stream goes on with the next
+    /// line; an error reading the body ends the stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate rquest;
+    /// # extern crate serde;
+    /// #
+    /// # use rquest::Error;
+    /// # use serde::Deserialize;
+    /// # use futures_util::StreamExt;
+    /// #
+    /// #[derive(Deserialize)]
+    /// struct Event {
+    ///     message: String,
+    /// }
+    ///
+    /// # async fn run() -> Result<(), Error> {
+    /// let mut events = rquest::get("http://example.com/events")
+    ///     .await?
+    ///     .json_stream::<Event>();
+    ///
+    /// while let Some(event) = events.next().await {
+    ///     println!("{}", event?.message);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `json` feature enabled.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn json_stream<T: DeserializeOwned>(
+        self,
+    ) -> impl futures_util::Stream<Item = crate::Result<T>> + Unpin {
+        let stream = futures_util::stream::unfold(
+            (Some(self), bytes::BytesMut::new()),
+            |(mut res, mut buf)| async move {
+                loop {
+                    let line = match buf.iter().position(|&b| b == b'\n') {
+                        Some(end) => buf.split_to(end + 1),
+                        // the last line may have no newline
+                        None if res.is_none() => buf.split(),
+                        None => {
+                            match res.as_mut()?.chunk().await {
+                                Ok(Some(chunk)) => buf.extend_from_slice(&chunk),
+                                Ok(None) => res = None,
+                                Err(err) => {
+                                    buf.clear();
+                                    return Some((Err(err), (None, buf)));
+                                }
+                            }
+                            continue;
+                        }
+                    };
+
+                    if line.trim_ascii().is_empty() {
+                        if res.is_none() && buf.is_empty() {
+                            return None;
+                        }
+                        continue;
+                    }
+                    let item = serde_json::from_slice(&line).map_err(crate::error::decode);
+                    return Some((item, (res, buf)));
+                }
+            },
+        );
+        Box::pin(stream)
+    }
+
     /// Get the full response body as `Bytes`.
     ///
     /// # Example
@@ -326,6 +564,196 @@ impl Response {
         }
     }
 
+    /// Copy the response body into a writer, returning the number of bytes written.
+    ///
+    /// The body is decompressed, and each chunk is written as it is received
+    /// rather than buffered in full. The writer is flushed once the body is
+    /// complete. Writing to it blocks, so prefer
+    /// [`copy_to_async`](Self::copy_to_async) for anything but fast writers.
+    ///
+    /// Errors reading the body are returned as they are, errors from the
+    /// writer as [`is_write`](crate::Error::is_write) errors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut res = rquest::get("http://httpbin.org/ip").await?;
+    ///
+    /// let mut buf = Vec::new();
+    /// let written = res.copy_to(&mut buf).await?;
+    /// println!("{written} bytes");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_to<W>(&mut self, w: &mut W) -> crate::Result<u64>
+    where
+        W: std::io::Write + ?Sized,
+    {
+        let mut written = 0;
+        while let Some(chunk) = self.chunk().await? {
+            w.write_all(&chunk).map_err(crate::error::write)?;
+            written += chunk.len() as u64;
+        }
+        w.flush().map_err(crate::error::write)?;
+        Ok(written)
+    }
+
+    /// Copy the response body into an `AsyncWrite`, returning the number of
+    /// bytes written.
+    ///
+    /// This is the asynchronous version of [`copy_to`](Self::copy_to).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut res = rquest::get("http://httpbin.org/ip").await?;
+    ///
+    /// let mut file = tokio::fs::File::create("ip.json").await?;
+    /// res.copy_to_async(&mut file).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_to_async<W>(&mut self, w: &mut W) -> crate::Result<u64>
+    where
+        W: tokio::io::AsyncWrite + Unpin + ?Sized,
+    {
+        use tokio::io::AsyncWriteExt;
+
+        let mut written = 0;
+        while let Some(chunk) = self.chunk().await? {
+            w.write_all(&chunk).await.map_err(crate::error::write)?;
+            written += chunk.len() as u64;
+        }
+        w.flush().await.map_err(crate::error::write)?;
+        Ok(written)
+    }
+
+    /// Save the response body to the file at `path`, returning the number of
+    /// bytes written.
+    ///
+    /// The body is decompressed and streamed to a temporary file next to
+    /// `path` as it is received, which then replaces the file at `path`. If
+    /// saving fails, the file at `path` is left as it was and the partially
+    /// written one is removed. See [`save_to_file_with`](Self::save_to_file_with)
+    /// to change this.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run() -> Result<(), rquest::Error> {
+    /// let written = rquest::get("http://httpbin.org/bytes/1024")
+    ///     .await?
+    ///     .save_to_file("download.bin")
+    ///     .await?;
+    /// println!("{written} bytes");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub async fn save_to_file<P: AsRef<std::path::Path>>(self, path: P) -> crate::Result<u64> {
+        self.save_to_file_with(path, SaveOptions::new()).await
+    }
+
+    /// Save the response body to the file at `path` with `options`, returning
+    /// the number of bytes written.
+    ///
+    /// Errors creating, writing or renaming the file are returned as
+    /// [`is_write`](crate::Error::is_write) errors.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use rquest::SaveOptions;
+    ///
+    /// # async fn run() -> Result<(), rquest::Error> {
+    /// let options = SaveOptions::new().create_dirs(true).keep_partial(true);
+    /// rquest::get("http://httpbin.org/bytes/1024")
+    ///     .await?
+    ///     .save_to_file_with("downloads/file.bin", options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub async fn save_to_file_with<P: AsRef<std::path::Path>>(
+        mut self,
+        path: P,
+        options: SaveOptions,
+    ) -> crate::Result<u64> {
+        use tokio::io::AsyncWriteExt;
+
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .ok_or_else(|| crate::error::write("no file name in path"))?;
+        if options.create_dirs {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                tokio::fs::create_dir_all(parent)
+                    .await
+                    .map_err(crate::error::write)?;
+            }
+        }
+
+        // the body is written next to the file, and only replaces it once saved
+        static SAVES: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let mut part_name = std::ffi::OsString::from(".");
+        part_name.push(name);
+        part_name.push(format!(
+            ".{}-{}.part",
+            std::process::id(),
+            SAVES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let part = path.with_file_name(part_name);
+
+        let mut file = tokio::fs::File::create(&part)
+            .await
+            .map_err(crate::error::write)?;
+        let res = self.copy_to_async(&mut file).await;
+        if res.is_err() {
+            // finish the writes still in flight before the file is closed
+            if let Err(err) = file.flush().await {
+                log::debug!("failed to flush partial file {:?}: {}", part, err);
+            }
+        }
+        drop(file);
+
+        let res = match res {
+            Ok(written) => tokio::fs::rename(&part, path)
+                .await
+                .map(|()| written)
+                .map_err(crate::error::write),
+            Err(err) => {
+                if options.keep_partial {
+                    if let Err(err) = tokio::fs::rename(&part, path).await {
+                        log::debug!("failed to keep partial file {:?}: {}", path, err);
+                    }
+                }
+                Err(err)
+            }
+        };
+        if res.is_err() {
+            match tokio::fs::remove_file(&part).await {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    log::debug!("failed to remove partial file {:?}: {}", part, err);
+                }
+                _ => (),
+            }
+        }
+        res
+    }
+
     /// Convert the response into a `Stream` of `Bytes` from the body.
     ///
     /// # Example
@@ -354,6 +782,48 @@ impl Response {
         super::body::DataStream(self.res.into_body())
     }
 
+    /// Convert the response into an `AsyncRead` of the body.
+    ///
+    /// The body is decompressed as with [`bytes_stream`](Self::bytes_stream).
+    /// Errors reading it, such as network errors, timeouts or a body over the
+    /// `bytes_limit`, are returned as `io::Error`s wrapping the
+    /// [`Error`](crate::Error).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut body = rquest::get("http://httpbin.org/ip")
+    ///     .await?
+    ///     .into_async_read();
+    ///
+    /// let mut file = tokio::fs::File::create("ip.json").await?;
+    /// tokio::io::copy(&mut body, &mut file).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn into_async_read(self) -> impl tokio::io::AsyncRead + Send + Unpin {
+        use futures_util::TryStreamExt;
+
+        tokio_util::io::StreamReader::new(self.bytes_stream().map_err(crate::Error::into_io))
+    }
+
+    /// Split the response into its status, headers, HTTP version and body.
+    ///
+    /// The body is not read, it can still be streamed, for instance through
+    /// its `http_body::Body` implementation. To keep the extensions as well,
+    /// convert the response into an `http::Response<Body>` instead.
+    pub fn into_parts(self) -> (StatusCode, HeaderMap, Version, Body) {
+        let (parts, body) = self.res.into_parts();
+        (parts.status, parts.headers, parts.version, Body::wrap(body))
+    }
+
     // util methods
 
     /// Turn a response into an error if the server returned an error.
@@ -417,6 +887,39 @@ impl Response {
     }
 }
 
+/// Options for [`Response::save_to_file_with`].
+///
+/// By default, the parent directory of the file must exist, and a failed save
+/// leaves the file as it was.
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SaveOptions {
+    create_dirs: bool,
+    keep_partial: bool,
+}
+
+#[cfg(feature = "stream")]
+impl SaveOptions {
+    /// Creates the default options.
+    pub fn new() -> SaveOptions {
+        SaveOptions::default()
+    }
+
+    /// Sets whether the missing parent directories of the file are created.
+    pub fn create_dirs(mut self, enabled: bool) -> SaveOptions {
+        self.create_dirs = enabled;
+        self
+    }
+
+    /// Sets whether a partially written body still replaces the file if
+    /// saving fails, such as to resume the download with a `Range` request.
+    pub fn keep_partial(mut self, enabled: bool) -> SaveOptions {
+        self.keep_partial = enabled;
+        self
+    }
+}
+
 impl fmt::Debug for Response {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Response")
@@ -435,9 +938,13 @@ impl<T: Into<Body>> From<http::Response<T>> for Response {
 
         let (mut parts, body) = r.into_parts();
         let body: super::body::Body = body.into();
+        let encoded_read = Arc::new(AtomicU64::new(0));
         let decoder = Decoder::detect(
             &mut parts.headers,
-            ResponseBody::new(body.map_err(Into::into)),
+            super::body::counting(
+                ResponseBody::new(body.map_err(Into::into)),
+                encoded_read.clone(),
+            ),
             Accepts::none(),
         );
         let url = parts
@@ -449,6 +956,9 @@ impl<T: Into<Body>> From<http::Response<T>> for Response {
         Response {
             res,
             url: Box::new(url),
+            redirect_history: Vec::new(),
+            encoded_read,
+            is_head: false,
         }
     }
 }
@@ -470,6 +980,70 @@ impl From<Response> for Body {
     }
 }
 
+/// Parses the `content-length` header, if it is present and valid.
+fn header_content_length(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(http::header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// Returns the target of the first link in a `Link` header value whose
+/// relation types include `next`.
+fn next_link(mut value: &str) -> Option<&str> {
+    while let Some(start) = value.find('<') {
+        let end = start + value[start..].find('>')?;
+        let target = &value[start + 1..end];
+        value = &value[end + 1..];
+
+        let mut is_next = false;
+        while let Some(params) = value.trim_start().strip_prefix(';') {
+            let (name, rel, rest) = link_param(params);
+            if name.eq_ignore_ascii_case("rel") {
+                is_next |= rel
+                    .split_ascii_whitespace()
+                    .any(|rel| rel.eq_ignore_ascii_case("next"));
+            }
+            value = rest;
+        }
+
+        if is_next {
+            return Some(target);
+        }
+    }
+    None
+}
+
+/// Splits one `name[=value]` link parameter off the front of `s`, returning
+/// the name, the (unquoted) value and the remainder.
+pub(super) fn link_param(s: &str) -> (&str, &str, &str) {
+    let s = s.trim_start();
+    let name_end = s.find(['=', ';', ',']).unwrap_or(s.len());
+    let (name, s) = (s[..name_end].trim_end(), &s[name_end..]);
+    let Some(s) = s.strip_prefix('=') else {
+        return (name, "", s);
+    };
+
+    let s = s.trim_start();
+    if let Some(quoted) = s.strip_prefix('"') {
+        let mut escaped = false;
+        for (i, c) in quoted.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => return (name, &quoted[..i], &quoted[i + 1..]),
+                _ => {}
+            }
+        }
+        (name, quoted, "")
+    } else {
+        let end = s.find([';', ',']).unwrap_or(s.len());
+        (name, s[..end].trim_end(), &s[end..])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Response;
@@ -490,4 +1064,95 @@ mod tests {
         assert_eq!(response.status(), 200);
         assert_eq!(*response.url(), url);
     }
+
+    #[test]
+    fn test_next_page_url() {
+        let url = Url::parse("https://api.example.com/items?page=2").unwrap();
+        let response = Builder::new()
+            .url(url)
+            .header(
+                "link",
+                r#"</items?page=1>; rel="prev first", <https://api.example.com/items?page=3>; title="a, b; c"; rel="next""#,
+            )
+            .body("")
+            .unwrap();
+        let response = Response::from(response);
+
+        assert_eq!(
+            response.next_page_url().unwrap().as_str(),
+            "https://api.example.com/items?page=3"
+        );
+    }
+
+    #[test]
+    fn test_next_page_url_missing() {
+        let url = Url::parse("https://api.example.com/items?page=3").unwrap();
+        let response = Builder::new()
+            .url(url)
+            .header("link", r#"</items?page=2>; rel=prev"#)
+            .body("")
+            .unwrap();
+        let response = Response::from(response);
+
+        assert_eq!(response.next_page_url(), None);
+    }
+
+    #[test]
+    fn test_alt_svc() {
+        let response = Builder::new()
+            .header(
+                "alt-svc",
+                r#"h3=":443"; ma=3600; persist=1, h3-29=":443"; ma=3600"#,
+            )
+            .header(
+                "alt-svc",
+                r#"h2="alt.example.com:8443", w%3Dx%3Ay="[::1]:80""#,
+            )
+            .header("alt-svc", r#"h3="no-port", quic=":443"; v="46,43""#)
+            .body("")
+            .unwrap();
+        let alt_svc = Response::from(response).alt_svc().unwrap();
+        assert!(!alt_svc.is_clear());
+
+        let services = alt_svc
+            .services()
+            .iter()
+            .map(|service| {
+                (
+                    service.protocol(),
+                    service.host(),
+                    service.port(),
+                    service.max_age().as_secs(),
+                    service.persist(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            services,
+            [
+                ("h3", None, Some(443), 3600, true),
+                ("h3-29", None, Some(443), 3600, false),
+                ("h2", Some("alt.example.com"), Some(8443), 86400, false),
+                ("w=x:y", Some("::1"), Some(80), 86400, false),
+                ("quic", None, Some(443), 86400, false),
+            ]
+        );
+
+        let h3 = alt_svc.find("h3").unwrap();
+        assert_eq!(h3.authority(), ":443");
+    }
+
+    #[test]
+    fn test_alt_svc_clear_and_missing() {
+        let response = Builder::new().header("alt-svc", "clear").body("").unwrap();
+        let alt_svc = Response::from(response).alt_svc().unwrap();
+        assert!(alt_svc.is_clear());
+        assert!(alt_svc.services().is_empty());
+
+        let response = Builder::new().header("alt-svc", "h3").body("").unwrap();
+        assert_eq!(Response::from(response).alt_svc(), None);
+
+        let response = Builder::new().body("").unwrap();
+        assert_eq!(Response::from(response).alt_svc(), None);
+    }
 }
@@ -21,6 +21,24 @@ pub use message::{CloseCode, Message};
 use tokio_util::compat::TokioAsyncReadCompatExt;
 use tungstenite::protocol::WebSocketConfig;
 
+/// The order of the websocket handshake headers in Chrome.
+const HANDSHAKE_HEADERS_ORDER: [header::HeaderName; 14] = [
+    header::HOST,
+    header::CONNECTION,
+    header::PRAGMA,
+    header::CACHE_CONTROL,
+    header::USER_AGENT,
+    header::UPGRADE,
+    header::ORIGIN,
+    header::SEC_WEBSOCKET_VERSION,
+    header::ACCEPT_ENCODING,
+    header::ACCEPT_LANGUAGE,
+    header::COOKIE,
+    header::SEC_WEBSOCKET_KEY,
+    header::SEC_WEBSOCKET_EXTENSIONS,
+    header::SEC_WEBSOCKET_PROTOCOL,
+];
+
 pub type WebSocketStream =
     async_tungstenite::WebSocketStream<tokio_util::compat::Compat<crate::Upgraded>>;
 
@@ -31,6 +49,7 @@ pub struct WebSocketRequestBuilder {
     inner: RequestBuilder,
     nonce: Option<Cow<'static, str>>,
     protocols: Option<Cow<'static, [String]>>,
+    extensions: Option<Cow<'static, str>>,
     config: WebSocketConfig,
 }
 
@@ -40,6 +59,7 @@ impl WebSocketRequestBuilder {
             inner,
             nonce: None,
             protocols: None,
+            extensions: None,
             config: WebSocketConfig::default(),
         }
     }
@@ -103,6 +123,29 @@ impl WebSocketRequestBuilder {
         self
     }
 
+    /// Sets the `Sec-WebSocket-Extensions` offer of the handshake.
+    ///
+    /// No extensions are offered by default. Chrome offers
+    /// `permessage-deflate; client_max_window_bits`, which can be set here
+    /// to match its handshake.
+    ///
+    /// Compressed messages can't be decoded yet, so
+    /// [`WebSocketResponse::into_websocket`] fails if the server accepts
+    /// an offered extension, as most servers do for `permessage-deflate`.
+    pub fn extensions<E>(mut self, extensions: E) -> Self
+    where
+        E: Into<Cow<'static, str>>,
+    {
+        self.extensions = Some(extensions.into());
+        self
+    }
+
+    /// Don't offer any extensions in the handshake.
+    pub fn no_extensions(mut self) -> Self {
+        self.extensions = None;
+        self
+    }
+
     /// Modifies the request builder before sending the request.
     ///
     /// This method allows you to customize the `RequestBuilder` by passing a closure
@@ -197,18 +240,39 @@ impl WebSocketRequestBuilder {
             .unwrap_or_else(|| Cow::Owned(tungstenite::handshake::client::generate_key()));
 
         // HTTP 1 requires us to set some headers.
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_owned(),
+            (None, _) => return Err(error::url_bad_uri(url.clone())),
+        };
         let headers = request.headers_mut();
-        headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        headers
+            .entry(header::HOST)
+            .or_insert(HeaderValue::from_str(&host).map_err(error::builder)?);
+        headers.insert(header::CONNECTION, HeaderValue::from_static("Upgrade"));
+        headers
+            .entry(header::PRAGMA)
+            .or_insert(HeaderValue::from_static("no-cache"));
+        headers
+            .entry(header::CACHE_CONTROL)
+            .or_insert(HeaderValue::from_static("no-cache"));
         headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        headers.insert(
+            header::SEC_WEBSOCKET_VERSION,
+            HeaderValue::from_static("13"),
+        );
         headers.insert(
             header::SEC_WEBSOCKET_KEY,
             HeaderValue::from_str(&nonce)
                 .map_err(|_| Error::new(Kind::Builder, Some("invalid key")))?,
         );
-        headers.insert(
-            header::SEC_WEBSOCKET_VERSION,
-            HeaderValue::from_static("13"),
-        );
+        if let Some(ref extensions) = self.extensions {
+            headers.insert(
+                header::SEC_WEBSOCKET_EXTENSIONS,
+                HeaderValue::from_str(extensions)
+                    .map_err(|_| Error::new(Kind::Builder, Some("invalid extensions")))?,
+            );
+        }
 
         // Set websocket subprotocols
         if let Some(ref protocols) = self.protocols {
@@ -229,6 +293,12 @@ impl WebSocketRequestBuilder {
             }
         }
 
+        // Send the handshake in the order a browser does, unless the
+        // caller picked an order of their own.
+        request
+            .headers_order_mut()
+            .get_or_insert(Cow::Borrowed(&HANDSHAKE_HEADERS_ORDER));
+
         client
             .execute(request)
             .await
@@ -393,6 +463,16 @@ impl WebSocketResponse {
                 }
             }
 
+            // None of the offered extensions is implemented, so the
+            // connection can't continue once the server accepted one.
+            if let Some(header) = headers.get(header::SEC_WEBSOCKET_EXTENSIONS) {
+                log::debug!("server negotiated unsupported extensions: {header:?}");
+                return Err(Error::new(
+                    Kind::Upgrade,
+                    Some(format!("unsupported extensions: {:?}", header)),
+                ));
+            }
+
             let inner = async_tungstenite::WebSocketStream::from_raw_socket(
                 self.inner.upgrade().await?.compat(),
                 async_tungstenite::tungstenite::protocol::Role::Client,
@@ -1,27 +1,31 @@
+pub(crate) use self::counted::ByteCounters;
 use self::tls_conn::BoringTlsConn;
-use crate::tls::{BoringTlsConnector, HttpsConnector, MaybeHttpsStream};
-use crate::util::client::connect::{Connected, Connection};
+pub(crate) use self::transport::Transport;
+use crate::tls::{BoringTlsConnector, HttpsConnector, MaybeHttpsStream, PublicKeyPins, TlsStream};
+use crate::util::client::connect::{Connected, Connection, HttpInfo};
 use crate::util::client::Dst;
+use crate::util::common::rewind::Rewind;
 use crate::util::rt::TokioIo;
 use crate::util::{self, into_uri};
 use antidote::RwLock;
-use http::uri::Scheme;
+use http::uri::{Authority, Scheme};
+use http::Uri;
 use hyper2::rt::{Read, ReadBufCursor, Write};
 use pin_project_lite::pin_project;
 use sealed::{Conn, Unnameable};
-use tokio_boring2::SslStream;
 use tower::util::{BoxCloneSyncServiceLayer, MapRequestLayer};
 use tower::{timeout::TimeoutLayer, util::BoxCloneSyncService, ServiceBuilder};
 use tower_service::Service;
 
 use std::future::Future;
 use std::io::{self, IoSlice};
+use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::dns::DynResolver;
+use crate::dns::{self, DynResolver, Name, Resolve, SrvRecord};
 use crate::error::{cast_to_internal_error, BoxError};
 use crate::proxy::ProxyScheme;
 
@@ -32,11 +36,68 @@ pub(crate) type BoxedConnectorService = BoxCloneSyncService<Unnameable, Conn, Bo
 pub(crate) type BoxedConnectorLayer =
     BoxCloneSyncServiceLayer<BoxedConnectorService, Unnameable, Conn, BoxError>;
 
+pub(crate) type ConnectHook = Arc<dyn Fn(ConnectInfo) + Send + Sync>;
+
+/// Information on a newly established connection, passed to the callback set
+/// with [`ClientBuilder::on_connect`](crate::ClientBuilder::on_connect).
+#[derive(Debug, Clone)]
+pub struct ConnectInfo {
+    remote_addr: Option<SocketAddr>,
+    tls: bool,
+    alpn_protocol: Option<Vec<u8>>,
+    handshake_duration: Duration,
+}
+
+impl ConnectInfo {
+    fn new(conn: &Conn, handshake_duration: Duration) -> ConnectInfo {
+        let mut extensions = http::Extensions::new();
+        conn.inner.connected().get_extras(&mut extensions);
+        let tls_info = conn.inner.tls_info();
+        ConnectInfo {
+            remote_addr: extensions.get::<HttpInfo>().map(HttpInfo::remote_addr),
+            tls: tls_info.is_some(),
+            alpn_protocol: tls_info.and_then(|info| info.alpn_protocol),
+            handshake_duration,
+        }
+    }
+
+    /// Get the address of the peer, which is the proxy for proxied connections.
+    ///
+    /// This is `None` for connections over a custom transport.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// Whether the connection is secured with TLS.
+    pub fn is_tls(&self) -> bool {
+        self.tls
+    }
+
+    /// Get the protocol negotiated with ALPN, such as `h2` or `http/1.1`.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// Get the time it took to establish the connection, from resolving the
+    /// host to the end of the TLS handshake.
+    pub fn handshake_duration(&self) -> Duration {
+        self.handshake_duration
+    }
+}
+
+#[derive(Clone)]
 pub(crate) struct ConnectorBuilder {
     http: HttpConnector,
     tls: BoringTlsConnector,
     verbose: verbose::Wrapper,
+    defensive: defensive::Wrapper,
+    transport: Option<Transport>,
+    srv: Option<Arc<dyn Resolve>>,
+    pins: Option<Arc<PublicKeyPins>>,
+    on_connect: Option<ConnectHook>,
+    bytes: Option<Arc<ByteCounters>>,
     timeout: Option<Duration>,
+    handshake_timeout: Option<Duration>,
     nodelay: bool,
     tls_info: bool,
 }
@@ -49,6 +110,13 @@ impl ConnectorBuilder {
                 http: self.http,
                 tls: InnerTLS::Simple(self.tls),
                 verbose: self.verbose,
+                defensive: self.defensive,
+                transport: self.transport,
+                srv: self.srv,
+                pins: self.pins,
+                on_connect: self.on_connect,
+                bytes: self.bytes,
+                handshake_timeout: self.handshake_timeout,
                 nodelay: self.nodelay,
                 tls_info: self.tls_info,
                 timeout: self.timeout,
@@ -57,10 +125,32 @@ impl ConnectorBuilder {
         }
 
         let inner_tls = InnerTLS::WithSharedState(Arc::new(RwLock::new(self.tls)));
+        let tunnel = ConnectorService {
+            http: self.http.clone(),
+            tls: inner_tls.clone(),
+            verbose: self.verbose,
+            defensive: self.defensive,
+            transport: None,
+            srv: None,
+            pins: None,
+            on_connect: None,
+            bytes: self.bytes.clone(),
+            handshake_timeout: self.handshake_timeout,
+            nodelay: self.nodelay,
+            tls_info: false,
+            timeout: self.timeout,
+        };
         let base_service = ConnectorService {
             http: self.http,
             tls: inner_tls.clone(),
             verbose: self.verbose,
+            defensive: self.defensive,
+            transport: self.transport,
+            srv: self.srv,
+            pins: self.pins,
+            on_connect: self.on_connect,
+            bytes: self.bytes,
+            handshake_timeout: self.handshake_timeout,
             nodelay: self.nodelay,
             tls_info: self.tls_info,
             timeout: None,
@@ -89,7 +179,11 @@ impl ConnectorBuilder {
                     .map_err(cast_to_internal_error)
                     .service(service);
                 let service = BoxCloneSyncService::new(service);
-                Connector::WithLayers { inner_tls, service }
+                Connector::WithLayers {
+                    inner_tls,
+                    service,
+                    tunnel,
+                }
             }
             None => {
                 // no timeout, but still map err
@@ -100,7 +194,11 @@ impl ConnectorBuilder {
                     .map_err(cast_to_internal_error)
                     .service(service);
                 let service = BoxCloneSyncService::new(service);
-                Connector::WithLayers { inner_tls, service }
+                Connector::WithLayers {
+                    inner_tls,
+                    service,
+                    tunnel,
+                }
             }
         }
     }
@@ -112,16 +210,29 @@ impl ConnectorBuilder {
         tls_info: bool,
     ) -> ConnectorBuilder {
         http.enforce_http(false);
+        http.set_nodelay(nodelay);
         ConnectorBuilder {
             http,
             tls,
             verbose: verbose::OFF,
+            defensive: defensive::OFF,
+            transport: None,
+            srv: None,
+            pins: None,
+            on_connect: None,
+            bytes: None,
             timeout: None,
+            handshake_timeout: None,
             nodelay,
             tls_info,
         }
     }
 
+    #[inline]
+    pub(crate) fn set_tls(&mut self, tls: BoringTlsConnector) {
+        self.tls = tls;
+    }
+
     #[inline]
     pub(crate) fn set_keepalive(&mut self, dur: Option<Duration>) {
         self.http.set_keepalive(dur);
@@ -132,10 +243,50 @@ impl ConnectorBuilder {
         self.timeout = timeout;
     }
 
+    #[inline]
+    pub(crate) fn set_handshake_timeout(&mut self, timeout: Option<Duration>) {
+        self.handshake_timeout = timeout;
+    }
+
     #[inline]
     pub(crate) fn set_verbose(&mut self, enabled: bool) {
         self.verbose.0 = enabled;
     }
+
+    #[inline]
+    pub(crate) fn set_defensive_parsing(&mut self, enabled: bool) {
+        self.defensive.strict = enabled;
+    }
+
+    #[inline]
+    pub(crate) fn set_max_response_header_size(&mut self, max: Option<usize>) {
+        self.defensive.max_head_size = max;
+    }
+
+    #[inline]
+    pub(crate) fn set_transport(&mut self, transport: Option<Transport>) {
+        self.transport = transport;
+    }
+
+    #[inline]
+    pub(crate) fn set_srv_resolver(&mut self, resolver: Option<Arc<dyn Resolve>>) {
+        self.srv = resolver;
+    }
+
+    #[inline]
+    pub(crate) fn set_public_key_pins(&mut self, pins: PublicKeyPins) {
+        self.pins = (!pins.is_empty()).then(|| Arc::new(pins));
+    }
+
+    #[inline]
+    pub(crate) fn set_on_connect(&mut self, on_connect: Option<ConnectHook>) {
+        self.on_connect = on_connect;
+    }
+
+    #[inline]
+    pub(crate) fn set_byte_counters(&mut self, bytes: Option<Arc<ByteCounters>>) {
+        self.bytes = bytes;
+    }
 }
 
 #[derive(Clone)]
@@ -147,6 +298,8 @@ pub(crate) enum Connector {
     WithLayers {
         inner_tls: InnerTLS,
         service: BoxedConnectorService,
+        // the base service, for tunnels, which are not connected through the layers
+        tunnel: ConnectorService,
     },
 }
 
@@ -166,6 +319,20 @@ impl Connector {
     }
 }
 
+impl Connector {
+    /// Opens a tunnel to `dst` through its proxy.
+    pub(crate) fn tunnel(&self, dst: Dst) -> Connecting {
+        let service = match self {
+            Connector::Simple(service) => service,
+            Connector::WithLayers { tunnel, .. } => tunnel,
+        };
+        Box::pin(with_timeout(
+            service.clone().connect_tunnel(dst),
+            service.timeout,
+        ))
+    }
+}
+
 impl Service<Dst> for Connector {
     type Response = Conn;
     type Error = BoxError;
@@ -207,6 +374,13 @@ pub(crate) struct ConnectorService {
     http: HttpConnector,
     tls: InnerTLS,
     verbose: verbose::Wrapper,
+    defensive: defensive::Wrapper,
+    transport: Option<Transport>,
+    srv: Option<Arc<dyn Resolve>>,
+    pins: Option<Arc<PublicKeyPins>>,
+    on_connect: Option<ConnectHook>,
+    bytes: Option<Arc<ByteCounters>>,
+    handshake_timeout: Option<Duration>,
     /// When there is a single timeout layer and no other layers,
     /// we embed it directly inside our base Service::call().
     /// This lets us avoid an extra `Box::pin` indirection layer
@@ -235,8 +409,10 @@ impl ConnectorService {
         if dst.scheme() == Some(&Scheme::HTTPS) {
             let http = HttpsConnector::builder(self.http.clone())
                 .alpn_protos(dst.alpn_protos())
+                .server_name(dst.server_name())
                 .interface(dst.take_interface())
                 .addresses(dst.take_addresses())
+                .handshake_timeout(self.handshake_timeout)
                 .build(self.tls.get_tls());
 
             log::trace!("socks HTTPS over proxy");
@@ -246,16 +422,19 @@ impl ConnectorService {
             let io = http.connect(&dst, host, TokioIo::new(conn)).await?;
 
             return Ok(Conn {
-                inner: self.verbose.wrap(BoringTlsConn {
-                    inner: TokioIo::new(io),
-                }),
+                inner: self.defensive.wrap(
+                    BoringTlsConn {
+                        inner: TokioIo::new(io),
+                    },
+                    self.verbose,
+                ),
                 is_proxy: false,
                 tls_info: self.tls_info,
             });
         }
 
         socks::connect(proxy, &dst, dns).await.map(|tcp| Conn {
-            inner: self.verbose.wrap(TokioIo::new(tcp)),
+            inner: self.defensive.wrap(TokioIo::new(tcp), self.verbose),
             is_proxy: false,
             tls_info: false,
         })
@@ -275,11 +454,16 @@ impl ConnectorService {
             http.set_nodelay(true);
         }
 
+        // The server name override is meant for the origin, not the proxy.
+        let server_name = if is_proxy { None } else { dst.server_name() };
+
         log::trace!("connect with maybe proxy");
         let mut http = HttpsConnector::builder(http)
             .alpn_protos(dst.alpn_protos())
+            .server_name(server_name)
             .interface(dst.take_interface())
             .addresses(dst.take_addresses())
+            .handshake_timeout(self.handshake_timeout)
             .build(self.tls.get_tls());
         let io = http.call(dst.into()).await?;
 
@@ -293,19 +477,140 @@ impl ConnectorService {
                     .set_nodelay(false)?;
             }
             Ok(Conn {
-                inner: self.verbose.wrap(BoringTlsConn { inner: stream }),
+                inner: self
+                    .defensive
+                    .wrap(BoringTlsConn { inner: stream }, self.verbose),
                 is_proxy,
                 tls_info: self.tls_info,
             })
         } else {
             Ok(Conn {
-                inner: self.verbose.wrap(io),
+                inner: self.defensive.wrap(io, self.verbose),
                 is_proxy,
                 tls_info: self.tls_info,
             })
         }
     }
 
+    async fn connect_with_srv(
+        self,
+        resolver: Arc<dyn Resolve>,
+        dst: Dst,
+    ) -> Result<Conn, BoxError> {
+        let records = match srv_name(dst.uri()) {
+            Some(name) => resolver.resolve_srv(name).await.unwrap_or_else(|err| {
+                log::debug!("SRV lookup failed: {}", err);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        if records.is_empty() {
+            return self.connect_with_maybe_proxy(dst, false).await;
+        }
+
+        // A lone "." target means the service is decidedly not available.
+        if let [record] = records.as_slice() {
+            if record.target == "." {
+                return Err("SRV record marks the service as unavailable".into());
+            }
+        }
+
+        let mut last_err = None;
+        for record in dns::order_srv_records(records) {
+            match self
+                .clone()
+                .connect_to_srv_target(dst.clone(), &record)
+                .await
+            {
+                Ok(conn) => return Ok(conn),
+                Err(err) => {
+                    log::debug!(
+                        "SRV target {}:{} failed: {}",
+                        record.target,
+                        record.port,
+                        err
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+        Err(last_err.expect("at least one SRV record"))
+    }
+
+    async fn connect_to_srv_target(self, dst: Dst, record: &SrvRecord) -> Result<Conn, BoxError> {
+        log::trace!("connecting to SRV target {}:{}", record.target, record.port);
+        let target = format!("{}:{}", record.target.trim_end_matches('.'), record.port);
+        let target = into_uri(Scheme::HTTP, Authority::try_from(target)?)?;
+        self.connect_to_target(dst, target).await
+    }
+
+    /// Connects to `target`, an SRV target or the endpoint set with
+    /// `RequestBuilder::connect_to`, instead of the host of `dst`.
+    async fn connect_to_target(self, mut dst: Dst, target: Uri) -> Result<Conn, BoxError> {
+        if dst.scheme() == Some(&Scheme::HTTPS) {
+            // Connect to the target, but keep verifying the certificate
+            // against the requested host.
+            let mut http = HttpsConnector::builder(self.http.clone())
+                .alpn_protos(dst.alpn_protos())
+                .server_name(dst.server_name())
+                .interface(dst.take_interface())
+                .addresses(dst.take_addresses())
+                .handshake_timeout(self.handshake_timeout)
+                .build(self.tls.get_tls());
+
+            let host = dst.host().ok_or(crate::error::uri_bad_host())?;
+            let conn = http.call(target).await?;
+            let io = http.connect(&dst, host, conn).await?;
+
+            return Ok(Conn {
+                inner: self.defensive.wrap(
+                    BoringTlsConn {
+                        inner: TokioIo::new(io),
+                    },
+                    self.verbose,
+                ),
+                is_proxy: false,
+                tls_info: self.tls_info,
+            });
+        }
+
+        dst.set_uri(target);
+        self.connect_with_maybe_proxy(dst, false).await
+    }
+
+    async fn connect_over(self, transport: Transport, dst: Dst) -> Result<Conn, BoxError> {
+        log::trace!("connect over custom transport");
+        let io = transport.open(dst.uri().clone()).await?;
+
+        if dst.scheme() == Some(&Scheme::HTTPS) {
+            let http = HttpsConnector::builder(self.http.clone())
+                .alpn_protos(dst.alpn_protos())
+                .server_name(dst.server_name())
+                .handshake_timeout(self.handshake_timeout)
+                .build(self.tls.get_tls());
+
+            let host = dst.host().ok_or(crate::error::uri_bad_host())?;
+            let io = http.connect(&dst, host, TokioIo::new(io)).await?;
+
+            return Ok(Conn {
+                inner: self.defensive.wrap(
+                    BoringTlsConn {
+                        inner: TokioIo::new(io),
+                    },
+                    self.verbose,
+                ),
+                is_proxy: false,
+                tls_info: self.tls_info,
+            });
+        }
+
+        Ok(Conn {
+            inner: self.defensive.wrap(TokioIo::new(io), self.verbose),
+            is_proxy: false,
+            tls_info: false,
+        })
+    }
+
     async fn connect_via_proxy(
         self,
         mut dst: Dst,
@@ -327,21 +632,35 @@ impl ConnectorService {
                 .alpn_protos(dst.alpn_protos())
                 .interface(dst.take_interface())
                 .addresses(dst.take_addresses())
+                .handshake_timeout(self.handshake_timeout)
                 .build(self.tls.get_tls());
 
             let host = dst.host().ok_or(crate::error::uri_bad_host())?;
-            let port = dst.port_u16().unwrap_or(443);
+            let target = connect_to_uri(&dst);
+            let (target_host, port) = match target {
+                Some(ref target) => (target.host().unwrap_or(host), target.port_u16()),
+                None => (host, dst.port_u16()),
+            };
 
             log::trace!("tunneling HTTPS over proxy");
             let conn = http.call(proxy_dst).await?;
-            let tunneled = tunnel::connect(conn, host, port, auth).await?;
+            let (tunneled, rest) =
+                tunnel::connect(conn, target_host, port.unwrap_or(443), auth).await?;
+            if !rest.is_empty() {
+                return Err("unexpected data from the proxy before the TLS handshake".into());
+            }
 
+            // Only the origin handshake uses the server name override.
+            http.set_server_name(dst.server_name());
             let io = http.connect(&dst, host, tunneled).await?;
 
             return Ok(Conn {
-                inner: self.verbose.wrap(BoringTlsConn {
-                    inner: TokioIo::new(io),
-                }),
+                inner: self.defensive.wrap(
+                    BoringTlsConn {
+                        inner: TokioIo::new(io),
+                    },
+                    self.verbose,
+                ),
                 is_proxy: false,
                 tls_info: self.tls_info,
             });
@@ -353,6 +672,71 @@ impl ConnectorService {
     }
 }
 
+impl ConnectorService {
+    async fn connect_tunnel(self, mut dst: Dst) -> Result<Conn, BoxError> {
+        let (proxy_dst, auth) = match dst.take_proxy_scheme() {
+            Some(ProxyScheme::Http { host, auth }) => (into_uri(Scheme::HTTP, host)?, auth),
+            Some(ProxyScheme::Https { host, auth }) => (into_uri(Scheme::HTTPS, host)?, auth),
+            _ => return Err("tunnels require an HTTP or HTTPS proxy".into()),
+        };
+
+        let mut http = HttpsConnector::builder(self.http.clone())
+            .interface(dst.take_interface())
+            .addresses(dst.take_addresses())
+            .handshake_timeout(self.handshake_timeout)
+            .build(self.tls.get_tls());
+
+        let host = dst.host().ok_or(crate::error::uri_bad_host())?;
+        let port = dst.port_u16().unwrap_or(80);
+
+        log::trace!("tunneling to {}:{} over proxy", host, port);
+        let conn = http.call(proxy_dst).await?;
+        let (tunneled, rest) = tunnel::connect(conn, host, port, auth).await?;
+        let tunneled = Rewind {
+            pre: Some(rest),
+            inner: tunneled,
+        };
+
+        // What goes through the tunnel is not HTTP, so it is not parsed defensively.
+        Ok(Conn {
+            inner: self.verbose.wrap(tunneled),
+            is_proxy: false,
+            tls_info: false,
+        })
+    }
+}
+
+/// The endpoint to connect to instead of the host of `dst`, if any, with the
+/// port of `dst` when the endpoint has none.
+fn connect_to_uri(dst: &Dst) -> Option<Uri> {
+    let endpoint = &dst.connect_to()?.0;
+    let authority = match endpoint.port() {
+        Some(_) => endpoint.clone(),
+        None => {
+            let port = dst.port_u16().unwrap_or(match dst.scheme() {
+                Some(scheme) if *scheme == Scheme::HTTPS => 443,
+                _ => 80,
+            });
+            Authority::try_from(format!("{}:{}", endpoint.host(), port)).ok()?
+        }
+    };
+    into_uri(Scheme::HTTP, authority).ok()
+}
+
+/// The SRV name of the service at `uri`, such as `_https._tcp.example.com`.
+///
+/// URLs with an explicit port or an IP address host are connected to as is.
+fn srv_name(uri: &Uri) -> Option<Name> {
+    if uri.port().is_some() {
+        return None;
+    }
+    let host = uri.host()?;
+    if host.starts_with('[') || host.parse::<std::net::IpAddr>().is_ok() {
+        return None;
+    }
+    format!("_{}._tcp.{}", uri.scheme_str()?, host).parse().ok()
+}
+
 async fn with_timeout<T, F>(f: F, timeout: Option<Duration>) -> Result<T, BoxError>
 where
     F: Future<Output = Result<T, BoxError>>,
@@ -380,17 +764,75 @@ impl Service<Dst> for ConnectorService {
     fn call(&mut self, mut dst: Dst) -> Self::Future {
         log::debug!("starting new connection: {:?}", dst.uri());
 
-        if let Some(proxy_scheme) = dst.take_proxy_scheme() {
-            return Box::pin(with_timeout(
+        let pinned = match self.pins {
+            Some(ref pins) if dst.scheme() == Some(&Scheme::HTTPS) => {
+                Some((pins.clone(), dst.host().unwrap_or_default().to_owned()))
+            }
+            _ => None,
+        };
+
+        let connecting: Connecting = if let Some(transport) = self.transport.clone() {
+            Box::pin(with_timeout(
+                self.clone().connect_over(transport, dst),
+                self.timeout,
+            ))
+        } else if let Some(proxy_scheme) = dst.take_proxy_scheme() {
+            Box::pin(with_timeout(
                 self.clone().connect_via_proxy(dst, proxy_scheme),
                 self.timeout,
-            ));
-        }
+            ))
+        } else if let Some(target) = connect_to_uri(&dst) {
+            Box::pin(with_timeout(
+                self.clone().connect_to_target(dst, target),
+                self.timeout,
+            ))
+        } else if let Some(resolver) = self.srv.clone() {
+            Box::pin(with_timeout(
+                self.clone().connect_with_srv(resolver, dst),
+                self.timeout,
+            ))
+        } else {
+            Box::pin(with_timeout(
+                self.clone().connect_with_maybe_proxy(dst, false),
+                self.timeout,
+            ))
+        };
 
-        Box::pin(with_timeout(
-            self.clone().connect_with_maybe_proxy(dst, false),
-            self.timeout,
-        ))
+        let connecting: Connecting = match self.bytes.clone() {
+            Some(bytes) => Box::pin(async move {
+                let mut conn = connecting.await?;
+                conn.inner = bytes.wrap(conn.inner);
+                Ok(conn)
+            }),
+            None => connecting,
+        };
+
+        let connecting = match pinned {
+            Some((pins, host)) => Box::pin(async move {
+                let conn = connecting.await?;
+                let tls_info = conn.inner.tls_info();
+                pins.verify(
+                    &host,
+                    tls_info
+                        .as_ref()
+                        .and_then(|info| info.peer_certificate_chain()),
+                )?;
+                Ok(conn)
+            }),
+            None => connecting,
+        };
+
+        match self.on_connect.clone() {
+            Some(on_connect) => {
+                let start = Instant::now();
+                Box::pin(async move {
+                    let conn = connecting.await?;
+                    on_connect(ConnectInfo::new(&conn, start.elapsed()));
+                    Ok(conn)
+                })
+            }
+            None => connecting,
+        }
     }
 }
 
@@ -410,39 +852,34 @@ impl TlsInfoFactory for tokio::net::TcpStream {
     }
 }
 
-impl TlsInfoFactory for SslStream<TokioIo<TokioIo<tokio::net::TcpStream>>> {
+impl TlsInfoFactory for TlsStream<TokioIo<TokioIo<tokio::net::TcpStream>>> {
     fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
-        self.ssl()
-            .peer_certificate()
-            .and_then(|c| c.to_der().ok())
-            .map(|c| crate::tls::TlsInfo {
-                peer_certificate: Some(c),
-            })
+        TlsStream::tls_info(self)
     }
 }
 
-impl TlsInfoFactory for SslStream<TokioIo<MaybeHttpsStream<TokioIo<tokio::net::TcpStream>>>> {
+impl TlsInfoFactory for TlsStream<TokioIo<MaybeHttpsStream<TokioIo<tokio::net::TcpStream>>>> {
     fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
-        self.ssl()
-            .peer_certificate()
-            .and_then(|c| c.to_der().ok())
-            .map(|c| crate::tls::TlsInfo {
-                peer_certificate: Some(c),
-            })
+        TlsStream::tls_info(self)
+    }
+}
+
+impl<T: Connection> Connection for Rewind<T> {
+    fn connected(&self) -> Connected {
+        self.inner.connected()
+    }
+}
+
+impl<T: TlsInfoFactory> TlsInfoFactory for Rewind<T> {
+    fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
+        self.inner.tls_info()
     }
 }
 
 impl TlsInfoFactory for MaybeHttpsStream<TokioIo<tokio::net::TcpStream>> {
     fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
         match self {
-            MaybeHttpsStream::Https(tls) => tls
-                .inner()
-                .ssl()
-                .peer_certificate()
-                .and_then(|c| c.to_der().ok())
-                .map(|c| crate::tls::TlsInfo {
-                    peer_certificate: Some(c),
-                }),
+            MaybeHttpsStream::Https(tls) => tls.inner().tls_info(),
             MaybeHttpsStream::Http(_) => None,
         }
     }
@@ -549,7 +986,7 @@ pub(crate) type Connecting = Pin<Box<dyn Future<Output = Result<Conn, BoxError>>
 mod tls_conn {
     use super::TlsInfoFactory;
     use crate::{
-        tls::MaybeHttpsStream,
+        tls::{MaybeHttpsStream, TlsStream},
         util::{
             client::connect::{Connected, Connection},
             rt::TokioIo,
@@ -566,11 +1003,10 @@ mod tls_conn {
         io::{AsyncRead, AsyncWrite},
         net::TcpStream,
     };
-    use tokio_boring2::SslStream;
 
     pin_project! {
         pub(super) struct BoringTlsConn<T> {
-            #[pin] pub(super) inner: TokioIo<SslStream<T>>,
+            #[pin] pub(super) inner: TokioIo<TlsStream<T>>,
         }
     }
 
@@ -596,6 +1032,17 @@ mod tls_conn {
         }
     }
 
+    impl Connection for BoringTlsConn<TokioIo<TokioIo<super::transport::Stream>>> {
+        fn connected(&self) -> Connected {
+            let connected = self.inner.inner().get_ref().connected();
+            if self.inner.inner().ssl().selected_alpn_protocol() == Some(b"h2") {
+                connected.negotiated_h2()
+            } else {
+                connected
+            }
+        }
+    }
+
     impl<T: AsyncRead + AsyncWrite + Unpin> Read for BoringTlsConn<T> {
         fn poll_read(
             self: Pin<&mut Self>,
@@ -649,7 +1096,7 @@ mod tls_conn {
 
     impl<T> TlsInfoFactory for BoringTlsConn<T>
     where
-        TokioIo<SslStream<T>>: TlsInfoFactory,
+        TokioIo<TlsStream<T>>: TlsInfoFactory,
     {
         fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
             self.inner.tls_info()
@@ -657,19 +1104,133 @@ mod tls_conn {
     }
 }
 
+mod transport {
+    use super::TlsInfoFactory;
+    use crate::tls::TlsStream;
+    use crate::util::client::connect::{Connected, Connection};
+    use crate::util::rt::TokioIo;
+    use http::Uri;
+    use std::{
+        fmt,
+        future::Future,
+        io::{self, IoSlice},
+        pin::Pin,
+        sync::Arc,
+        task::{Context, Poll},
+    };
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    trait Io: AsyncRead + AsyncWrite + Send + Sync + Unpin + 'static {}
+
+    impl<T: AsyncRead + AsyncWrite + Send + Sync + Unpin + 'static> Io for T {}
+
+    type Opening = Pin<Box<dyn Future<Output = io::Result<Stream>> + Send>>;
+
+    /// Opens the byte stream of each new connection in place of a TCP socket.
+    #[derive(Clone)]
+    pub(crate) struct Transport(Arc<dyn Fn(Uri) -> Opening + Send + Sync>);
+
+    impl Transport {
+        pub(crate) fn new<F, Fut, S>(open: F) -> Transport
+        where
+            F: Fn(Uri) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = io::Result<S>> + Send + 'static,
+            S: AsyncRead + AsyncWrite + Send + Sync + Unpin + 'static,
+        {
+            Transport(Arc::new(move |uri| {
+                let opening = open(uri);
+                Box::pin(async move { opening.await.map(|io| Stream(Box::new(io))) })
+            }))
+        }
+
+        pub(super) fn open(&self, uri: Uri) -> Opening {
+            (self.0)(uri)
+        }
+    }
+
+    pub(super) struct Stream(Box<dyn Io>);
+
+    impl fmt::Debug for Stream {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Stream")
+        }
+    }
+
+    impl Connection for Stream {
+        fn connected(&self) -> Connected {
+            Connected::new()
+        }
+    }
+
+    impl TlsInfoFactory for Stream {
+        fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
+            None
+        }
+    }
+
+    impl TlsInfoFactory for TlsStream<TokioIo<TokioIo<Stream>>> {
+        fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
+            TlsStream::tls_info(self)
+        }
+    }
+
+    impl AsyncRead for Stream {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            Pin::new(&mut *self.0).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for Stream {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut *self.0).poll_write(cx, buf)
+        }
+
+        fn poll_write_vectored(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &[IoSlice<'_>],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut *self.0).poll_write_vectored(cx, bufs)
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            self.0.is_write_vectored()
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut *self.0).poll_flush(cx)
+        }
+
+        fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut *self.0).poll_shutdown(cx)
+        }
+    }
+}
+
 mod tunnel {
     use super::BoxError;
     use crate::util::rt::TokioIo;
+    use bytes::Bytes;
     use http::HeaderValue;
     use hyper2::rt::{Read, Write};
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
+    /// Sends a CONNECT request to `host:port` over `conn`, returning it along
+    /// with what the proxy sent after its response head.
     pub(super) async fn connect<T>(
         mut conn: T,
         host: &str,
         port: u16,
         auth: Option<HeaderValue>,
-    ) -> Result<T, BoxError>
+    ) -> Result<(T, Bytes), BoxError>
     where
         T: Read + Write + Unpin,
     {
@@ -716,9 +1277,16 @@ mod tunnel {
             pos += n;
 
             let recvd = &buf[..pos];
+            let Some(line_end) = recvd.windows(2).position(|w| w == b"\r\n") else {
+                if pos == buf.len() {
+                    return Err("proxy headers too long for tunnel".into());
+                }
+                continue;
+            };
             if recvd.starts_with(b"HTTP/1.1 200") || recvd.starts_with(b"HTTP/1.0 200") {
-                if recvd.ends_with(b"\r\n\r\n") {
-                    return Ok(conn);
+                if let Some(end) = recvd.windows(4).position(|w| w == b"\r\n\r\n") {
+                    let rest = Bytes::copy_from_slice(&recvd[end + 4..]);
+                    return Ok((conn, rest));
                 }
                 if pos == buf.len() {
                     return Err("proxy headers too long for tunnel".into());
@@ -727,7 +1295,10 @@ mod tunnel {
             } else if recvd.starts_with(b"HTTP/1.1 407") {
                 return Err("proxy authentication required".into());
             } else {
-                return Err("unsuccessful tunnel".into());
+                let status_line = String::from_utf8_lossy(&recvd[..line_end]);
+                return Err(
+                    format!("unsuccessful tunnel, the proxy answered: {status_line}").into(),
+                );
             }
         }
     }
@@ -803,6 +1374,115 @@ mod socks {
     }
 }
 
+mod counted {
+    use crate::util::client::connect::{Connected, Connection};
+    use hyper2::rt::{Read, ReadBufCursor, Write};
+    use std::io::{self, IoSlice};
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+
+    /// The bytes written to and read from the connections of a client, above
+    /// TLS.
+    #[derive(Debug, Default)]
+    pub(crate) struct ByteCounters {
+        pub(crate) sent: AtomicU64,
+        pub(crate) received: AtomicU64,
+    }
+
+    impl ByteCounters {
+        pub(super) fn wrap(self: Arc<Self>, conn: super::BoxConn) -> super::BoxConn {
+            Box::new(Counted {
+                inner: conn,
+                bytes: self,
+            })
+        }
+    }
+
+    struct Counted {
+        inner: super::BoxConn,
+        bytes: Arc<ByteCounters>,
+    }
+
+    impl Connection for Counted {
+        fn connected(&self) -> Connected {
+            self.inner.connected()
+        }
+    }
+
+    impl Read for Counted {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            mut buf: ReadBufCursor<'_>,
+        ) -> Poll<io::Result<()>> {
+            // SAFETY: Passing to a ReadBuf will never de-initialize any bytes.
+            let mut vbuf = hyper2::rt::ReadBuf::uninit(unsafe { buf.as_mut() });
+            match Pin::new(&mut *self.inner).poll_read(cx, vbuf.unfilled()) {
+                Poll::Ready(Ok(())) => {
+                    let len = vbuf.filled().len();
+                    self.bytes.received.fetch_add(len as u64, Ordering::Relaxed);
+                    // SAFETY: The two cursors were for the same buffer. What was
+                    // filled in one is safe in the other.
+                    unsafe {
+                        buf.advance(len);
+                    }
+                    Poll::Ready(Ok(()))
+                }
+                other => other,
+            }
+        }
+    }
+
+    impl Write for Counted {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, io::Error>> {
+            let res = Pin::new(&mut *self.inner).poll_write(cx, buf);
+            if let Poll::Ready(Ok(n)) = res {
+                self.bytes.sent.fetch_add(n as u64, Ordering::Relaxed);
+            }
+            res
+        }
+
+        fn poll_write_vectored(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &[IoSlice<'_>],
+        ) -> Poll<Result<usize, io::Error>> {
+            let res = Pin::new(&mut *self.inner).poll_write_vectored(cx, bufs);
+            if let Poll::Ready(Ok(n)) = res {
+                self.bytes.sent.fetch_add(n as u64, Ordering::Relaxed);
+            }
+            res
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            self.inner.is_write_vectored()
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), io::Error>> {
+            Pin::new(&mut *self.inner).poll_flush(cx)
+        }
+
+        fn poll_shutdown(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<(), io::Error>> {
+            Pin::new(&mut *self.inner).poll_shutdown(cx)
+        }
+    }
+
+    impl super::TlsInfoFactory for Counted {
+        fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
+            self.inner.tls_info()
+        }
+    }
+}
+
 mod verbose {
     use crate::util::client::connect::{Connected, Connection};
     use hyper2::rt::{Read, ReadBufCursor, Write};
@@ -978,3 +1658,447 @@ mod verbose {
         }
     }
 }
+
+mod defensive {
+    use crate::util::client::connect::{Connected, Connection};
+    use hyper2::rt::{Read, ReadBufCursor, Write};
+    use std::fmt;
+    use std::io::{self, IoSlice};
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    /// The longest status or header line accepted, excluding the CRLF.
+    const MAX_LINE_LEN: usize = 8 * 1024;
+
+    const H2_PREFACE: &[u8] = b"PRI * HTTP/2.0";
+
+    pub(super) const OFF: Wrapper = Wrapper {
+        strict: false,
+        max_head_size: None,
+    };
+
+    #[derive(Clone, Copy)]
+    pub(super) struct Wrapper {
+        /// Whether the strict line rules are enforced.
+        pub(super) strict: bool,
+        /// The largest response head accepted, in bytes.
+        pub(super) max_head_size: Option<usize>,
+    }
+
+    impl Wrapper {
+        pub(super) fn wrap<T: super::AsyncConnWithInfo>(
+            &self,
+            conn: T,
+            verbose: super::verbose::Wrapper,
+        ) -> super::BoxConn {
+            // HTTP/2 framing is binary, these checks only apply to HTTP/1.
+            let enabled = self.strict || self.max_head_size.is_some();
+            if enabled && !conn.connected().is_negotiated_h2() {
+                verbose.wrap(Defensive {
+                    inner: conn,
+                    head: Head::new(),
+                    limits: *self,
+                    state: State::AwaitingHead,
+                })
+            } else {
+                verbose.wrap(conn)
+            }
+        }
+    }
+
+    /// Validates every HTTP/1 response head read from the connection before
+    /// hyper parses it.
+    struct Defensive<T> {
+        inner: T,
+        head: Head,
+        limits: Wrapper,
+        state: State,
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        /// Validating a response head.
+        AwaitingHead,
+        /// Passing a response body through until the next request line is
+        /// written.
+        Body,
+        /// The connection speaks HTTP/2 with prior knowledge, or switched
+        /// protocols after a `101` response.
+        Off,
+    }
+
+    struct Head {
+        size: usize,
+        line_len: usize,
+        lines: usize,
+        prev: u8,
+        status: u16,
+    }
+
+    /// How far the bytes passed to [`Head::validate`] got.
+    #[derive(Debug, PartialEq)]
+    enum Progress {
+        /// The head continues past them.
+        Partial,
+        /// The head ended within them, and a body may follow.
+        Done,
+        /// A `101 Switching Protocols` head ended within them, and what
+        /// follows is no longer HTTP/1.
+        Upgraded,
+    }
+
+    #[derive(Debug)]
+    enum Malformed {
+        BareLf,
+        BareCr,
+        Nul,
+        ControlChar(u8),
+        ObsFold,
+        LineTooLong,
+        TooLarge(usize),
+    }
+
+    impl Head {
+        fn new() -> Head {
+            Head {
+                size: 0,
+                line_len: 0,
+                lines: 0,
+                prev: 0,
+                status: 0,
+            }
+        }
+
+        /// Validates `bytes`, returning whether the head ended within them.
+        fn validate(&mut self, bytes: &[u8], limits: &Wrapper) -> Result<Progress, Malformed> {
+            for &b in bytes {
+                let prev = std::mem::replace(&mut self.prev, b);
+                if limits.strict {
+                    self.check(prev, b)?;
+                }
+
+                self.size += 1;
+                if let Some(max) = limits.max_head_size.filter(|&max| self.size > max) {
+                    return Err(Malformed::TooLarge(max));
+                }
+
+                match b {
+                    b'\n' if self.line_len > 0 => {
+                        self.lines += 1;
+                        self.line_len = 0;
+                    }
+                    // leading empty lines are skipped, like hyper does
+                    b'\n' if self.lines == 0 => {}
+                    b'\n' if self.status == 101 => {
+                        *self = Head::new();
+                        return Ok(Progress::Upgraded);
+                    }
+                    // `HTTP/1.1 1xx` is followed by the final response head
+                    b'\n' if (100..200).contains(&self.status) => *self = Head::new(),
+                    b'\n' => {
+                        *self = Head::new();
+                        return Ok(Progress::Done);
+                    }
+                    b'\r' => {}
+                    _ => {
+                        self.line_len += 1;
+                        // the status code follows `HTTP/1.1 `
+                        if self.lines == 0 && (10..=12).contains(&self.line_len) {
+                            let digit = if b.is_ascii_digit() { b - b'0' } else { 0 };
+                            self.status = self.status * 10 + u16::from(digit);
+                        }
+                    }
+                }
+            }
+            Ok(Progress::Partial)
+        }
+
+        /// Applies the strict line rules to `b`, following `prev`.
+        fn check(&self, prev: u8, b: u8) -> Result<(), Malformed> {
+            match b {
+                b'\n' if prev != b'\r' => Err(Malformed::BareLf),
+                b'\n' => Ok(()),
+                _ if prev == b'\r' => Err(Malformed::BareCr),
+                b'\r' => Ok(()),
+                b' ' | b'\t' if self.line_len == 0 && self.lines > 0 => Err(Malformed::ObsFold),
+                0 => Err(Malformed::Nul),
+                0..=0x08 | 0x0a..=0x1f | 0x7f => Err(Malformed::ControlChar(b)),
+                _ if self.line_len >= MAX_LINE_LEN => Err(Malformed::LineTooLong),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    impl fmt::Display for Malformed {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Malformed::BareLf => f.write_str("bare LF in response head"),
+                Malformed::BareCr => f.write_str("bare CR in response head"),
+                Malformed::Nul => f.write_str("NUL in response head"),
+                Malformed::ControlChar(b) => {
+                    write!(f, "control character {:#04x} in response head", b)
+                }
+                Malformed::ObsFold => f.write_str("obsolete line folding in response head"),
+                Malformed::LineTooLong => {
+                    write!(f, "response head line exceeds {} bytes", MAX_LINE_LEN)
+                }
+                Malformed::TooLarge(max) => {
+                    write!(f, "response head exceeds {} bytes", max)
+                }
+            }
+        }
+    }
+
+    impl std::error::Error for Malformed {}
+
+    impl<T: Write + Unpin> Defensive<T> {
+        fn wrote(&mut self, buf: &[u8]) {
+            match self.state {
+                // The first request reveals HTTP/2 prior knowledge.
+                State::AwaitingHead if self.head.lines == 0 && buf.starts_with(H2_PREFACE) => {
+                    self.state = State::Off;
+                }
+                // hyper does not pipeline, so a new request line is only written
+                // once the previous response was read, and its response head
+                // follows. Other writes finish a request body the server
+                // answered early, and the response body is still to be read.
+                State::Body if is_request_line(buf) => self.state = State::AwaitingHead,
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether `buf` starts with an HTTP/1 request line, as hyper writes at
+    /// the start of every request.
+    fn is_request_line(buf: &[u8]) -> bool {
+        let Some(end) = buf.windows(2).position(|w| w == b"\r\n") else {
+            return false;
+        };
+        let line = &buf[..end];
+        let method = line.split(|&b| b == b' ').next().unwrap_or_default();
+        !method.is_empty()
+            && method.iter().all(|&b| b.is_ascii_graphic())
+            && (line.ends_with(b" HTTP/1.1") || line.ends_with(b" HTTP/1.0"))
+    }
+
+    impl<T: Connection + Read + Write + Unpin> Connection for Defensive<T> {
+        fn connected(&self) -> Connected {
+            self.inner.connected()
+        }
+    }
+
+    impl<T: Read + Write + Unpin> Read for Defensive<T> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            mut buf: ReadBufCursor<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            // SAFETY: Passing to a ReadBuf will never de-initialize any bytes.
+            let mut vbuf = hyper2::rt::ReadBuf::uninit(unsafe { buf.as_mut() });
+            match Pin::new(&mut self.inner).poll_read(cx, vbuf.unfilled()) {
+                Poll::Ready(Ok(())) => {
+                    if self.state == State::AwaitingHead {
+                        let limits = self.limits;
+                        match self.head.validate(vbuf.filled(), &limits) {
+                            Ok(Progress::Done) => self.state = State::Body,
+                            Ok(Progress::Upgraded) => self.state = State::Off,
+                            Ok(Progress::Partial) => {}
+                            Err(Malformed::TooLarge(max)) => {
+                                return Poll::Ready(Err(io::Error::new(
+                                    io::ErrorKind::InvalidData,
+                                    crate::error::HeaderTooLarge(max),
+                                )))
+                            }
+                            Err(err) => {
+                                return Poll::Ready(Err(io::Error::new(
+                                    io::ErrorKind::InvalidData,
+                                    err,
+                                )))
+                            }
+                        }
+                    }
+                    let len = vbuf.filled().len();
+                    // SAFETY: The two cursors were for the same buffer. What was
+                    // filled in one is safe in the other.
+                    unsafe {
+                        buf.advance(len);
+                    }
+                    Poll::Ready(Ok(()))
+                }
+                Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+                Poll::Pending => Poll::Pending,
+            }
+        }
+    }
+
+    impl<T: Read + Write + Unpin> Write for Defensive<T> {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, std::io::Error>> {
+            let res = Pin::new(&mut self.inner).poll_write(cx, buf);
+            if let Poll::Ready(Ok(n)) = res {
+                self.wrote(&buf[..n]);
+            }
+            res
+        }
+
+        fn poll_write_vectored(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &[IoSlice<'_>],
+        ) -> Poll<Result<usize, io::Error>> {
+            let res = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+            if let Poll::Ready(Ok(n)) = res {
+                let first = bufs.iter().find(|buf| !buf.is_empty());
+                if let Some(first) = first.filter(|_| n > 0) {
+                    self.wrote(&first[..n.min(first.len())]);
+                }
+            }
+            res
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            self.inner.is_write_vectored()
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<(), std::io::Error>> {
+            Pin::new(&mut self.inner).poll_flush(cx)
+        }
+
+        fn poll_shutdown(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<(), std::io::Error>> {
+            Pin::new(&mut self.inner).poll_shutdown(cx)
+        }
+    }
+
+    impl<T: super::TlsInfoFactory> super::TlsInfoFactory for Defensive<T> {
+        fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
+            self.inner.tls_info()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{is_request_line, Head, Malformed, Progress, Wrapper};
+
+        const STRICT: Wrapper = Wrapper {
+            strict: true,
+            max_head_size: None,
+        };
+
+        fn validate(bytes: &[u8]) -> Result<Progress, Malformed> {
+            Head::new().validate(bytes, &STRICT)
+        }
+
+        #[test]
+        fn accepts_well_formed_head() {
+            assert_eq!(
+                validate(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").unwrap(),
+                Progress::Done
+            );
+            assert_eq!(
+                validate(b"HTTP/1.1 200 OK\r\nContent-Len").unwrap(),
+                Progress::Partial
+            );
+        }
+
+        #[test]
+        fn skips_informational_heads() {
+            let mut head = Head::new();
+            assert_eq!(
+                head.validate(b"HTTP/1.1 100 Continue\r\n\r\n", &STRICT)
+                    .unwrap(),
+                Progress::Partial
+            );
+            assert_eq!(
+                head.validate(b"HTTP/1.1 200 OK\r\n\r\n", &STRICT).unwrap(),
+                Progress::Done
+            );
+        }
+
+        #[test]
+        fn stops_after_switching_protocols() {
+            // a websocket frame follows the head in the same read
+            let bytes = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n\x81\x00";
+            assert_eq!(validate(bytes).unwrap(), Progress::Upgraded);
+        }
+
+        #[test]
+        fn limits_head_size() {
+            const HEAD: &[u8] = b"HTTP/1.1 200 OK\nFoo: bar\n\n";
+            let limits = |max_head_size| Wrapper {
+                strict: false,
+                max_head_size: Some(max_head_size),
+            };
+
+            // without the strict rules, bare LFs still end the head
+            assert_eq!(
+                Head::new().validate(HEAD, &limits(HEAD.len())).unwrap(),
+                Progress::Done
+            );
+            assert!(matches!(
+                Head::new().validate(HEAD, &limits(HEAD.len() - 1)),
+                Err(Malformed::TooLarge(_))
+            ));
+
+            // each head is limited on its own
+            let mut head = Head::new();
+            let limits = limits(24);
+            assert_eq!(
+                head.validate(b"HTTP/1.1 100 Continue\n\n", &limits)
+                    .unwrap(),
+                Progress::Partial
+            );
+            assert_eq!(
+                head.validate(b"HTTP/1.1 200 OK\n\n", &limits).unwrap(),
+                Progress::Done
+            );
+            assert_eq!(
+                head.validate(b"HTTP/1.1 200 OK\n\n", &limits).unwrap(),
+                Progress::Done
+            );
+        }
+
+        #[test]
+        fn detects_request_lines() {
+            assert!(is_request_line(b"GET / HTTP/1.1\r\nHost: a\r\n\r\n"));
+            assert!(is_request_line(b"OPTIONS * HTTP/1.0\r\n"));
+            // the rest of a request body
+            assert!(!is_request_line(b"5\r\nhello\r\n"));
+            assert!(!is_request_line(b"0\r\n\r\n"));
+            assert!(!is_request_line(b"GET / HTTP/1.1"));
+        }
+
+        #[test]
+        fn rejects_malformed_heads() {
+            assert!(matches!(
+                validate(b"HTTP/1.1 200 OK\nFoo: bar\n\n"),
+                Err(Malformed::BareLf)
+            ));
+            assert!(matches!(
+                validate(b"HTTP/1.1 200 OK\r\nFoo: b\rar\r\n\r\n"),
+                Err(Malformed::BareCr)
+            ));
+            assert!(matches!(
+                validate(b"HTTP/1.1 200 OK\r\nFoo: b\0ar\r\n\r\n"),
+                Err(Malformed::Nul)
+            ));
+            assert!(matches!(
+                validate(b"HTTP/1.1 200 OK\r\nFoo: bar\r\n baz\r\n\r\n"),
+                Err(Malformed::ObsFold)
+            ));
+            let long = format!("HTTP/1.1 200 OK\r\nFoo: {}\r\n\r\n", "a".repeat(8 * 1024));
+            assert!(matches!(
+                validate(long.as_bytes()),
+                Err(Malformed::LineTooLong)
+            ));
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! DNS cache
+
+use super::{Addrs, Name, Resolve, Resolving, ResolvingSrv};
+use antidote::Mutex;
+use lru::LruCache;
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Configuration for the client's DNS cache.
+///
+/// The cache sits in front of whichever resolver the client uses (the default
+/// `getaddrinfo` resolver, `hickory-dns`, or a custom [`Resolve`] implementation),
+/// so it applies regardless of the backend.
+#[derive(Debug, Clone)]
+pub struct DnsCacheConfig {
+    /// Maximum number of host names kept in the cache.
+    ///
+    /// Least recently used entries are evicted once the limit is reached.
+    /// A value of `0` disables the cache.
+    pub max_entries: usize,
+    /// Whether to honor the TTL reported by the resolver.
+    ///
+    /// When `false`, or when the resolver does not report a TTL (e.g. `getaddrinfo`),
+    /// records are cached for `max_ttl`.
+    pub respect_ttl: bool,
+    /// The minimum time a record is cached, even if its TTL is lower.
+    pub min_ttl: Duration,
+    /// The maximum time a record is cached, even if its TTL is higher.
+    pub max_ttl: Duration,
+}
+
+impl Default for DnsCacheConfig {
+    fn default() -> Self {
+        DnsCacheConfig {
+            max_entries: 256,
+            respect_ttl: true,
+            min_ttl: Duration::ZERO,
+            max_ttl: Duration::from_secs(300),
+        }
+    }
+}
+
+struct CachedAddrs {
+    addrs: Vec<SocketAddr>,
+    expires_at: Instant,
+}
+
+/// A shared, clearable cache of resolved addresses.
+#[derive(Clone)]
+pub(crate) struct DnsCache {
+    config: Arc<DnsCacheConfig>,
+    entries: Arc<Mutex<LruCache<String, CachedAddrs>>>,
+}
+
+impl DnsCache {
+    /// Returns `None` if the configuration disables caching.
+    pub(crate) fn new(config: DnsCacheConfig) -> Option<DnsCache> {
+        let max_entries = NonZeroUsize::new(config.max_entries)?;
+        Some(DnsCache {
+            config: Arc::new(config),
+            entries: Arc::new(Mutex::new(LruCache::new(max_entries))),
+        })
+    }
+
+    /// Removes all cached entries.
+    pub(crate) fn clear(&self) {
+        self.entries.lock().clear();
+    }
+
+    fn get(&self, host: &str) -> Option<Vec<SocketAddr>> {
+        let mut entries = self.entries.lock();
+        match entries.get(host) {
+            Some(cached) if cached.expires_at > Instant::now() => Some(cached.addrs.clone()),
+            Some(_) => {
+                entries.pop(host);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, host: String, addrs: Vec<SocketAddr>, ttl: Option<Duration>) {
+        let ttl = match ttl {
+            Some(ttl) if self.config.respect_ttl => ttl,
+            _ => self.config.max_ttl,
+        };
+        let ttl = ttl.min(self.config.max_ttl).max(self.config.min_ttl);
+        if ttl.is_zero() || addrs.is_empty() {
+            return;
+        }
+
+        let expires_at = Instant::now() + ttl;
+        self.entries
+            .lock()
+            .put(host, CachedAddrs { addrs, expires_at });
+    }
+}
+
+/// A resolver that consults a [`DnsCache`] before delegating to the wrapped resolver.
+pub(crate) struct CachingResolver {
+    resolver: Arc<dyn Resolve>,
+    cache: DnsCache,
+}
+
+impl CachingResolver {
+    pub(crate) fn new(resolver: Arc<dyn Resolve>, cache: DnsCache) -> Self {
+        CachingResolver { resolver, cache }
+    }
+}
+
+impl Resolve for CachingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_owned();
+        if let Some(addrs) = self.cache.get(&host) {
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            return Box::pin(futures_util::future::ready(Ok(addrs)));
+        }
+
+        let resolving = self.resolver.resolve_with_ttl(name);
+        let cache = self.cache.clone();
+        Box::pin(async move {
+            let (addrs, ttl) = resolving.await?;
+            let addrs = addrs.collect::<Vec<_>>();
+            cache.insert(host, addrs.clone(), ttl);
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+
+    fn resolve_srv(&self, name: Name) -> ResolvingSrv {
+        self.resolver.resolve_srv(name)
+    }
+}
@@ -1,11 +1,12 @@
 //! DNS resolution via the [hickory-resolver](https://github.com/hickory-dns/hickory-dns) crate
 
-use super::{Addrs, Name, Resolve, Resolving};
+use super::{Addrs, Name, Resolve, Resolving, ResolvingSrv, ResolvingWithTtl, SrvRecord};
 pub use hickory_resolver::config::LookupIpStrategy;
 use hickory_resolver::config::{ResolverConfig, ResolverOpts};
 use hickory_resolver::{lookup_ip::LookupIpIntoIter, system_conf, TokioAsyncResolver};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Instant;
 
 /// Wrapper around an `AsyncResolver`, which implements the `Resolve` trait.
 #[derive(Debug, Clone)]
@@ -51,6 +52,36 @@ impl Resolve for HickoryDnsResolver {
             Ok(addrs)
         })
     }
+
+    fn resolve_with_ttl(&self, name: Name) -> ResolvingWithTtl {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let lookup = resolver.state.lookup_ip(name.as_str()).await?;
+            let ttl = lookup
+                .valid_until()
+                .saturating_duration_since(Instant::now());
+            let addrs: Addrs = Box::new(SocketAddrs {
+                iter: lookup.into_iter(),
+            });
+            Ok((addrs, Some(ttl)))
+        })
+    }
+
+    fn resolve_srv(&self, name: Name) -> ResolvingSrv {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let lookup = resolver.state.srv_lookup(name.as_str()).await?;
+            Ok(lookup
+                .iter()
+                .map(|srv| SrvRecord {
+                    priority: srv.priority(),
+                    weight: srv.weight(),
+                    port: srv.port(),
+                    target: srv.target().to_utf8(),
+                })
+                .collect())
+        })
+    }
 }
 
 impl Iterator for SocketAddrs {
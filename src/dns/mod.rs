@@ -1,10 +1,15 @@
 //! DNS resolution
 
+pub use cache::DnsCacheConfig;
+pub(crate) use cache::{CachingResolver, DnsCache};
 #[cfg(feature = "hickory-dns")]
 pub use hickory::{HickoryDnsResolver, LookupIpStrategy};
-pub use resolve::{Addrs, Name, Resolve, Resolving};
-pub(crate) use resolve::{DnsResolverWithOverrides, DynResolver};
+pub(crate) use resolve::{order_srv_records, DnsResolverWithOverrides, DynResolver};
+pub use resolve::{
+    Addrs, IntoResolve, Name, Resolve, Resolving, ResolvingSrv, ResolvingWithTtl, SrvRecord,
+};
 
+pub(crate) mod cache;
 pub(crate) mod gai;
 #[cfg(feature = "hickory-dns")]
 pub(crate) mod hickory;
@@ -8,6 +8,7 @@ use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use crate::error::BoxError;
 
@@ -17,6 +18,26 @@ pub type Addrs = Box<dyn Iterator<Item = SocketAddr> + Send>;
 /// Alias for the `Future` type returned by a DNS resolver.
 pub type Resolving = Pin<Box<dyn Future<Output = Result<Addrs, BoxError>> + Send>>;
 
+/// Alias for the `Future` type returned by [`Resolve::resolve_with_ttl`].
+pub type ResolvingWithTtl =
+    Pin<Box<dyn Future<Output = Result<(Addrs, Option<Duration>), BoxError>> + Send>>;
+
+/// Alias for the `Future` type returned by [`Resolve::resolve_srv`].
+pub type ResolvingSrv = Pin<Box<dyn Future<Output = Result<Vec<SrvRecord>, BoxError>> + Send>>;
+
+/// A DNS SRV record (RFC 2782), locating a service at a host and port.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrvRecord {
+    /// Targets with a lower priority are tried first.
+    pub priority: u16,
+    /// The relative weight of targets with the same priority.
+    pub weight: u16,
+    /// The port the service listens on.
+    pub port: u16,
+    /// The host name of the target.
+    pub target: String,
+}
+
 /// Trait for customizing DNS resolution in rquest.
 pub trait Resolve: Send + Sync {
     /// Performs DNS resolution on a `Name`.
@@ -31,6 +52,49 @@ pub trait Resolve: Send + Sync {
     /// Explicitly specified port in the URL will override any port in the resolved `SocketAddr`s.
     /// Otherwise, port `0` will be replaced by the conventional port for the given scheme (e.g. 80 for http).
     fn resolve(&self, name: Name) -> Resolving;
+
+    /// Performs DNS resolution on a `Name`, additionally reporting how long the
+    /// returned addresses may be cached.
+    ///
+    /// This is used by the client DNS cache when `respect_ttl` is enabled. The
+    /// default implementation delegates to [`Resolve::resolve`] and reports no TTL,
+    /// in which case cached entries live for the configured `max_ttl`.
+    fn resolve_with_ttl(&self, name: Name) -> ResolvingWithTtl {
+        let resolving = self.resolve(name);
+        Box::pin(async move { resolving.await.map(|addrs| (addrs, None)) })
+    }
+
+    /// Looks up the SRV records of a `Name` such as `_https._tcp.example.com`.
+    ///
+    /// This is used when [`ClientBuilder::srv_lookup`](crate::ClientBuilder::srv_lookup)
+    /// is enabled. The default implementation finds no records, in which
+    /// case the host is resolved with [`Resolve::resolve`] as usual.
+    fn resolve_srv(&self, name: Name) -> ResolvingSrv {
+        let _ = name;
+        Box::pin(futures_util::future::ready(Ok(Vec::new())))
+    }
+}
+
+/// Converts a value into a resolver for
+/// [`ClientBuilder::dns_resolver`](crate::ClientBuilder::dns_resolver).
+///
+/// Implemented for `Arc<R>` of any concrete resolver `R`, and for
+/// `Arc<dyn Resolve>`, so a resolver chosen at runtime can be passed as well.
+pub trait IntoResolve {
+    /// Converts `self` into a shared resolver.
+    fn into_resolve(self) -> Arc<dyn Resolve>;
+}
+
+impl<R: Resolve + 'static> IntoResolve for Arc<R> {
+    fn into_resolve(self) -> Arc<dyn Resolve> {
+        self
+    }
+}
+
+impl IntoResolve for Arc<dyn Resolve> {
+    fn into_resolve(self) -> Arc<dyn Resolve> {
+        self
+    }
 }
 
 /// A name that must be resolved to addresses.
@@ -106,6 +170,44 @@ impl Resolve for DnsResolverWithOverrides {
             None => self.dns_resolver.resolve(name),
         }
     }
+
+    fn resolve_srv(&self, name: Name) -> ResolvingSrv {
+        self.dns_resolver.resolve_srv(name)
+    }
+}
+
+/// Orders SRV records for connection attempts, by priority and then by a
+/// weighted random selection among records of the same priority, as
+/// described by RFC 2782.
+pub(crate) fn order_srv_records(mut records: Vec<SrvRecord>) -> Vec<SrvRecord> {
+    use std::hash::{BuildHasher, Hasher};
+
+    records.sort_by_key(|record| record.priority);
+    let mut ordered = Vec::with_capacity(records.len());
+    for group in records.chunk_by(|a, b| a.priority == b.priority) {
+        let mut group = group.to_vec();
+        while !group.is_empty() {
+            let total: u64 = group.iter().map(|r| u64::from(r.weight)).sum();
+            let random = std::collections::hash_map::RandomState::new()
+                .build_hasher()
+                .finish();
+            let mut pick = random % (total + 1);
+            let index = group
+                .iter()
+                .position(|r| {
+                    let weight = u64::from(r.weight);
+                    if pick <= weight {
+                        true
+                    } else {
+                        pick -= weight;
+                        false
+                    }
+                })
+                .unwrap_or(0);
+            ordered.push(group.remove(index));
+        }
+    }
+    ordered
 }
 
 mod sealed {
@@ -137,6 +137,149 @@ impl Error {
         false
     }
 
+    /// Returns true if the TLS handshake of a new connection took longer than
+    /// the [`tls_handshake_timeout`](crate::ClientBuilder::tls_handshake_timeout).
+    pub fn is_handshake_timeout(&self) -> bool {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if err.is::<TlsHandshakeTimedOut>() {
+                return true;
+            }
+            source = err.source();
+        }
+
+        false
+    }
+
+    /// Returns true if the server's certificate chain did not match the public
+    /// keys pinned for its host.
+    pub fn is_pin_mismatch(&self) -> bool {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if err.is::<PinMismatch>() {
+                return true;
+            }
+            source = err.source();
+        }
+
+        false
+    }
+
+    /// Returns the HTTP/2 error code, if the error was caused by the server
+    /// resetting the stream or closing the connection with one.
+    ///
+    /// This tells a `REFUSED_STREAM`, which is safe to retry, apart from
+    /// protocol errors such as `ENHANCE_YOUR_CALM`.
+    pub fn h2_reason(&self) -> Option<crate::Reason> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(h2_err) = err.downcast_ref::<hyper2::h2::Error>() {
+                return h2_err.reason();
+            }
+            if let Some(io) = err.downcast_ref::<io::Error>() {
+                if let Some(h2_err) = io
+                    .get_ref()
+                    .and_then(|inner| inner.downcast_ref::<hyper2::h2::Error>())
+                {
+                    return h2_err.reason();
+                }
+            }
+            source = err.source();
+        }
+
+        None
+    }
+
+    /// Returns the kind of the I/O error that caused this error, if any, such
+    /// as `ConnectionRefused` when connecting or `ConnectionReset` when the
+    /// connection was closed in the middle of a request.
+    ///
+    /// The causes returned by [`source`](StdError::source) are searched for an
+    /// [`io::Error`], looking inside those of kind `Other` that wrap another
+    /// error, and inside HTTP/2 errors.
+    pub fn io_kind(&self) -> Option<io::ErrorKind> {
+        let mut source = self.source();
+        let mut other = None;
+
+        while let Some(mut err) = source {
+            if let Some(h2_err) = err.downcast_ref::<hyper2::h2::Error>() {
+                if let Some(io) = h2_err.get_io() {
+                    err = io;
+                }
+            }
+            if let Some(io) = err.downcast_ref::<io::Error>() {
+                match io.get_ref() {
+                    Some(inner) if io.kind() == io::ErrorKind::Other => {
+                        other = Some(io::ErrorKind::Other);
+                        source = Some(inner);
+                        continue;
+                    }
+                    _ => return Some(io.kind()),
+                }
+            }
+            source = err.source();
+        }
+
+        other
+    }
+
+    /// Returns true if the response body exceeded the limit set with
+    /// `bytes_limit`.
+    pub fn is_body_too_large(&self) -> bool {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if err.is::<BodyTooLarge>() {
+                return true;
+            }
+            source = err.source();
+        }
+
+        false
+    }
+
+    /// Returns true if the response headers exceeded the limit set with
+    /// [`max_response_header_size`](crate::ClientBuilder::max_response_header_size).
+    pub fn is_header_too_large(&self) -> bool {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if err.is::<HeaderTooLarge>() {
+                return true;
+            }
+            if let Some(io) = err.downcast_ref::<io::Error>() {
+                if io
+                    .get_ref()
+                    .is_some_and(|inner| inner.is::<HeaderTooLarge>())
+                {
+                    return true;
+                }
+            }
+            source = err.source();
+        }
+
+        false
+    }
+
+    /// Returns true if the request was turned away because too many requests
+    /// were already waiting on the rate limit, see
+    /// [`ClientBuilder::rate_limit_max_queue`](crate::ClientBuilder::rate_limit_max_queue).
+    pub fn is_rate_limited(&self) -> bool {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if err.is::<RateLimited>() {
+                return true;
+            }
+            source = err.source();
+        }
+
+        false
+    }
+
     /// Returns true if the error is related to the request or response body
     pub fn is_body(&self) -> bool {
         matches!(self.inner.kind, Kind::Body)
@@ -147,6 +290,12 @@ impl Error {
         matches!(self.inner.kind, Kind::Decode)
     }
 
+    /// Returns true if the error is from writing the response body out, such
+    /// as to a file
+    pub fn is_write(&self) -> bool {
+        matches!(self.inner.kind, Kind::Write)
+    }
+
     /// Returns the status code, if the error was generated from a response.
     pub fn status(&self) -> Option<StatusCode> {
         match self.inner.kind {
@@ -199,6 +348,7 @@ impl fmt::Display for Error {
             Kind::Request => f.write_str("error sending request")?,
             Kind::Body => f.write_str("request or response body error")?,
             Kind::Decode => f.write_str("error decoding response body")?,
+            Kind::Write => f.write_str("error writing response body")?,
             Kind::Redirect => f.write_str("error following redirect")?,
             Kind::Upgrade => f.write_str("error upgrading connection")?,
             Kind::Status(ref code) => {
@@ -264,6 +414,7 @@ pub(crate) enum Kind {
     Status(StatusCode),
     Body,
     Decode,
+    Write,
     Upgrade,
 }
 
@@ -281,6 +432,10 @@ pub(crate) fn decode<E: Into<BoxError>>(e: E) -> Error {
     Error::new(Kind::Decode, Some(e))
 }
 
+pub(crate) fn write<E: Into<BoxError>>(e: E) -> Error {
+    Error::new(Kind::Write, Some(e))
+}
+
 pub(crate) fn request<E: Into<BoxError>>(e: E) -> Error {
     Error::new(Kind::Request, Some(e))
 }
@@ -321,7 +476,12 @@ pub(crate) fn into_io(e: BoxError) -> io::Error {
     io::Error::new(io::ErrorKind::Other, e)
 }
 
-#[allow(unused)]
+#[cfg(any(
+    feature = "gzip",
+    feature = "zstd",
+    feature = "brotli",
+    feature = "deflate",
+))]
 pub(crate) fn decode_io(e: io::Error) -> Error {
     if e.get_ref().map(|r| r.is::<Error>()).unwrap_or(false) {
         *e.into_inner()
@@ -346,6 +506,80 @@ impl fmt::Display for TimedOut {
 
 impl StdError for TimedOut {}
 
+#[derive(Debug)]
+pub(crate) struct TlsHandshakeTimedOut;
+
+impl fmt::Display for TlsHandshakeTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("TLS handshake timed out")
+    }
+}
+
+impl StdError for TlsHandshakeTimedOut {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&TimedOut)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ShutDown;
+
+impl fmt::Display for ShutDown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("client was shut down")
+    }
+}
+
+impl StdError for ShutDown {}
+
+#[derive(Debug)]
+pub(crate) struct RateLimited;
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("too many requests waiting on the rate limit")
+    }
+}
+
+impl StdError for RateLimited {}
+
+#[derive(Debug)]
+pub(crate) struct BodyTooLarge;
+
+impl fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("response body exceeds the size limit")
+    }
+}
+
+impl StdError for BodyTooLarge {}
+
+#[derive(Debug)]
+pub(crate) struct HeaderTooLarge(pub(crate) usize);
+
+impl fmt::Display for HeaderTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "response headers exceed {} bytes", self.0)
+    }
+}
+
+impl StdError for HeaderTooLarge {}
+
+#[derive(Debug)]
+pub(crate) struct PinMismatch(pub(crate) String);
+
+impl fmt::Display for PinMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "certificate chain of {} matches none of the pinned public keys",
+            self.0
+        )
+    }
+}
+
+impl StdError for PinMismatch {}
+
 #[derive(Debug)]
 pub(crate) struct BadScheme;
 
@@ -382,6 +616,12 @@ mod tests {
     }
 
     #[test]
+    #[cfg(any(
+        feature = "gzip",
+        feature = "zstd",
+        feature = "brotli",
+        feature = "deflate",
+    ))]
     fn roundtrip_io_error() {
         let orig = super::request("orig");
         // Convert rquest::Error into an io::Error...
@@ -396,6 +636,12 @@ mod tests {
     }
 
     #[test]
+    #[cfg(any(
+        feature = "gzip",
+        feature = "zstd",
+        feature = "brotli",
+        feature = "deflate",
+    ))]
     fn from_unknown_io_error() {
         let orig = io::Error::new(io::ErrorKind::Other, "orly");
         let err = super::decode_io(orig);
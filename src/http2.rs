@@ -32,6 +32,9 @@ pub struct Http2Settings {
     /// Enables or disables server push functionality.
     ///
     /// - **Purpose:** Allows the server to send resources to the client proactively.
+    /// - **Behavior:** Pushed streams are never surfaced; they are reset with `CANCEL`
+    ///   once the response they were promised on is dropped. With push disabled, a
+    ///   `PUSH_PROMISE` is treated as a connection error.
     #[builder(default, setter(into))]
     pub enable_push: Option<bool>,
 
@@ -7,9 +7,14 @@ mod macros;
 mod chrome;
 mod firefox;
 mod okhttp;
+#[cfg(feature = "json")]
+mod profile;
 mod safari;
 
+use crate::tls::CertCompressionAlgorithm;
+use boring2::ssl::{ExtensionType, SslCurve, SslSignatureAlgorithm};
 use http::{HeaderMap, HeaderName};
+use hyper2::PseudoOrder;
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 use Impersonate::*;
@@ -55,6 +60,16 @@ pub struct ImpersonateBuilder {
     impersonate_os: ImpersonateOS,
     skip_http2: bool,
     skip_headers: bool,
+    tls_extension_order: Option<Cow<'static, [ExtensionType]>>,
+    supported_groups: Option<Cow<'static, [SslCurve]>>,
+    key_shares: Option<Cow<'static, [SslCurve]>>,
+    signature_algorithms: Option<Cow<'static, [SslSignatureAlgorithm]>>,
+    cert_compression: Option<Cow<'static, [CertCompressionAlgorithm]>>,
+    post_quantum: Option<bool>,
+    locale: Option<Cow<'static, str>>,
+    user_agent_suffix: Option<Cow<'static, str>>,
+    priority: Option<(u8, bool)>,
+    pseudo_header_order: Option<[PseudoOrder; 4]>,
 }
 
 /// ========= Impersonate impls =========
@@ -119,13 +134,253 @@ impl ImpersonateBuilder {
         self
     }
 
+    /// Sets the exact order of the TLS ClientHello extensions.
+    ///
+    /// Overrides the profile's extension order, including any permutation. Extensions
+    /// left out are not sent; the client fails to build if the order is missing an
+    /// extension required for the handshake, repeats one, or names one BoringSSL
+    /// cannot send.
+    ///
+    /// The `padding` extension cannot be ordered: BoringSSL always appends it
+    /// last, before `pre_shared_key`, to pad a ClientHello of 256 to 511 bytes
+    /// to 512 bytes as Chrome does. The padded length is not configurable.
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The extensions, in the order they should appear.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ImpersonateBuilder` instance.
+    #[inline(always)]
+    pub fn tls_extension_order<T>(mut self, order: T) -> Self
+    where
+        T: Into<Cow<'static, [ExtensionType]>>,
+    {
+        self.tls_extension_order = Some(order.into());
+        self
+    }
+
+    /// Sets the groups of the TLS `supported_groups` extension, in order.
+    ///
+    /// Overrides the profile's groups. Post-quantum hybrids such as
+    /// [`SslCurve::X25519_MLKEM768`] are only offered when listed.
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The supported groups, in the order they should appear.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ImpersonateBuilder` instance.
+    #[inline(always)]
+    pub fn supported_groups<T>(mut self, groups: T) -> Self
+    where
+        T: Into<Cow<'static, [SslCurve]>>,
+    {
+        self.supported_groups = Some(groups.into());
+        self
+    }
+
+    /// Sets the groups the TLS `key_share` extension sends key shares for, in order.
+    ///
+    /// Key shares are sent for the first supported groups, so these groups are
+    /// moved to the front of the supported groups, in this order, and added to
+    /// them if missing. The client fails to build if the list is empty or repeats
+    /// a group.
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - The groups to send key shares for, in the order they should appear.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ImpersonateBuilder` instance.
+    #[inline(always)]
+    pub fn key_shares<T>(mut self, groups: T) -> Self
+    where
+        T: Into<Cow<'static, [SslCurve]>>,
+    {
+        self.key_shares = Some(groups.into());
+        self
+    }
+
+    /// Sets the algorithms of the TLS `signature_algorithms` extension, in order.
+    ///
+    /// Overrides the profile's algorithms. The client fails to build if the
+    /// list is empty, repeats an algorithm, or offers none that a TLS 1.3
+    /// server can use, unless the maximum TLS version is below 1.3.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithms` - The signature algorithms, in the order they should appear.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ImpersonateBuilder` instance.
+    #[inline(always)]
+    pub fn signature_algorithms<T>(mut self, algorithms: T) -> Self
+    where
+        T: Into<Cow<'static, [SslSignatureAlgorithm]>>,
+    {
+        self.signature_algorithms = Some(algorithms.into());
+        self
+    }
+
+    /// Sets the algorithms of the TLS `compress_certificate` extension
+    /// ([RFC 8879](https://datatracker.ietf.org/doc/html/rfc8879)), in order.
+    ///
+    /// Overrides the profile's algorithms. The server may then send its
+    /// certificate compressed with any of them. An empty list leaves the
+    /// extension out, and the client fails to build if the list repeats an
+    /// algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithms` - The compression algorithms, in the order they should appear.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ImpersonateBuilder` instance.
+    #[inline(always)]
+    pub fn cert_compression<T>(mut self, algorithms: T) -> Self
+    where
+        T: Into<Cow<'static, [CertCompressionAlgorithm]>>,
+    {
+        self.cert_compression = Some(algorithms.into());
+        self
+    }
+
+    /// Sets whether a post-quantum hybrid key exchange is offered.
+    ///
+    /// When enabled, profiles offering none get the hybrid group of the browser
+    /// in front of their supported groups, which also sends its (large) key share:
+    /// `X25519Kyber768Draft00` for Chrome and Edge before 131, `X25519MLKEM768`
+    /// otherwise. When disabled, every hybrid group is removed, as in a browser
+    /// with post-quantum key agreement turned off. By default the profile decides.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to offer a post-quantum hybrid key exchange.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ImpersonateBuilder` instance.
+    #[inline(always)]
+    pub fn post_quantum(mut self, enabled: bool) -> Self {
+        self.post_quantum = Some(enabled);
+        self
+    }
+
+    /// Sets the locale of the impersonated browser, such as `"de-DE"`.
+    ///
+    /// This adjusts the `Accept-Language` header to what the browser sends when
+    /// its language is `locale`, instead of `en-US`. The client hints don't depend
+    /// on the locale. A locale that isn't a language tag is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - The language tag of the locale.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ImpersonateBuilder` instance.
+    #[inline(always)]
+    pub fn locale<T>(mut self, locale: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Appends a product token, such as `"MyBot/1.0"`, to the `User-Agent`
+    /// header of the profile.
+    ///
+    /// The suffix is separated from the browser's user agent by a space, which
+    /// is otherwise kept as is, and so are the client hints such as `sec-ch-ua`.
+    /// A suffix that isn't valid in a header value is ignored. Has no effect when
+    /// headers are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `suffix` - The product token to append.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ImpersonateBuilder` instance.
+    #[inline(always)]
+    pub fn user_agent_suffix<T>(mut self, suffix: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.user_agent_suffix = Some(suffix.into());
+        self
+    }
+
+    /// Sets the `Priority` request header (RFC 9218) the impersonated browser sends.
+    ///
+    /// Profiles of browsers that send the header already do, with the priority of
+    /// a top-level navigation (`u=0, i`). The header is sent over every HTTP version;
+    /// PRIORITY_UPDATE frames are not sent. Has no effect when headers are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `urgency` - The urgency, from 0 (highest) to 7 (lowest); larger values are capped at 7.
+    /// * `incremental` - Whether the response can be processed incrementally.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ImpersonateBuilder` instance.
+    #[inline(always)]
+    pub fn priority_header(mut self, urgency: u8, incremental: bool) -> Self {
+        self.priority = Some((urgency.min(7), incremental));
+        self
+    }
+
+    /// Sets the order of the HTTP/2 pseudo-header fields.
+    ///
+    /// Repeated pseudo-headers are sent once, at their first position, and the
+    /// ones left out follow in the default order (`:method`, `:scheme`,
+    /// `:authority`, `:path`), so every request stays valid. Has no effect when
+    /// HTTP/2 is skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The pseudo-headers in the order to send them.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ImpersonateBuilder` instance.
+    #[inline(always)]
+    pub fn pseudo_header_order<T>(mut self, order: T) -> Self
+    where
+        T: IntoIterator<Item = PseudoOrder>,
+    {
+        const DEFAULT: [PseudoOrder; 4] = [
+            PseudoOrder::Method,
+            PseudoOrder::Scheme,
+            PseudoOrder::Authority,
+            PseudoOrder::Path,
+        ];
+
+        let mut complete = Vec::with_capacity(DEFAULT.len());
+        for pseudo in order.into_iter().chain(DEFAULT) {
+            if !complete.contains(&pseudo) {
+                complete.push(pseudo);
+            }
+        }
+        self.pseudo_header_order = complete.try_into().ok();
+        self
+    }
+
     /// Builds the `ImpersonateSettings` instance.
     ///
     /// # Returns
     ///
     /// The constructed `ImpersonateSettings` instance.
     pub fn build(self) -> ImpersonateSettings {
-        impersonate_match!(
+        let mut settings = impersonate_match!(
             self.impersonate,
             self.impersonate_os,
             self.skip_http2,
@@ -191,8 +446,158 @@ impl ImpersonateBuilder {
             Firefox135 => ff135::settings,
             FirefoxPrivate135 => ff_private_135::settings,
             FirefoxAndroid135 => ff_android_135::settings
-        )
+        );
+
+        if let Some(order) = self.tls_extension_order {
+            settings.tls.extension_order = Some(order);
+        }
+
+        if let Some(groups) = self.supported_groups {
+            settings.tls.curves = Some(groups);
+        }
+
+        if let Some(groups) = self.key_shares {
+            settings.tls.key_shares = Some(groups);
+        }
+
+        if let Some(algorithms) = self.signature_algorithms {
+            settings.tls.signature_algorithms = Some(algorithms);
+        }
+
+        if let Some(algorithms) = self.cert_compression {
+            settings.tls.cert_compression_algorithm =
+                (!algorithms.is_empty()).then_some(algorithms);
+        }
+
+        if let (Some(order), Some(http2)) = (self.pseudo_header_order, settings.http2.as_mut()) {
+            http2.headers_pseudo_order = Some(order);
+        }
+
+        match self.post_quantum {
+            Some(true) => {
+                let curves = settings
+                    .tls
+                    .curves
+                    .as_deref()
+                    .unwrap_or(&crate::tls::DEFAULT_CURVES);
+                if !curves
+                    .iter()
+                    .any(|curve| POST_QUANTUM_CURVES.contains(curve))
+                {
+                    let mut with_hybrid = vec![post_quantum_curve(self.impersonate)];
+                    with_hybrid.extend_from_slice(curves);
+                    settings.tls.curves = Some(with_hybrid.into());
+                }
+            }
+            Some(false) => {
+                let classic = |curves: &[SslCurve]| -> Vec<SslCurve> {
+                    curves
+                        .iter()
+                        .filter(|curve| !POST_QUANTUM_CURVES.contains(curve))
+                        .copied()
+                        .collect()
+                };
+                if let Some(curves) = settings.tls.curves.as_deref() {
+                    settings.tls.curves = Some(classic(curves).into());
+                }
+                if let Some(key_shares) = settings.tls.key_shares.as_deref() {
+                    let key_shares = classic(key_shares);
+                    settings.tls.key_shares = (!key_shares.is_empty()).then(|| key_shares.into());
+                }
+            }
+            None => (),
+        }
+
+        if let Some(locale) = self.locale {
+            if let Some(headers) = settings
+                .headers
+                .as_mut()
+                .filter(|headers| headers.contains_key(ACCEPT_LANGUAGE))
+            {
+                match accept_language(&locale, self.impersonate) {
+                    Some(value) => {
+                        headers.insert(ACCEPT_LANGUAGE, value);
+                    }
+                    None => log::warn!("Invalid locale {locale:?}, keeping the default"),
+                }
+            }
+        }
+
+        if let Some(suffix) = self.user_agent_suffix.as_deref().map(str::trim) {
+            if let Some(user_agent) = settings
+                .headers
+                .as_mut()
+                .and_then(|headers| headers.get_mut(USER_AGENT))
+                .filter(|_| !suffix.is_empty())
+            {
+                let mut value = user_agent.as_bytes().to_vec();
+                value.push(b' ');
+                value.extend_from_slice(suffix.as_bytes());
+                match HeaderValue::from_bytes(&value) {
+                    Ok(value) => *user_agent = value,
+                    Err(_) => log::warn!("Invalid User-Agent suffix {suffix:?}, ignoring it"),
+                }
+            }
+        }
+
+        if let Some((urgency, incremental)) = self.priority {
+            if let Some(headers) = settings.headers.as_mut() {
+                let value = if incremental {
+                    format!("u={urgency}, i")
+                } else {
+                    format!("u={urgency}")
+                };
+                headers.insert(
+                    HeaderName::from_static("priority"),
+                    HeaderValue::from_str(&value).expect("valid priority header"),
+                );
+            }
+        }
+
+        settings
+    }
+}
+
+/// The post-quantum hybrid groups BoringSSL supports.
+const POST_QUANTUM_CURVES: [SslCurve; 5] = [
+    SslCurve::X25519_MLKEM768,
+    SslCurve::X25519_KYBER768_DRAFT00,
+    SslCurve::X25519_KYBER768_DRAFT00_OLD,
+    SslCurve::X25519_KYBER512_DRAFT00,
+    SslCurve::P256_KYBER768_DRAFT00,
+];
+
+/// The post-quantum hybrid group `impersonate` offers when it is turned on.
+fn post_quantum_curve(impersonate: Impersonate) -> SslCurve {
+    match impersonate {
+        Chrome100 | Chrome101 | Chrome104 | Chrome105 | Chrome106 | Chrome107 | Chrome108
+        | Chrome109 | Chrome114 | Chrome116 | Chrome117 | Chrome118 | Chrome119 | Chrome120
+        | Chrome123 | Chrome124 | Chrome126 | Chrome127 | Chrome128 | Chrome129 | Chrome130
+        | Edge101 | Edge122 | Edge127 => SslCurve::X25519_KYBER768_DRAFT00,
+        _ => SslCurve::X25519_MLKEM768,
+    }
+}
+
+/// The `Accept-Language` header `impersonate` sends when its language is `locale`.
+fn accept_language(locale: &str, impersonate: Impersonate) -> Option<HeaderValue> {
+    if locale.is_empty()
+        || !locale
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    {
+        return None;
     }
+
+    let q = match impersonate {
+        Firefox109 | Firefox117 | Firefox128 | Firefox133 | Firefox135 | FirefoxPrivate135
+        | FirefoxAndroid135 => "0.5",
+        _ => "0.9",
+    };
+    let value = match locale.split_once('-') {
+        Some((language, _)) => format!("{locale},{language};q={q}"),
+        None => locale.to_owned(),
+    };
+    HeaderValue::from_str(&value).ok()
 }
 
 /// A struct for impersonate settings.
@@ -218,7 +623,7 @@ impl From<Impersonate> for ImpersonateSettings {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Impersonate {
     Chrome100,
@@ -294,8 +699,122 @@ impl Impersonate {
             impersonate_os: Default::default(),
             skip_http2: false,
             skip_headers: false,
+            tls_extension_order: None,
+            supported_groups: None,
+            key_shares: None,
+            signature_algorithms: None,
+            cert_compression: None,
+            post_quantum: None,
+            locale: None,
+            user_agent_suffix: None,
+            priority: None,
+            pseudo_header_order: None,
         }
     }
+
+    /// Every profile, except for the exact Chrome builds.
+    pub const ALL: &'static [Impersonate] = &[
+        Chrome100,
+        Chrome101,
+        Chrome104,
+        Chrome105,
+        Chrome106,
+        Chrome107,
+        Chrome108,
+        Chrome109,
+        Chrome114,
+        Chrome116,
+        Chrome117,
+        Chrome118,
+        Chrome119,
+        Chrome120,
+        Chrome123,
+        Chrome124,
+        Chrome126,
+        Chrome127,
+        Chrome128,
+        Chrome129,
+        Chrome130,
+        Chrome131,
+        Chrome133,
+        SafariIos17_2,
+        SafariIos17_4_1,
+        SafariIos16_5,
+        Safari15_3,
+        Safari15_5,
+        Safari15_6_1,
+        Safari16,
+        Safari16_5,
+        Safari17_0,
+        Safari17_2_1,
+        Safari17_4_1,
+        Safari17_5,
+        Safari18,
+        SafariIPad18,
+        Safari18_2,
+        SafariIos18_1_1,
+        OkHttp3_9,
+        OkHttp3_11,
+        OkHttp3_13,
+        OkHttp3_14,
+        OkHttp4_9,
+        OkHttp4_10,
+        OkHttp5,
+        Edge101,
+        Edge122,
+        Edge127,
+        Edge131,
+        Firefox109,
+        Firefox117,
+        Firefox128,
+        Firefox133,
+        Firefox135,
+        FirefoxPrivate135,
+        FirefoxAndroid135,
+    ];
+
+    /// Returns the TLS extension types of the ClientHello this profile sends,
+    /// in the order they are sent, GREASE values included.
+    ///
+    /// The ClientHello is generated in memory without any network round trip.
+    /// Profiles that permute their extensions, as Chrome does since version 106,
+    /// return a different order on every call, and GREASE values are random.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the TLS settings of the profile are invalid.
+    pub fn extension_order(&self) -> crate::Result<Vec<u16>> {
+        let settings = ImpersonateSettings::from(*self);
+        crate::tls::BoringTlsConnector::new(settings.tls, None)?.extension_order()
+    }
+
+    /// Picks a random profile out of [`Impersonate::ALL`].
+    pub fn random() -> Impersonate {
+        Impersonate::random_from(Impersonate::ALL)
+    }
+
+    /// Picks a random profile out of `profiles`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `profiles` is empty.
+    pub fn random_from(profiles: &[Impersonate]) -> Impersonate {
+        profiles[crate::util::fast_random() as usize % profiles.len()]
+    }
+
+    /// Picks a profile out of `profiles`, always the same one for the same `seed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `profiles` is empty.
+    pub fn random_from_seed(profiles: &[Impersonate], seed: u64) -> Impersonate {
+        // splitmix64, so that close seeds pick unrelated profiles
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        profiles[(z % profiles.len() as u64) as usize]
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
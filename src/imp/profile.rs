@@ -0,0 +1,839 @@
+//! Impersonation profiles as JSON.
+//!
+//! A profile is the data half of [`ImpersonateSettings`]: the TLS and HTTP/2
+//! fingerprint and the default headers. Settings that decide what is trusted
+//! are not part of it, so loading a shared profile can't weaken a client: the
+//! root certificate store, certificate and hostname verification, the client
+//! identity and early data keep their defaults.
+
+use std::borrow::Cow;
+
+use boring2::ssl::{ExtensionType, SslCurve, SslSignatureAlgorithm};
+use http::{HeaderMap, HeaderName, HeaderValue};
+use hyper2::{Priority, PseudoOrder, SettingsOrder, StreamDependency, StreamId};
+use serde::{Deserialize, Serialize};
+
+use super::ImpersonateSettings;
+use crate::error::{self, Error};
+use crate::http2::Http2Settings;
+use crate::tls::{
+    AlpnProtos, AlpsProtos, CertCompressionAlgorithm, RootCertStore, TlsSettings, TlsVersion,
+};
+
+impl ImpersonateSettings {
+    /// Serializes the fingerprint of these settings as a JSON profile.
+    ///
+    /// The profile covers the TLS ClientHello (cipher suites, groups, key
+    /// shares, signature algorithms, extension order, ...), the HTTP/2
+    /// settings, pseudo-header order and priorities, and the default headers
+    /// and their order. The root certificate store, certificate and hostname
+    /// verification, the client identity and early data are left out.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a default header value is not visible ASCII, or a group or TLS
+    /// extension has no name in the profile format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rquest::{Impersonate, ImpersonateSettings};
+    ///
+    /// let json = ImpersonateSettings::from(Impersonate::Chrome131).to_json()?;
+    /// let settings = ImpersonateSettings::from_json(&json)?;
+    /// # Ok::<(), rquest::Error>(())
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn to_json(&self) -> crate::Result<String> {
+        let profile = Profile::try_from(self)?;
+        serde_json::to_string_pretty(&profile).map_err(error::builder)
+    }
+
+    /// Loads settings from a JSON profile written by [`to_json`](Self::to_json).
+    ///
+    /// Fields left out of the profile take their defaults, so a profile only
+    /// needs to spell out what it changes.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the JSON doesn't describe a profile, or names a group, TLS
+    /// extension or header that isn't known or valid.
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn from_json(json: &str) -> crate::Result<ImpersonateSettings> {
+        serde_json::from_str::<Profile>(json)
+            .map_err(error::builder)?
+            .try_into()
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Profile {
+    tls: TlsProfile,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http2: Option<Http2Profile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers: Option<Vec<(String, String)>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers_order: Option<Vec<String>>,
+}
+
+impl TryFrom<&ImpersonateSettings> for Profile {
+    type Error = Error;
+
+    fn try_from(settings: &ImpersonateSettings) -> Result<Self, Error> {
+        let headers = settings
+            .headers
+            .as_ref()
+            .map(|headers| {
+                headers
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = value.to_str().map_err(error::builder)?;
+                        Ok((name.as_str().to_owned(), value.to_owned()))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .transpose()?;
+
+        Ok(Profile {
+            tls: TlsProfile::try_from(&settings.tls)?,
+            http2: settings.http2.as_ref().map(Http2Profile::from),
+            headers,
+            headers_order: settings
+                .headers_order
+                .as_ref()
+                .map(|order| order.iter().map(|name| name.as_str().to_owned()).collect()),
+        })
+    }
+}
+
+impl TryFrom<Profile> for ImpersonateSettings {
+    type Error = Error;
+
+    fn try_from(profile: Profile) -> Result<Self, Error> {
+        let headers = profile
+            .headers
+            .map(|headers| {
+                let mut map = HeaderMap::with_capacity(headers.len());
+                for (name, value) in headers {
+                    map.append(
+                        HeaderName::try_from(name).map_err(error::builder)?,
+                        HeaderValue::try_from(value).map_err(error::builder)?,
+                    );
+                }
+                Ok::<_, Error>(map)
+            })
+            .transpose()?;
+        let headers_order = profile
+            .headers_order
+            .map(|order| {
+                order
+                    .into_iter()
+                    .map(|name| HeaderName::try_from(name).map_err(error::builder))
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .transpose()?;
+
+        Ok(ImpersonateSettings {
+            tls: profile.tls.try_into()?,
+            http2: profile.http2.map(Http2Settings::from),
+            headers,
+            headers_order: headers_order.map(Cow::Owned),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct TlsProfile {
+    tls_sni: bool,
+    alpn_protos: Alpn,
+    alps_protos: Option<Alpn>,
+    alps_use_new_codepoint: bool,
+    session_ticket: bool,
+    min_tls_version: Option<Version>,
+    max_tls_version: Option<Version>,
+    pre_shared_key: bool,
+    enable_ech_grease: bool,
+    permute_extensions: Option<bool>,
+    grease_enabled: Option<bool>,
+    enable_ocsp_stapling: bool,
+    enable_signed_cert_timestamps: bool,
+    record_size_limit: Option<u16>,
+    psk_skip_session_ticket: bool,
+    key_shares_limit: Option<u8>,
+    psk_dhe_ke: bool,
+    renegotiation: bool,
+    delegated_credentials: Option<String>,
+    cipher_list: Option<String>,
+    curves: Option<Vec<String>>,
+    key_shares: Option<Vec<String>>,
+    sigalgs_list: Option<String>,
+    signature_algorithms: Option<Vec<String>>,
+    cert_compression_algorithm: Option<Vec<CertCompression>>,
+    extension_permutation_indices: Option<Vec<u8>>,
+    extension_order: Option<Vec<String>>,
+}
+
+impl Default for TlsProfile {
+    fn default() -> Self {
+        TlsProfile::try_from(&TlsSettings::default()).expect("default TLS settings have names")
+    }
+}
+
+impl TryFrom<&TlsSettings> for TlsProfile {
+    type Error = Error;
+
+    fn try_from(tls: &TlsSettings) -> Result<Self, Error> {
+        let curves = |curves: &[SslCurve]| {
+            curves
+                .iter()
+                .map(|curve| {
+                    curve_name(*curve)
+                        .map(str::to_owned)
+                        .ok_or_else(|| error::builder(format!("unknown group {curve:?}")))
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        };
+        let extension_order = tls
+            .extension_order
+            .as_deref()
+            .map(|extensions| {
+                extensions
+                    .iter()
+                    .map(|extension| {
+                        extension_name(*extension)
+                            .map(str::to_owned)
+                            .ok_or_else(|| {
+                                error::builder(format!("unknown TLS extension {extension:?}"))
+                            })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .transpose()?;
+        let signature_algorithms = tls
+            .signature_algorithms
+            .as_deref()
+            .map(|algorithms| {
+                algorithms
+                    .iter()
+                    .map(|algorithm| {
+                        signature_algorithm_name(*algorithm)
+                            .map(str::to_owned)
+                            .ok_or_else(|| {
+                                error::builder(format!(
+                                    "unknown TLS signature algorithm {algorithm:?}"
+                                ))
+                            })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .transpose()?;
+
+        Ok(TlsProfile {
+            tls_sni: tls.tls_sni,
+            alpn_protos: Alpn::from_alpn(tls.alpn_protos),
+            alps_protos: tls.alps_protos.map(Alpn::from_alps),
+            alps_use_new_codepoint: tls.alps_use_new_codepoint,
+            session_ticket: tls.session_ticket,
+            min_tls_version: tls.min_tls_version.map(Version::from),
+            max_tls_version: tls.max_tls_version.map(Version::from),
+            pre_shared_key: tls.pre_shared_key,
+            enable_ech_grease: tls.enable_ech_grease,
+            permute_extensions: tls.permute_extensions,
+            grease_enabled: tls.grease_enabled,
+            enable_ocsp_stapling: tls.enable_ocsp_stapling,
+            enable_signed_cert_timestamps: tls.enable_signed_cert_timestamps,
+            record_size_limit: tls.record_size_limit,
+            psk_skip_session_ticket: tls.psk_skip_session_ticket,
+            key_shares_limit: tls.key_shares_limit,
+            psk_dhe_ke: tls.psk_dhe_ke,
+            renegotiation: tls.renegotiation,
+            delegated_credentials: tls.delegated_credentials.as_deref().map(str::to_owned),
+            cipher_list: tls.cipher_list.as_deref().map(str::to_owned),
+            curves: tls.curves.as_deref().map(curves).transpose()?,
+            key_shares: tls.key_shares.as_deref().map(curves).transpose()?,
+            sigalgs_list: tls.sigalgs_list.as_deref().map(str::to_owned),
+            signature_algorithms,
+            cert_compression_algorithm: tls.cert_compression_algorithm.as_deref().map(
+                |algorithms| {
+                    algorithms
+                        .iter()
+                        .map(|a| CertCompression::from(*a))
+                        .collect()
+                },
+            ),
+            extension_permutation_indices: tls
+                .extension_permutation_indices
+                .as_deref()
+                .map(Vec::from),
+            extension_order,
+        })
+    }
+}
+
+impl TryFrom<TlsProfile> for TlsSettings {
+    type Error = Error;
+
+    fn try_from(tls: TlsProfile) -> Result<Self, Error> {
+        let curves = |names: Vec<String>| {
+            names
+                .iter()
+                .map(|name| {
+                    CURVES
+                        .iter()
+                        .find(|(known, _)| known == name)
+                        .map(|(_, curve)| *curve)
+                        .ok_or_else(|| error::builder(format!("unknown group {name:?}")))
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        };
+        let extension_order = tls
+            .extension_order
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|name| {
+                        EXTENSIONS
+                            .iter()
+                            .find(|(known, _)| known == name)
+                            .map(|(_, extension)| *extension)
+                            .ok_or_else(|| {
+                                error::builder(format!("unknown TLS extension {name:?}"))
+                            })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .transpose()?;
+        let signature_algorithms = tls
+            .signature_algorithms
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|name| {
+                        SIGNATURE_ALGORITHMS
+                            .iter()
+                            .find(|(known, _)| known == name)
+                            .map(|(_, algorithm)| *algorithm)
+                            .ok_or_else(|| {
+                                error::builder(format!("unknown TLS signature algorithm {name:?}"))
+                            })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .transpose()?;
+
+        Ok(TlsSettings {
+            tls_sni: tls.tls_sni,
+            alpn_protos: tls.alpn_protos.alpn(),
+            alps_protos: tls.alps_protos.map(Alpn::alps),
+            alps_use_new_codepoint: tls.alps_use_new_codepoint,
+            session_ticket: tls.session_ticket,
+            min_tls_version: tls.min_tls_version.map(TlsVersion::from),
+            max_tls_version: tls.max_tls_version.map(TlsVersion::from),
+            pre_shared_key: tls.pre_shared_key,
+            enable_ech_grease: tls.enable_ech_grease,
+            permute_extensions: tls.permute_extensions,
+            grease_enabled: tls.grease_enabled,
+            enable_ocsp_stapling: tls.enable_ocsp_stapling,
+            enable_signed_cert_timestamps: tls.enable_signed_cert_timestamps,
+            record_size_limit: tls.record_size_limit,
+            psk_skip_session_ticket: tls.psk_skip_session_ticket,
+            key_shares_limit: tls.key_shares_limit,
+            psk_dhe_ke: tls.psk_dhe_ke,
+            renegotiation: tls.renegotiation,
+            delegated_credentials: tls.delegated_credentials.map(Cow::Owned),
+            cipher_list: tls.cipher_list.map(Cow::Owned),
+            curves: tls.curves.map(curves).transpose()?.map(Cow::Owned),
+            key_shares: tls.key_shares.map(curves).transpose()?.map(Cow::Owned),
+            sigalgs_list: tls.sigalgs_list.map(Cow::Owned),
+            signature_algorithms: signature_algorithms.map(Cow::Owned),
+            cert_compression_algorithm: tls.cert_compression_algorithm.map(|algorithms| {
+                algorithms
+                    .into_iter()
+                    .map(CertCompressionAlgorithm::from)
+                    .collect()
+            }),
+            extension_permutation_indices: tls.extension_permutation_indices.map(Cow::Owned),
+            extension_order: extension_order.map(Cow::Owned),
+            root_certs_store: RootCertStore::Default,
+            certs_verification: true,
+            verify_hostname: true,
+            identity: None,
+            enable_early_data: false,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum Alpn {
+    Http1,
+    Http2,
+    All,
+}
+
+impl Alpn {
+    fn from_alpn(protos: AlpnProtos) -> Alpn {
+        if protos == AlpnProtos::Http1 {
+            Alpn::Http1
+        } else if protos == AlpnProtos::Http2 {
+            Alpn::Http2
+        } else {
+            Alpn::All
+        }
+    }
+
+    fn from_alps(protos: AlpsProtos) -> Alpn {
+        if protos == AlpsProtos::Http1 {
+            Alpn::Http1
+        } else {
+            Alpn::Http2
+        }
+    }
+
+    fn alpn(self) -> AlpnProtos {
+        match self {
+            Alpn::Http1 => AlpnProtos::Http1,
+            Alpn::Http2 => AlpnProtos::Http2,
+            Alpn::All => AlpnProtos::All,
+        }
+    }
+
+    fn alps(self) -> AlpsProtos {
+        match self {
+            Alpn::Http1 => AlpsProtos::Http1,
+            Alpn::Http2 | Alpn::All => AlpsProtos::Http2,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+enum Version {
+    #[serde(rename = "1.0")]
+    Tls10,
+    #[serde(rename = "1.1")]
+    Tls11,
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+impl From<TlsVersion> for Version {
+    fn from(version: TlsVersion) -> Self {
+        [
+            (TlsVersion::TLS_1_0, Version::Tls10),
+            (TlsVersion::TLS_1_1, Version::Tls11),
+            (TlsVersion::TLS_1_2, Version::Tls12),
+        ]
+        .into_iter()
+        .find(|(known, _)| *known == version)
+        .map_or(Version::Tls13, |(_, version)| version)
+    }
+}
+
+impl From<Version> for TlsVersion {
+    fn from(version: Version) -> Self {
+        match version {
+            Version::Tls10 => TlsVersion::TLS_1_0,
+            Version::Tls11 => TlsVersion::TLS_1_1,
+            Version::Tls12 => TlsVersion::TLS_1_2,
+            Version::Tls13 => TlsVersion::TLS_1_3,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum CertCompression {
+    Brotli,
+    Zlib,
+    Zstd,
+}
+
+impl From<CertCompressionAlgorithm> for CertCompression {
+    fn from(algorithm: CertCompressionAlgorithm) -> Self {
+        match algorithm {
+            CertCompressionAlgorithm::Brotli => CertCompression::Brotli,
+            CertCompressionAlgorithm::Zlib => CertCompression::Zlib,
+            CertCompressionAlgorithm::Zstd => CertCompression::Zstd,
+        }
+    }
+}
+
+impl From<CertCompression> for CertCompressionAlgorithm {
+    fn from(algorithm: CertCompression) -> Self {
+        match algorithm {
+            CertCompression::Brotli => CertCompressionAlgorithm::Brotli,
+            CertCompression::Zlib => CertCompressionAlgorithm::Zlib,
+            CertCompression::Zstd => CertCompressionAlgorithm::Zstd,
+        }
+    }
+}
+
+/// The groups by their BoringSSL names.
+const CURVES: [(&str, SslCurve); 12] = [
+    ("P-224", SslCurve::SECP224R1),
+    ("P-256", SslCurve::SECP256R1),
+    ("P-384", SslCurve::SECP384R1),
+    ("P-521", SslCurve::SECP521R1),
+    ("X25519", SslCurve::X25519),
+    ("ffdhe2048", SslCurve::FFDHE2048),
+    ("ffdhe3072", SslCurve::FFDHE3072),
+    ("X25519MLKEM768", SslCurve::X25519_MLKEM768),
+    ("X25519Kyber768Draft00", SslCurve::X25519_KYBER768_DRAFT00),
+    (
+        "X25519Kyber768Draft00Old",
+        SslCurve::X25519_KYBER768_DRAFT00_OLD,
+    ),
+    ("X25519Kyber512Draft00", SslCurve::X25519_KYBER512_DRAFT00),
+    ("P256Kyber768Draft00", SslCurve::P256_KYBER768_DRAFT00),
+];
+
+fn curve_name(curve: SslCurve) -> Option<&'static str> {
+    CURVES
+        .iter()
+        .find(|(_, known)| *known == curve)
+        .map(|(name, _)| *name)
+}
+
+/// The ClientHello extensions by their IANA names.
+const EXTENSIONS: [(&str, ExtensionType); 30] = [
+    ("server_name", ExtensionType::SERVER_NAME),
+    ("status_request", ExtensionType::STATUS_REQUEST),
+    ("ec_point_formats", ExtensionType::EC_POINT_FORMATS),
+    ("signature_algorithms", ExtensionType::SIGNATURE_ALGORITHMS),
+    ("use_srtp", ExtensionType::SRTP),
+    (
+        "application_layer_protocol_negotiation",
+        ExtensionType::APPLICATION_LAYER_PROTOCOL_NEGOTIATION,
+    ),
+    ("padding", ExtensionType::PADDING),
+    (
+        "extended_master_secret",
+        ExtensionType::EXTENDED_MASTER_SECRET,
+    ),
+    (
+        "quic_transport_parameters_legacy",
+        ExtensionType::QUIC_TRANSPORT_PARAMETERS_LEGACY,
+    ),
+    (
+        "quic_transport_parameters",
+        ExtensionType::QUIC_TRANSPORT_PARAMETERS_STANDARD,
+    ),
+    ("compress_certificate", ExtensionType::CERT_COMPRESSION),
+    ("session_ticket", ExtensionType::SESSION_TICKET),
+    ("supported_groups", ExtensionType::SUPPORTED_GROUPS),
+    ("pre_shared_key", ExtensionType::PRE_SHARED_KEY),
+    ("early_data", ExtensionType::EARLY_DATA),
+    ("supported_versions", ExtensionType::SUPPORTED_VERSIONS),
+    ("cookie", ExtensionType::COOKIE),
+    (
+        "psk_key_exchange_modes",
+        ExtensionType::PSK_KEY_EXCHANGE_MODES,
+    ),
+    (
+        "certificate_authorities",
+        ExtensionType::CERTIFICATE_AUTHORITIES,
+    ),
+    (
+        "signature_algorithms_cert",
+        ExtensionType::SIGNATURE_ALGORITHMS_CERT,
+    ),
+    ("key_share", ExtensionType::KEY_SHARE),
+    ("renegotiation_info", ExtensionType::RENEGOTIATE),
+    ("delegated_credential", ExtensionType::DELEGATED_CREDENTIAL),
+    (
+        "application_settings_old",
+        ExtensionType::APPLICATION_SETTINGS,
+    ),
+    (
+        "application_settings",
+        ExtensionType::APPLICATION_SETTINGS_NEW,
+    ),
+    (
+        "encrypted_client_hello",
+        ExtensionType::ENCRYPTED_CLIENT_HELLO,
+    ),
+    (
+        "signed_certificate_timestamp",
+        ExtensionType::CERTIFICATE_TIMESTAMP,
+    ),
+    ("next_protocol_negotiation", ExtensionType::NEXT_PROTO_NEG),
+    ("channel_id", ExtensionType::CHANNEL_ID),
+    ("record_size_limit", ExtensionType::RECORD_SIZE_LIMIT),
+];
+
+fn extension_name(extension: ExtensionType) -> Option<&'static str> {
+    EXTENSIONS
+        .iter()
+        .find(|(_, known)| *known == extension)
+        .map(|(name, _)| *name)
+}
+
+/// The signature algorithms by their IANA names.
+const SIGNATURE_ALGORITHMS: [(&str, SslSignatureAlgorithm); 13] = [
+    ("rsa_pkcs1_sha1", SslSignatureAlgorithm::RSA_PKCS1_SHA1),
+    ("rsa_pkcs1_sha256", SslSignatureAlgorithm::RSA_PKCS1_SHA256),
+    ("rsa_pkcs1_sha384", SslSignatureAlgorithm::RSA_PKCS1_SHA384),
+    ("rsa_pkcs1_sha512", SslSignatureAlgorithm::RSA_PKCS1_SHA512),
+    (
+        "rsa_pkcs1_md5_sha1",
+        SslSignatureAlgorithm::RSA_PKCS1_MD5_SHA1,
+    ),
+    ("ecdsa_sha1", SslSignatureAlgorithm::ECDSA_SHA1),
+    (
+        "ecdsa_secp256r1_sha256",
+        SslSignatureAlgorithm::ECDSA_SECP256R1_SHA256,
+    ),
+    (
+        "ecdsa_secp384r1_sha384",
+        SslSignatureAlgorithm::ECDSA_SECP384R1_SHA384,
+    ),
+    (
+        "ecdsa_secp521r1_sha512",
+        SslSignatureAlgorithm::ECDSA_SECP521R1_SHA512,
+    ),
+    (
+        "rsa_pss_rsae_sha256",
+        SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256,
+    ),
+    (
+        "rsa_pss_rsae_sha384",
+        SslSignatureAlgorithm::RSA_PSS_RSAE_SHA384,
+    ),
+    (
+        "rsa_pss_rsae_sha512",
+        SslSignatureAlgorithm::RSA_PSS_RSAE_SHA512,
+    ),
+    ("ed25519", SslSignatureAlgorithm::ED25519),
+];
+
+fn signature_algorithm_name(algorithm: SslSignatureAlgorithm) -> Option<&'static str> {
+    SIGNATURE_ALGORITHMS
+        .iter()
+        .find(|(_, known)| *known == algorithm)
+        .map(|(name, _)| *name)
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Http2Profile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    initial_stream_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    initial_connection_window_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    header_table_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enable_push: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_concurrent_streams: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    initial_stream_window_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_frame_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_header_list_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unknown_setting8: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unknown_setting9: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    settings_order: Option<[Setting; 8]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers_priority: Option<Dependency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers_pseudo_order: Option<[Pseudo; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority: Option<Vec<PriorityFrame>>,
+}
+
+impl From<&Http2Settings> for Http2Profile {
+    fn from(http2: &Http2Settings) -> Self {
+        Http2Profile {
+            initial_stream_id: http2.initial_stream_id,
+            initial_connection_window_size: http2.initial_connection_window_size,
+            header_table_size: http2.header_table_size,
+            enable_push: http2.enable_push,
+            max_concurrent_streams: http2.max_concurrent_streams,
+            initial_stream_window_size: http2.initial_stream_window_size,
+            max_frame_size: http2.max_frame_size,
+            max_header_list_size: http2.max_header_list_size,
+            unknown_setting8: http2.unknown_setting8,
+            unknown_setting9: http2.unknown_setting9,
+            settings_order: http2.settings_order.map(|order| order.map(Setting::from)),
+            headers_priority: http2.headers_priority.map(Dependency::from),
+            headers_pseudo_order: http2
+                .headers_pseudo_order
+                .map(|order| order.map(Pseudo::from)),
+            priority: http2
+                .priority
+                .as_deref()
+                .map(|priority| priority.iter().map(PriorityFrame::from).collect()),
+        }
+    }
+}
+
+impl From<Http2Profile> for Http2Settings {
+    fn from(http2: Http2Profile) -> Self {
+        Http2Settings {
+            initial_stream_id: http2.initial_stream_id,
+            initial_connection_window_size: http2.initial_connection_window_size,
+            header_table_size: http2.header_table_size,
+            enable_push: http2.enable_push,
+            max_concurrent_streams: http2.max_concurrent_streams,
+            initial_stream_window_size: http2.initial_stream_window_size,
+            max_frame_size: http2.max_frame_size,
+            max_header_list_size: http2.max_header_list_size,
+            unknown_setting8: http2.unknown_setting8,
+            unknown_setting9: http2.unknown_setting9,
+            settings_order: http2
+                .settings_order
+                .map(|order| order.map(SettingsOrder::from)),
+            headers_priority: http2.headers_priority.map(StreamDependency::from),
+            headers_pseudo_order: http2
+                .headers_pseudo_order
+                .map(|order| order.map(PseudoOrder::from)),
+            priority: http2
+                .priority
+                .map(|priority| priority.into_iter().map(Priority::from).collect()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum Setting {
+    HeaderTableSize,
+    EnablePush,
+    InitialWindowSize,
+    MaxConcurrentStreams,
+    MaxFrameSize,
+    MaxHeaderListSize,
+    UnknownSetting8,
+    UnknownSetting9,
+}
+
+impl From<SettingsOrder> for Setting {
+    fn from(setting: SettingsOrder) -> Self {
+        match setting {
+            SettingsOrder::HeaderTableSize => Setting::HeaderTableSize,
+            SettingsOrder::EnablePush => Setting::EnablePush,
+            SettingsOrder::InitialWindowSize => Setting::InitialWindowSize,
+            SettingsOrder::MaxConcurrentStreams => Setting::MaxConcurrentStreams,
+            SettingsOrder::MaxFrameSize => Setting::MaxFrameSize,
+            SettingsOrder::MaxHeaderListSize => Setting::MaxHeaderListSize,
+            SettingsOrder::UnknownSetting8 => Setting::UnknownSetting8,
+            SettingsOrder::UnknownSetting9 => Setting::UnknownSetting9,
+        }
+    }
+}
+
+impl From<Setting> for SettingsOrder {
+    fn from(setting: Setting) -> Self {
+        match setting {
+            Setting::HeaderTableSize => SettingsOrder::HeaderTableSize,
+            Setting::EnablePush => SettingsOrder::EnablePush,
+            Setting::InitialWindowSize => SettingsOrder::InitialWindowSize,
+            Setting::MaxConcurrentStreams => SettingsOrder::MaxConcurrentStreams,
+            Setting::MaxFrameSize => SettingsOrder::MaxFrameSize,
+            Setting::MaxHeaderListSize => SettingsOrder::MaxHeaderListSize,
+            Setting::UnknownSetting8 => SettingsOrder::UnknownSetting8,
+            Setting::UnknownSetting9 => SettingsOrder::UnknownSetting9,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum Pseudo {
+    Method,
+    Scheme,
+    Authority,
+    Path,
+}
+
+impl From<PseudoOrder> for Pseudo {
+    fn from(pseudo: PseudoOrder) -> Self {
+        match pseudo {
+            PseudoOrder::Method => Pseudo::Method,
+            PseudoOrder::Scheme => Pseudo::Scheme,
+            PseudoOrder::Authority => Pseudo::Authority,
+            PseudoOrder::Path => Pseudo::Path,
+        }
+    }
+}
+
+impl From<Pseudo> for PseudoOrder {
+    fn from(pseudo: Pseudo) -> Self {
+        match pseudo {
+            Pseudo::Method => PseudoOrder::Method,
+            Pseudo::Scheme => PseudoOrder::Scheme,
+            Pseudo::Authority => PseudoOrder::Authority,
+            Pseudo::Path => PseudoOrder::Path,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+struct Dependency {
+    stream_id: u32,
+    weight: u8,
+    exclusive: bool,
+}
+
+impl From<StreamDependency> for Dependency {
+    fn from(dependency: StreamDependency) -> Self {
+        Dependency {
+            stream_id: dependency.dependency_id().into(),
+            weight: dependency.weight(),
+            exclusive: dependency.is_exclusive(),
+        }
+    }
+}
+
+impl From<Dependency> for StreamDependency {
+    fn from(dependency: Dependency) -> Self {
+        StreamDependency::new(
+            StreamId::from(dependency.stream_id),
+            dependency.weight,
+            dependency.exclusive,
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PriorityFrame {
+    stream_id: u32,
+    dependency: Dependency,
+}
+
+impl From<&Priority> for PriorityFrame {
+    fn from(priority: &Priority) -> Self {
+        // The dependency of a priority frame is only readable from its encoding:
+        // a 9 byte frame header followed by the dependency.
+        let mut frame = Vec::with_capacity(14);
+        priority.encode(&mut frame);
+        let dependency =
+            StreamDependency::load(&frame[9..]).expect("priority frame encodes its dependency");
+
+        PriorityFrame {
+            stream_id: priority.stream_id().into(),
+            dependency: dependency.into(),
+        }
+    }
+}
+
+impl From<PriorityFrame> for Priority {
+    fn from(priority: PriorityFrame) -> Self {
+        Priority::new(
+            StreamId::from(priority.stream_id),
+            priority.dependency.into(),
+        )
+    }
+}
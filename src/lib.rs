@@ -223,12 +223,15 @@
 //! - **brotli**: Provides response body brotli decompression.
 //! - **zstd**: Provides response body zstd decompression.
 //! - **deflate**: Provides response body deflate decompression.
-//! - **json**: Provides serialization and deserialization for JSON bodies.
+//! - **json**: Provides serialization and deserialization for JSON bodies, and
+//!   impersonation profiles as JSON.
 //! - **multipart**: Provides functionality for multipart forms.
 //! - **stream**: Adds support for `futures::Stream`.
 //! - **socks**: Provides SOCKS5 proxy support.
 //! - **hickory-dns**: Enables a hickory-dns async resolver instead of default
 //!   threadpool using `getaddrinfo`.
+//! - **cassette**: Records and replays HTTP interactions, see [`cassette`].
+//! - **cache**: Caches responses in memory, see [`cache`].
 //!
 //! [hyper]: http://hyper.rs
 //! [client]: ./struct.Client.html
@@ -387,26 +390,41 @@ pub use self::client::multipart;
 pub use self::client::websocket::{
     CloseCode, Message, WebSocket, WebSocketRequestBuilder, WebSocketResponse,
 };
+#[cfg(any(
+    feature = "gzip",
+    feature = "brotli",
+    feature = "zstd",
+    feature = "deflate"
+))]
+pub use self::client::ContentEncoding;
+#[cfg(feature = "stream")]
+pub use self::client::SaveOptions;
 pub use self::client::{
-    Body, Client, ClientBuilder, ClientMut, Request, RequestBuilder, Response, Upgraded,
+    AltService, AltSvc, Body, Client, ClientBuilder, ClientMut, DefaultHeadersMode, FetchMode,
+    Paginate, PinnedConnection, ReloadKind, Request, RequestBuilder, Response, Upgraded,
 };
+pub use self::connect::ConnectInfo;
 pub use self::imp::{Impersonate, ImpersonateBuilder, ImpersonateOS, ImpersonateSettings};
 pub use self::proxy::{NoProxy, Proxy};
 pub use self::tls::{
-    AlpnProtos, AlpsProtos, CertCompressionAlgorithm, RootCertStore, TlsInfo, TlsSettings,
-    TlsVersion,
+    AlpnProtos, AlpsProtos, CertCompressionAlgorithm, Certificate, Identity, RootCertStore,
+    Sha256Pin, TlsInfo, TlsSettings, TlsVersion,
 };
 pub use self::util::client::Dst;
 pub use boring2::{
-    ssl::{ExtensionType, SslCurve},
+    ssl::{ExtensionType, SslConnectorBuilder, SslCurve, SslSignatureAlgorithm},
     x509::{
         store::{X509Store, X509StoreBuilder},
         X509,
     },
 };
 pub use http2::Http2Settings;
-pub use hyper2::{Priority, PseudoOrder, SettingsOrder, StreamDependency, StreamId};
+pub use hyper2::{h2::Reason, Priority, PseudoOrder, SettingsOrder, StreamDependency, StreamId};
 
+#[cfg(feature = "cache")]
+pub mod cache;
+#[cfg(feature = "cassette")]
+pub mod cassette;
 mod client;
 mod connect;
 #[cfg(feature = "cookies")]
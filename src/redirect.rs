@@ -21,10 +21,41 @@ use crate::Url;
 /// - `limited` can be used have the same as the default behavior, but adjust
 ///   the allowed maximum redirect hops in a chain.
 /// - `none` can be used to disable all redirect behavior.
+/// - `same_origin` can be used to only follow redirects to the same origin.
 /// - `custom` can be used to create a customized policy.
+///
+/// Whatever the policy, the `Authorization`, `Cookie` and `Proxy-Authorization`
+/// headers are not sent along a redirect to another origin, unless disabled
+/// with [`Policy::strip_sensitive_headers`].
 #[derive(Clone)]
 pub struct Policy {
     inner: PolicyKind,
+    preserve_method: bool,
+    redirect_body: RedirectBodyMode,
+    strip_sensitive_headers: bool,
+}
+
+/// What to do when a redirect asks for the request body to be sent again, but
+/// the body is a stream that can't be replayed.
+///
+/// This applies to `307 Temporary Redirect` and `308 Permanent Redirect`, and
+/// to `301` and `302` with [`Policy::preserve_method`]. Bodies that are not
+/// streams are always sent again.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RedirectBodyMode {
+    /// Don't follow the redirect, and return the redirect response.
+    #[default]
+    Stop,
+    /// Fail the request with a redirect error.
+    Error,
+    /// Follow the redirect with a `GET` without a body.
+    SwitchToGet,
+    /// Keep a copy of the body as it is sent, up to the given number of bytes,
+    /// and send it again.
+    ///
+    /// The request fails with a redirect error if the body is larger, or the
+    /// redirect came before the body was sent to the end.
+    Buffer(usize),
 }
 
 /// A type that holds information on the next request and previous requests
@@ -36,6 +67,7 @@ pub struct Attempt<'a> {
     next: &'a Url,
     previous_method: &'a Method,
     previous: &'a [Url],
+    headers: &'a HeaderMap,
 }
 
 /// An action to perform when a redirect status code is found.
@@ -51,6 +83,9 @@ impl Policy {
     pub fn limited(max: usize) -> Self {
         Self {
             inner: PolicyKind::Limit(max),
+            preserve_method: false,
+            redirect_body: RedirectBodyMode::Stop,
+            strip_sensitive_headers: true,
         }
     }
 
@@ -58,6 +93,28 @@ impl Policy {
     pub fn none() -> Self {
         Self {
             inner: PolicyKind::None,
+            preserve_method: false,
+            redirect_body: RedirectBodyMode::Stop,
+            strip_sensitive_headers: true,
+        }
+    }
+
+    /// Create a `Policy` that only follows redirects to the same origin.
+    ///
+    /// Redirects are followed while they keep the scheme, host and port of
+    /// the request. A redirect to another origin is not followed, without an
+    /// error: the redirect response is returned, and its `Location` header
+    /// holds the cross-origin target. As with the default policy, an `Error`
+    /// is returned after 10 redirects.
+    ///
+    /// This keeps credentials and other request headers from being sent to
+    /// another host, for instance during an authentication flow.
+    pub fn same_origin() -> Self {
+        Self {
+            inner: PolicyKind::SameOrigin(10),
+            preserve_method: false,
+            redirect_body: RedirectBodyMode::Stop,
+            strip_sensitive_headers: true,
         }
     }
 
@@ -105,9 +162,65 @@ impl Policy {
     {
         Self {
             inner: PolicyKind::Custom(Arc::new(policy)),
+            preserve_method: false,
+            redirect_body: RedirectBodyMode::Stop,
+            strip_sensitive_headers: true,
         }
     }
 
+    /// Keep the request method and body when following `301` and `302` redirects.
+    ///
+    /// By default, as browsers do, a `POST` (or any method other than `GET` and
+    /// `HEAD`) answered with `301 Moved Permanently` or `302 Found` is followed
+    /// with a `GET` without a body. With this option the redirect is instead
+    /// handled like a `307 Temporary Redirect`: the same method and body are
+    /// sent to the new location, and the redirect is not followed if the body
+    /// is a stream that can't be replayed. `303 See Other` still switches to
+    /// `GET`, since it designates a different resource to fetch.
+    ///
+    /// RFC 9110 allows either behavior for `301` and `302`, but servers commonly
+    /// expect the method change, so only enable this for APIs that rely on it.
+    pub fn preserve_method(mut self, preserve: bool) -> Self {
+        self.preserve_method = preserve;
+        self
+    }
+
+    /// Set what happens to a streaming request body when a redirect asks for
+    /// it to be sent again.
+    ///
+    /// By default the redirect is not followed, and the redirect response is
+    /// returned. See [`RedirectBodyMode`] for the alternatives.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rquest::redirect::{Policy, RedirectBodyMode};
+    ///
+    /// // replay bodies of up to 1 MiB
+    /// let policy = Policy::default().redirect_body(RedirectBodyMode::Buffer(1024 * 1024));
+    /// ```
+    pub fn redirect_body(mut self, mode: RedirectBodyMode) -> Self {
+        self.redirect_body = mode;
+        self
+    }
+
+    /// Strip credentials from requests redirected to another origin.
+    ///
+    /// When a redirect changes the scheme, host or port, the `Authorization`,
+    /// `Cookie` and `Proxy-Authorization` headers of the request are not sent
+    /// to the new location, as browsers and curl do. Cookies of a cookie store
+    /// are still sent where they belong. `Proxy-Authorization` is kept when
+    /// [`ClientBuilder::redirect_with_proxy_auth`] is enabled.
+    ///
+    /// Defaults to `true`. Only disable this when every origin the request
+    /// may be redirected to can be trusted with its credentials.
+    ///
+    /// [`ClientBuilder::redirect_with_proxy_auth`]: crate::ClientBuilder::redirect_with_proxy_auth
+    pub fn strip_sensitive_headers(mut self, strip: bool) -> Self {
+        self.strip_sensitive_headers = strip;
+        self
+    }
+
     /// Apply this policy to a given [`Attempt`] to produce a [`Action`].
     ///
     /// # Note
@@ -138,10 +251,35 @@ impl Policy {
                     attempt.follow()
                 }
             }
+            PolicyKind::SameOrigin(max) => {
+                let same_origin = attempt
+                    .previous
+                    .last()
+                    .map_or(true, |previous| previous.origin() == attempt.next.origin());
+                if !same_origin {
+                    attempt.stop()
+                } else if attempt.previous.len() >= max {
+                    attempt.error(TooManyRedirects)
+                } else {
+                    attempt.follow()
+                }
+            }
             PolicyKind::None => attempt.stop(),
         }
     }
 
+    pub(crate) fn preserves_method(&self) -> bool {
+        self.preserve_method
+    }
+
+    pub(crate) fn redirect_body_mode(&self) -> RedirectBodyMode {
+        self.redirect_body
+    }
+
+    pub(crate) fn strips_sensitive_headers(&self) -> bool {
+        self.strip_sensitive_headers
+    }
+
     pub(crate) fn check(
         &self,
         status: StatusCode,
@@ -149,6 +287,7 @@ impl Policy {
         next: &Url,
         previous_method: &Method,
         previous: &[Url],
+        headers: &HeaderMap,
     ) -> ActionKind {
         self.redirect(Attempt {
             status,
@@ -156,6 +295,7 @@ impl Policy {
             next,
             previous_method,
             previous,
+            headers,
         })
         .inner
     }
@@ -167,9 +307,7 @@ impl Policy {
         cross_proxy_auth: bool,
     ) {
         if let Some(previous) = previous.last() {
-            let cross_host = next.host_str() != previous.host_str()
-                || next.port_or_known_default() != previous.port_or_known_default();
-            if cross_host {
+            if next.origin() != previous.origin() {
                 headers.remove(AUTHORIZATION);
                 headers.remove(COOKIE);
                 headers.remove("cookie2");
@@ -214,6 +352,14 @@ impl Attempt<'_> {
     pub fn previous(&self) -> &[Url] {
         self.previous
     }
+
+    /// Get the headers of the redirect response.
+    ///
+    /// Useful to inspect e.g. `Set-Cookie` on intermediate responses.
+    pub fn headers(&self) -> &HeaderMap {
+        self.headers
+    }
+
     /// Returns an action meaning rquest should follow the next URL.
     pub fn follow(self) -> Action {
         Action {
@@ -244,12 +390,24 @@ impl Attempt<'_> {
 enum PolicyKind {
     Custom(Arc<dyn Fn(Attempt) -> Action + Send + Sync + 'static>),
     Limit(usize),
+    SameOrigin(usize),
     None,
 }
 
 impl fmt::Debug for Policy {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_tuple("Policy").field(&self.inner).finish()
+        let mut f = f.debug_tuple("Policy");
+        f.field(&self.inner);
+        if self.preserve_method {
+            f.field(&"preserve_method");
+        }
+        if self.redirect_body != RedirectBodyMode::Stop {
+            f.field(&self.redirect_body);
+        }
+        if !self.strip_sensitive_headers {
+            f.field(&"keep_sensitive_headers");
+        }
+        f.finish()
     }
 }
 
@@ -258,6 +416,7 @@ impl fmt::Debug for PolicyKind {
         match *self {
             PolicyKind::Custom(..) => f.pad("Custom"),
             PolicyKind::Limit(max) => f.debug_tuple("Limit").field(&max).finish(),
+            PolicyKind::SameOrigin(max) => f.debug_tuple("SameOrigin").field(&max).finish(),
             PolicyKind::None => f.pad("None"),
         }
     }
@@ -283,6 +442,17 @@ impl fmt::Display for TooManyRedirects {
 
 impl StdError for TooManyRedirects {}
 
+#[derive(Debug)]
+pub(crate) struct BodyNotReplayable;
+
+impl fmt::Display for BodyNotReplayable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("request body can't be sent again for the redirect")
+    }
+}
+
+impl StdError for BodyNotReplayable {}
+
 #[test]
 fn test_redirect_policy_limit() {
     let policy = Policy::default();
@@ -297,6 +467,7 @@ fn test_redirect_policy_limit() {
         &next,
         &Method::GET,
         &previous,
+        &HeaderMap::new(),
     ) {
         ActionKind::Follow => (),
         other => panic!("unexpected {:?}", other),
@@ -310,6 +481,7 @@ fn test_redirect_policy_limit() {
         &next,
         &Method::GET,
         &previous,
+        &HeaderMap::new(),
     ) {
         ActionKind::Error(err) if err.is::<TooManyRedirects>() => (),
         other => panic!("unexpected {:?}", other),
@@ -328,6 +500,49 @@ fn test_redirect_policy_limit_to_0() {
         &next,
         &Method::GET,
         &previous,
+        &HeaderMap::new(),
+    ) {
+        ActionKind::Error(err) if err.is::<TooManyRedirects>() => (),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_redirect_policy_same_origin() {
+    let policy = Policy::same_origin();
+    let previous = [Url::parse("https://a.b/login").unwrap()];
+
+    for (next, same_origin) in [
+        ("https://a.b/callback", true),
+        ("https://a.b:443/callback", true),
+        ("http://a.b/callback", false),
+        ("https://a.b:8443/callback", false),
+        ("https://c.a.b/callback", false),
+    ] {
+        let next = Url::parse(next).unwrap();
+        match policy.check(
+            StatusCode::FOUND,
+            &Method::GET,
+            &next,
+            &Method::GET,
+            &previous,
+            &HeaderMap::new(),
+        ) {
+            ActionKind::Follow if same_origin => (),
+            ActionKind::Stop if !same_origin => (),
+            other => panic!("unexpected {:?} for {}", other, next),
+        }
+    }
+
+    let next = Url::parse("https://a.b/z").unwrap();
+    let previous = vec![Url::parse("https://a.b/y").unwrap(); 10];
+    match policy.check(
+        StatusCode::FOUND,
+        &Method::GET,
+        &next,
+        &Method::GET,
+        &previous,
+        &HeaderMap::new(),
     ) {
         ActionKind::Error(err) if err.is::<TooManyRedirects>() => (),
         other => panic!("unexpected {:?}", other),
@@ -345,13 +560,27 @@ fn test_redirect_policy_custom() {
     });
 
     let next = Url::parse("http://bar/baz").unwrap();
-    match policy.check(StatusCode::FOUND, &Method::GET, &next, &Method::GET, &[]) {
+    match policy.check(
+        StatusCode::FOUND,
+        &Method::GET,
+        &next,
+        &Method::GET,
+        &[],
+        &HeaderMap::new(),
+    ) {
         ActionKind::Follow => (),
         other => panic!("unexpected {:?}", other),
     }
 
     let next = Url::parse("http://foo/baz").unwrap();
-    match policy.check(StatusCode::FOUND, &Method::GET, &next, &Method::GET, &[]) {
+    match policy.check(
+        StatusCode::FOUND,
+        &Method::GET,
+        &next,
+        &Method::GET,
+        &[],
+        &HeaderMap::new(),
+    ) {
         ActionKind::Stop => (),
         other => panic!("unexpected {:?}", other),
     }
@@ -372,7 +601,14 @@ fn test_redirect_custom_policy_methods() {
     });
 
     let next = Url::parse("http://bar/baz").unwrap();
-    let res = policy.check(StatusCode::FOUND, &Method::HEAD, &next, &Method::PUT, &[]);
+    let res = policy.check(
+        StatusCode::FOUND,
+        &Method::HEAD,
+        &next,
+        &Method::PUT,
+        &[],
+        &HeaderMap::new(),
+    );
     assert!(matches!(res, ActionKind::Stop));
 }
 
@@ -400,6 +636,20 @@ fn test_remove_sensitive_headers() {
     assert_eq!(headers, filtered_headers);
 }
 
+#[test]
+fn test_remove_sensitive_headers_scheme_change() {
+    use hyper2::header::{HeaderValue, AUTHORIZATION};
+
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, HeaderValue::from_static("let me in"));
+
+    let next = Url::parse("http://initial-domain.com/path").unwrap();
+    let prev = vec![Url::parse("https://initial-domain.com/path").unwrap()];
+
+    Policy::remove_sensitive_headers(&mut headers, &next, &prev, false);
+    assert!(headers.is_empty());
+}
+
 #[test]
 fn test_proxy_auth_redirect_headers() {
     use hyper2::header::{HeaderValue, ACCEPT, AUTHORIZATION, COOKIE};
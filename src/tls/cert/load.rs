@@ -3,33 +3,37 @@ use boring2::x509::{store::X509StoreBuilder, X509};
 use boring2::{error::ErrorStack, x509::store::X509Store};
 use std::sync::LazyLock;
 
-pub static LOAD_CERTS: LazyLock<Option<X509Store>> = LazyLock::new(|| {
+pub static LOAD_CERTS: LazyLock<Option<X509Store>> = LazyLock::new(
+    || match load_certs_from_source(built_in_certs().into_iter()) {
+        Ok(store) => Some(store),
+        Err(err) => {
+            log::error!("tls failed to load root certificates: {err}");
+            None
+        }
+    },
+);
+
+/// The root certificates of `webpki-roots`, or else of the system's native store.
+pub fn built_in_certs() -> Vec<Result<X509, ErrorStack>> {
     #[cfg(feature = "webpki-roots")]
-    let res = {
-        load_certs_from_source(
-            webpki_root_certs::TLS_SERVER_ROOT_CERTS
-                .iter()
-                .map(|c| X509::from_der(c)),
-        )
+    let certs = {
+        webpki_root_certs::TLS_SERVER_ROOT_CERTS
+            .iter()
+            .map(|c| X509::from_der(c))
+            .collect()
     };
 
     #[cfg(all(feature = "native-roots", not(feature = "webpki-roots")))]
-    let res = {
-        load_certs_from_source(
-            rustls_native_certs::load_native_certs()
-                .iter()
-                .map(|c| X509::from_der(&*c)),
-        )
+    let certs = {
+        rustls_native_certs::load_native_certs()
+            .certs
+            .iter()
+            .map(|c| X509::from_der(c))
+            .collect()
     };
 
-    match res {
-        Ok(store) => Some(store),
-        Err(err) => {
-            log::error!("tls failed to load root certificates: {err}");
-            None
-        }
-    }
-});
+    certs
+}
 
 pub fn load_certs_from_source<I>(certs: I) -> Result<X509Store, crate::Error>
 where
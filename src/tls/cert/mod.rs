@@ -3,9 +3,126 @@ pub mod compression;
 mod load;
 
 use super::{sv_handler, TlsResult};
-use boring2::{ssl::SslConnectorBuilder, x509::store::X509Store};
+use boring2::{
+    pkcs12::Pkcs12,
+    pkey::{PKey, Private},
+    ssl::SslConnectorBuilder,
+    x509::{
+        store::{X509Store, X509StoreBuilder},
+        X509,
+    },
+};
 use boring_sys2 as ffi;
 use foreign_types::ForeignTypeRef;
+use std::fmt;
+
+/// A certificate, to be trusted as a root by
+/// [`ClientBuilder::add_root_certificate`](crate::ClientBuilder::add_root_certificate).
+#[derive(Clone)]
+pub struct Certificate(X509);
+
+impl Certificate {
+    /// Parses a PEM encoded certificate.
+    pub fn from_pem(pem: &[u8]) -> crate::Result<Certificate> {
+        X509::from_pem(pem)
+            .map(Certificate)
+            .map_err(crate::error::builder)
+    }
+
+    /// Parses all the certificates of a PEM encoded bundle.
+    pub fn from_pem_bundle(pem: &[u8]) -> crate::Result<Vec<Certificate>> {
+        X509::stack_from_pem(pem)
+            .map(|certs| certs.into_iter().map(Certificate).collect())
+            .map_err(crate::error::builder)
+    }
+
+    /// Parses a DER encoded certificate.
+    pub fn from_der(der: &[u8]) -> crate::Result<Certificate> {
+        X509::from_der(der)
+            .map(Certificate)
+            .map_err(crate::error::builder)
+    }
+}
+
+impl fmt::Debug for Certificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Certificate")
+            .field(&self.0.subject_name())
+            .finish()
+    }
+}
+
+/// A client certificate and its private key, presented when a server
+/// requests client authentication.
+///
+/// See [`ClientBuilder::identity`](crate::ClientBuilder::identity).
+#[derive(Clone)]
+pub struct Identity {
+    cert: X509,
+    key: PKey<Private>,
+    chain: Vec<X509>,
+}
+
+impl Identity {
+    /// Parses a PEM buffer holding a private key and the client certificate,
+    /// optionally followed by its intermediate certificates.
+    pub fn from_pem(pem: &[u8]) -> crate::Result<Identity> {
+        Identity::from_pkcs8_pem(pem, pem)
+    }
+
+    /// Parses a PEM encoded certificate chain and a PEM encoded PKCS#8
+    /// private key.
+    pub fn from_pkcs8_pem(pem: &[u8], key: &[u8]) -> crate::Result<Identity> {
+        let key = PKey::private_key_from_pem(key).map_err(crate::error::builder)?;
+        let mut certs = X509::stack_from_pem(pem)
+            .map_err(crate::error::builder)?
+            .into_iter();
+        let cert = certs
+            .next()
+            .ok_or_else(|| crate::error::builder("no certificate found in PEM"))?;
+        Identity::new(cert, key, certs.collect())
+    }
+
+    /// Parses a DER encoded PKCS#12 archive, decrypting it with `password`.
+    pub fn from_pkcs12_der(der: &[u8], password: &str) -> crate::Result<Identity> {
+        let parsed = Pkcs12::from_der(der)
+            .and_then(|pkcs12| pkcs12.parse(password))
+            .map_err(crate::error::builder)?;
+        let chain = parsed
+            .chain
+            .map(|chain| chain.into_iter().collect())
+            .unwrap_or_default();
+        Identity::new(parsed.cert, parsed.pkey, chain)
+    }
+
+    fn new(cert: X509, key: PKey<Private>, chain: Vec<X509>) -> crate::Result<Identity> {
+        let public_key = cert.public_key().map_err(crate::error::builder)?;
+        if !public_key.public_eq(&key) {
+            return Err(crate::error::builder(
+                "private key does not match the client certificate",
+            ));
+        }
+        Ok(Identity { cert, key, chain })
+    }
+
+    /// Applies the identity to the TLS builder.
+    pub(crate) fn apply(&self, builder: &mut SslConnectorBuilder) -> TlsResult<()> {
+        builder.set_certificate(&self.cert)?;
+        builder.set_private_key(&self.key)?;
+        for cert in &self.chain {
+            builder.add_extra_chain_cert(cert.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Identity")
+            .field("cert", &self.cert.subject_name())
+            .finish_non_exhaustive()
+    }
+}
 
 /// The root certificate store.
 #[allow(missing_debug_implementations)]
@@ -67,6 +184,30 @@ impl RootCertStore {
     }
 }
 
+impl RootCertStore {
+    /// Builds a store trusting `certs`, along with the built-in roots if
+    /// `built_in` is set.
+    pub(crate) fn with_certificates(
+        certs: &[Certificate],
+        built_in: bool,
+    ) -> crate::Result<RootCertStore> {
+        let mut store = X509StoreBuilder::new()?;
+        if built_in {
+            #[cfg(any(feature = "webpki-roots", feature = "native-roots"))]
+            for cert in load::built_in_certs().into_iter().flatten() {
+                store.add_cert(cert)?;
+            }
+
+            #[cfg(not(any(feature = "webpki-roots", feature = "native-roots")))]
+            store.set_default_paths()?;
+        }
+        for cert in certs {
+            store.add_cert(cert.0.clone())?;
+        }
+        Ok(RootCertStore::Owned(store.build()))
+    }
+}
+
 macro_rules! impl_root_cert_store {
     ($($type:ty => $variant:ident),* $(,)?) => {
         $(
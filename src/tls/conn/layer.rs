@@ -1,16 +1,18 @@
 /// referrer: https://github.com/cloudflare/boring/blob/master/hyper-boring/src/lib.rs
 use super::cache::{SessionCache, SessionKey};
-use super::{key_index, HttpsConnectorBuilder, HttpsLayerSettings, MaybeHttpsStream};
+use super::{key_index, HttpsConnectorBuilder, HttpsLayerSettings, MaybeHttpsStream, TlsStream};
 use crate::connect::HttpConnector;
 use crate::error::BoxError;
 
 use crate::tls::ConnectConfigurationExt;
 use crate::util::client::connect::Connection;
+use crate::util::client::ServerName;
 use crate::util::rt::TokioIo;
 use antidote::Mutex;
 use boring2::error::ErrorStack;
 use boring2::ssl::{
-    ConnectConfiguration, Ssl, SslConnector, SslConnectorBuilder, SslRef, SslSessionCacheMode,
+    ConnectConfiguration, HandshakeError, Ssl, SslConnector, SslConnectorBuilder, SslRef,
+    SslSessionCacheMode,
 };
 use http::uri::Scheme;
 use http::Uri;
@@ -19,11 +21,11 @@ use std::error::Error;
 use std::fmt::Debug;
 use std::future::Future;
 use std::net::Ipv6Addr;
-use tokio_boring2::SslStream;
 
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tower_service::Service;
 
 /// A Connector using BoringSSL to support `http` and `https` schemes.
@@ -55,6 +57,21 @@ where
         }
     }
 
+    /// Overrides the TLS server name, which the certificate is also verified
+    /// against, or disables SNI with `ServerName(None)`.
+    #[inline]
+    pub(crate) fn set_server_name(&mut self, server_name: Option<ServerName>) {
+        self.inner.server_name = server_name;
+    }
+
+    /// Bounds the TLS handshake, which fails with
+    /// [`TlsHandshakeTimedOut`](crate::error::TlsHandshakeTimedOut) if it
+    /// takes longer.
+    #[inline]
+    pub(crate) fn set_handshake_timeout(&mut self, timeout: Option<Duration>) {
+        self.inner.handshake_timeout = timeout;
+    }
+
     /// Registers a callback which can customize the SSL context for a given URI.
     ///
     /// This callback is executed after the callback registered by [`Self::set_ssl_callback`] is executed.
@@ -75,7 +92,7 @@ where
         uri: &Uri,
         host: &str,
         conn: A,
-    ) -> Result<SslStream<TokioIo<A>>, BoxError>
+    ) -> Result<TlsStream<TokioIo<A>>, BoxError>
     where
         A: Read + Write + Unpin + Send + Sync + Debug + 'static,
     {
@@ -83,6 +100,27 @@ where
     }
 }
 
+/// A stream that records everything written to it and has nothing to read.
+#[derive(Debug, Default)]
+struct Recorder(Vec<u8>);
+
+impl std::io::Read for Recorder {
+    fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::ErrorKind::WouldBlock.into())
+    }
+}
+
+impl std::io::Write for Recorder {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 /// A layer which wraps services in an `HttpsConnector`.
 #[derive(Clone)]
 pub struct HttpsLayer {
@@ -96,6 +134,8 @@ struct Inner {
     callback: Option<Callback>,
     ssl_callback: Option<SslCallback>,
     skip_session_ticket: bool,
+    server_name: Option<ServerName>,
+    handshake_timeout: Option<Duration>,
 }
 
 type Callback =
@@ -153,9 +193,30 @@ impl HttpsLayer {
                 callback: Some(callback),
                 ssl_callback: None,
                 skip_session_ticket: settings.skip_session_ticket,
+                server_name: None,
+                handshake_timeout: None,
             },
         }
     }
+
+    /// Returns the ClientHello record a connection to `host` starts with,
+    /// without sending it anywhere.
+    pub(crate) fn client_hello(&self, host: &str) -> Result<Vec<u8>, BoxError> {
+        let uri = Uri::builder()
+            .scheme(Scheme::HTTPS)
+            .authority(host)
+            .path_and_query("/")
+            .build()?;
+        let ssl = self.inner.setup_ssl(&uri, host)?;
+
+        // The handshake stops at the first read, right after the ClientHello is written.
+        match ssl.connect(Recorder::default()) {
+            Err(HandshakeError::WouldBlock(mid)) => Ok(mid.into_source_stream().0),
+            Err(HandshakeError::SetupFailure(err)) => Err(err.into()),
+            Err(HandshakeError::Failure(mid)) => Err(mid.into_error().into()),
+            Ok(_) => Err("handshake completed without a peer".into()),
+        }
+    }
 }
 
 impl Inner {
@@ -167,15 +228,19 @@ impl Inner {
         uri: &Uri,
         host: &str,
         conn: A,
-    ) -> Result<SslStream<TokioIo<A>>, BoxError>
+    ) -> Result<TlsStream<TokioIo<A>>, BoxError>
     where
         A: Read + Write + Unpin + Send + Sync + Debug + 'static,
     {
         let ssl = self.setup_ssl(uri, host)?;
-        tokio_boring2::SslStreamBuilder::new(ssl, TokioIo::new(conn))
-            .connect()
-            .await
-            .map_err(Into::into)
+        let handshake = tokio_boring2::SslStreamBuilder::new(ssl, TokioIo::new(conn)).connect();
+        let stream = match self.handshake_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, handshake)
+                .await
+                .map_err(|_| crate::error::TlsHandshakeTimedOut)?,
+            None => handshake.await,
+        };
+        stream.map(TlsStream::new).map_err(Into::into)
     }
 
     fn setup_ssl(&self, uri: &Uri, host: &str) -> Result<Ssl, ErrorStack> {
@@ -185,7 +250,18 @@ impl Inner {
             callback(&mut conf, uri)?;
         }
 
-        if let Some(authority) = uri.authority() {
+        let host = match self.server_name {
+            Some(ServerName(Some(ref name))) => name,
+            Some(ServerName(None)) => {
+                conf.set_use_server_name_indication(false);
+                host
+            }
+            None => host,
+        };
+
+        // Sessions are cached by authority, so they are not resumed when the
+        // server name is overridden, as the server may not accept them.
+        if let Some(authority) = uri.authority().filter(|_| self.server_name.is_none()) {
             let key = SessionKey(authority.clone());
 
             if let Some(ref cache) = self.cache {
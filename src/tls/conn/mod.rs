@@ -3,14 +3,17 @@
 #![allow(missing_docs)]
 mod cache;
 mod layer;
+mod stream;
 
 pub use self::layer::*;
+pub use self::stream::TlsStream;
 use super::BoringTlsConnector;
 use crate::cfg_bindable_device;
 use crate::connect::HttpConnector;
 use crate::tls::ext::SslRefExt;
 use crate::tls::{AlpnProtos, AlpsProtos, TlsResult};
 use crate::util::client::connect::{Connected, Connection};
+use crate::util::client::ServerName;
 use crate::util::rt::TokioIo;
 use boring2::ex_data::Index;
 use boring2::ssl::Ssl;
@@ -23,8 +26,8 @@ use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::pin::Pin;
 use std::sync::LazyLock;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio::io;
-use tokio_boring2::SslStream;
 
 fn key_index() -> TlsResult<Index<Ssl, SessionKey>> {
     static IDX: LazyLock<TlsResult<Index<Ssl, SessionKey>>> = LazyLock::new(Ssl::new_ex_index);
@@ -34,6 +37,8 @@ fn key_index() -> TlsResult<Index<Ssl, SessionKey>> {
 pub(crate) struct HttpsConnectorBuilder {
     http: HttpConnector,
     alpn_protos: Option<AlpnProtos>,
+    server_name: Option<ServerName>,
+    handshake_timeout: Option<Duration>,
 }
 
 impl HttpsConnectorBuilder {
@@ -42,6 +47,8 @@ impl HttpsConnectorBuilder {
         HttpsConnectorBuilder {
             http,
             alpn_protos: None,
+            server_name: None,
+            handshake_timeout: None,
         }
     }
 
@@ -51,6 +58,18 @@ impl HttpsConnectorBuilder {
         self
     }
 
+    #[inline]
+    pub fn server_name(mut self, server_name: Option<ServerName>) -> Self {
+        self.server_name = server_name;
+        self
+    }
+
+    #[inline]
+    pub fn handshake_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
     #[inline]
     pub fn addresses(mut self, (ipv4, ipv6): (Option<Ipv4Addr>, Option<Ipv6Addr>)) -> Self {
         match (ipv4, ipv6) {
@@ -74,6 +93,8 @@ impl HttpsConnectorBuilder {
     #[inline]
     pub(crate) fn build(self, tls: BoringTlsConnector) -> HttpsConnector<HttpConnector> {
         let mut connector = HttpsConnector::with_connector_layer(self.http, tls.0);
+        connector.set_server_name(self.server_name);
+        connector.set_handshake_timeout(self.handshake_timeout);
         connector.set_ssl_callback(move |ssl, _| ssl.alpn_protos(self.alpn_protos));
         connector
     }
@@ -187,7 +208,7 @@ pub enum MaybeHttpsStream<T> {
     /// A raw HTTP stream.
     Http(T),
     /// An SSL-wrapped HTTP stream.
-    Https(TokioIo<SslStream<TokioIo<T>>>),
+    Https(TokioIo<TlsStream<TokioIo<T>>>),
 }
 
 impl<T> fmt::Debug for MaybeHttpsStream<T> {
@@ -0,0 +1,242 @@
+//! TLS 1.3 early data (0-RTT).
+use crate::tls::TlsInfo;
+use boring2::ssl::{ErrorCode, SslRef};
+use boring_sys2 as ffi;
+use bytes::{Buf, BytesMut};
+use foreign_types::ForeignTypeRef;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll, Waker};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_boring2::SslStream;
+
+/// Methods whose requests may be sent as early data. Early data can be
+/// replayed, so like browsers and RFC 8470 only safe methods are sent early.
+const SAFE_METHODS: [&[u8]; 3] = [b"GET ", b"HEAD ", b"OPTIONS "];
+
+/// A TLS stream whose handshake may still be in early data.
+///
+/// When a resumed session allows it, BoringSSL completes the handshake as
+/// soon as the ClientHello is sent, and what is written until the server
+/// answers is sent as early data. Only an HTTP/1 request with a safe method
+/// is written early; anything else waits for the handshake to complete. If
+/// the server rejects the early data, it is sent again once the handshake
+/// completes, so the rejection never surfaces.
+pub struct TlsStream<S> {
+    inner: SslStream<S>,
+    early_data: Option<EarlyData>,
+    replay: BytesMut,
+    read_buf: BytesMut,
+    early_data_accepted: Arc<AtomicBool>,
+}
+
+struct EarlyData {
+    /// Whether the first write was a request that may be sent early.
+    allowed: Option<bool>,
+    /// A read waiting for the first write, which would otherwise complete
+    /// the handshake before the request is written.
+    read_waker: Option<Waker>,
+    /// What was sent as early data, to send again if it is rejected.
+    sent: BytesMut,
+}
+
+impl<S> TlsStream<S> {
+    pub(crate) fn new(inner: SslStream<S>) -> TlsStream<S> {
+        let early_data = in_early_data(inner.ssl()).then(|| EarlyData {
+            allowed: None,
+            read_waker: None,
+            sent: BytesMut::new(),
+        });
+        TlsStream {
+            inner,
+            early_data,
+            replay: BytesMut::new(),
+            read_buf: BytesMut::new(),
+            early_data_accepted: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns a shared reference to the `Ssl` object associated with this stream.
+    pub fn ssl(&self) -> &SslRef {
+        self.inner.ssl()
+    }
+
+    /// Returns a shared reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        self.inner.get_ref()
+    }
+
+    pub(crate) fn tls_info(&self) -> Option<TlsInfo> {
+        TlsInfo::from_ssl(self.ssl()).map(|mut info| {
+            info.early_data_accepted = self.early_data_accepted.clone();
+            info
+        })
+    }
+
+    /// Records the outcome of the early data once the handshake completed.
+    fn update_early_data(&mut self) {
+        if self.early_data.is_some() && !in_early_data(self.ssl()) {
+            let sent = self.early_data.take().is_some_and(|e| !e.sent.is_empty());
+            let accepted = unsafe { ffi::SSL_early_data_accepted(self.ssl().as_ptr()) } == 1;
+            self.early_data_accepted
+                .store(sent && accepted, Ordering::Release);
+        }
+    }
+
+    /// Resets the connection after the server rejected the early data, which
+    /// is then sent again as regular application data.
+    fn reject_early_data(&mut self) {
+        log::debug!("TLS early data rejected, sending it again");
+        unsafe { ffi::SSL_reset_early_data_reject(self.inner.ssl_mut().as_ptr()) };
+        if let Some(early_data) = self.early_data.take() {
+            self.replay = early_data.sent;
+        }
+    }
+}
+
+impl<S> TlsStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Sends again the early data the server rejected.
+    fn poll_replay(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.replay.is_empty() {
+            let n = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.replay))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.replay.advance(n);
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// Drives the handshake out of early data, keeping what the server sent
+    /// meanwhile for the next read.
+    fn poll_handshake(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.early_data.is_some() {
+            let mut buf = [0; 4096];
+            let mut buf = ReadBuf::new(&mut buf);
+            match Pin::new(&mut self.inner).poll_read(cx, &mut buf) {
+                Poll::Ready(Ok(())) if buf.filled().is_empty() => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "connection closed during the TLS handshake",
+                    )));
+                }
+                Poll::Ready(Ok(())) => self.read_buf.extend_from_slice(buf.filled()),
+                Poll::Ready(Err(err)) if is_early_data_rejected(&err) => self.reject_early_data(),
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending if in_early_data(self.ssl()) => return Poll::Pending,
+                Poll::Pending => {}
+            }
+            self.update_early_data();
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S> AsyncRead for TlsStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.read_buf.is_empty() {
+            let n = this.read_buf.len().min(buf.remaining());
+            buf.put_slice(&this.read_buf.split_to(n));
+            return Poll::Ready(Ok(()));
+        }
+
+        if let Some(ref mut early_data) = this.early_data {
+            if early_data.allowed.is_none() {
+                early_data.read_waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+        }
+
+        loop {
+            ready!(this.poll_replay(cx))?;
+            match Pin::new(&mut this.inner).poll_read(cx, buf) {
+                Poll::Ready(Err(err)) if is_early_data_rejected(&err) => this.reject_early_data(),
+                res => {
+                    this.update_early_data();
+                    return res;
+                }
+            }
+        }
+    }
+}
+
+impl<S> AsyncWrite for TlsStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            ready!(this.poll_replay(cx))?;
+
+            let alpn_h2 = this.ssl().selected_alpn_protocol() == Some(b"h2");
+            let write_early = match this.early_data {
+                Some(ref mut early_data) => {
+                    if let Some(waker) = early_data.read_waker.take() {
+                        waker.wake();
+                    }
+                    *early_data.allowed.get_or_insert_with(|| {
+                        !alpn_h2 && SAFE_METHODS.iter().any(|method| buf.starts_with(method))
+                    })
+                }
+                None => false,
+            };
+            if this.early_data.is_some() && !write_early {
+                ready!(this.poll_handshake(cx))?;
+                continue;
+            }
+
+            let res = ready!(Pin::new(&mut this.inner).poll_write(cx, buf));
+            match res {
+                Err(err) if is_early_data_rejected(&err) => this.reject_early_data(),
+                Ok(n) => {
+                    if let Some(ref mut early_data) = this.early_data {
+                        early_data.sent.extend_from_slice(&buf[..n]);
+                    }
+                    this.update_early_data();
+                    return Poll::Ready(Ok(n));
+                }
+                Err(err) => return Poll::Ready(Err(err)),
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_replay(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+fn in_early_data(ssl: &SslRef) -> bool {
+    unsafe { ffi::SSL_in_early_data(ssl.as_ptr()) == 1 }
+}
+
+fn is_early_data_rejected(err: &io::Error) -> bool {
+    err.get_ref()
+        .and_then(|err| err.downcast_ref::<boring2::ssl::Error>())
+        .is_some_and(|err| {
+            err.code() == ErrorCode::from_raw(ffi::SSL_ERROR_EARLY_DATA_REJECTED as _)
+        })
+}
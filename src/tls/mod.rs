@@ -8,23 +8,33 @@
 mod cert;
 mod conn;
 mod ext;
+mod pin;
 
 use crate::impl_debug;
-use boring2::ssl::SslCurve;
+use boring2::ssl::{ExtensionType, SslCurve, SslRef, SslSignatureAlgorithm};
 use boring2::{
     error::ErrorStack,
-    ssl::{SslConnector, SslMethod, SslOptions, SslVersion},
+    ssl::{SslConnector, SslConnectorBuilder, SslMethod, SslOptions, SslVersion},
 };
 use conn::{HttpsLayer, HttpsLayerSettings};
+use foreign_types::ForeignTypeRef;
 use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use typed_builder::TypedBuilder;
 
-pub use cert::{compression::CertCompressionAlgorithm, RootCertStore};
+pub use cert::{compression::CertCompressionAlgorithm, Certificate, Identity, RootCertStore};
+pub(crate) use conn::TlsStream;
 pub use conn::{HttpsConnector, MaybeHttpsStream};
 pub use ext::{ConnectConfigurationExt, SslConnectorBuilderExt, SslRefExt};
+pub(crate) use pin::PublicKeyPins;
+pub use pin::Sha256Pin;
 
 type TlsResult<T> = Result<T, ErrorStack>;
 
+/// A hook run on the `SslConnectorBuilder` once the TLS settings are applied.
+pub(crate) type TlsConnectorHook = Arc<dyn Fn(&mut SslConnectorBuilder) + Send + Sync>;
+
 /// Error handler for the boringssl functions.
 fn sv_handler(r: ::std::os::raw::c_int) -> TlsResult<::std::os::raw::c_int> {
     if r == 0 {
@@ -41,7 +51,10 @@ pub(crate) struct BoringTlsConnector(HttpsLayer);
 impl BoringTlsConnector {
     /// Create a new `BoringTlsConnector` with the given function.
     #[inline]
-    pub fn new(settings: TlsSettings) -> TlsResult<BoringTlsConnector> {
+    pub fn new(
+        settings: TlsSettings,
+        hook: Option<&TlsConnectorHook>,
+    ) -> crate::Result<BoringTlsConnector> {
         let mut connector = SslConnector::no_default_verify_builder(SslMethod::tls_client())?
             .root_cert_store(settings.root_certs_store)?
             .cert_verification(settings.certs_verification)?
@@ -74,10 +87,13 @@ impl BoringTlsConnector {
         }
 
         if let Some(permute_extensions) = settings.permute_extensions {
-            connector.set_permute_extensions(permute_extensions);
+            connector
+                .set_permute_extensions(permute_extensions && settings.extension_order.is_none());
         }
 
-        if let Some(curves) = settings.curves.as_deref() {
+        if let Some(key_shares) = settings.key_shares.as_deref() {
+            connector.set_curves(&key_shares_first(key_shares, settings.curves.as_deref())?)?;
+        } else if let Some(curves) = settings.curves.as_deref() {
             connector.set_curves(curves)?;
         }
 
@@ -85,6 +101,11 @@ impl BoringTlsConnector {
             connector.set_sigalgs_list(sigalgs_list)?;
         }
 
+        if let Some(algorithms) = settings.signature_algorithms.as_deref() {
+            check_signature_algorithms(algorithms, settings.max_tls_version)?;
+            connector.set_verify_algorithm_prefs(algorithms)?;
+        }
+
         if let Some(delegated_credentials) = settings.delegated_credentials.as_deref() {
             connector.set_delegated_credentials(delegated_credentials)?;
         }
@@ -103,14 +124,30 @@ impl BoringTlsConnector {
             connector.set_record_size_limit(record_size_limit);
         }
 
-        if let Some(limit) = settings.key_shares_limit {
+        if let Some(key_shares) = settings.key_shares.as_deref() {
+            connector.set_key_shares_limit(key_shares.len() as u8);
+        } else if let Some(limit) = settings.key_shares_limit {
             connector.set_key_shares_limit(limit);
         }
 
-        if let Some(indices) = settings.extension_permutation_indices {
+        if let Some(order) = settings.extension_order.as_deref() {
+            connector.set_extension_permutation_indices(&extension_order_indices(order)?)?;
+        } else if let Some(indices) = settings.extension_permutation_indices {
             connector.set_extension_permutation_indices(indices.as_ref())?;
         }
 
+        if let Some(identity) = settings.identity.as_ref() {
+            identity.apply(&mut connector)?;
+        }
+
+        if settings.enable_early_data {
+            unsafe { boring_sys2::SSL_CTX_set_early_data_enabled(connector.as_ptr(), 1) };
+        }
+
+        if let Some(hook) = hook {
+            hook(&mut connector);
+        }
+
         // Create the `HttpsLayerSettings` with the default session cache capacity.
         let settings = HttpsLayerSettings::builder()
             .session_cache(settings.pre_shared_key)
@@ -127,6 +164,197 @@ impl BoringTlsConnector {
             connector, settings,
         )))
     }
+
+    /// Returns the extension types of a ClientHello this connector writes, in
+    /// the order they are sent.
+    ///
+    /// The ClientHello is generated in memory, nothing is sent over the network.
+    pub(crate) fn extension_order(&self) -> crate::Result<Vec<u16>> {
+        let record = self
+            .0
+            .client_hello("example.com")
+            .map_err(crate::error::builder)?;
+        client_hello_extensions(&record)
+            .ok_or_else(|| crate::error::builder("malformed ClientHello"))
+    }
+}
+
+/// Parses the extension types out of a ClientHello handshake record.
+fn client_hello_extensions(record: &[u8]) -> Option<Vec<u16>> {
+    fn take<'a>(buf: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+        let (head, tail) = (buf.len() >= n).then(|| buf.split_at(n))?;
+        *buf = tail;
+        Some(head)
+    }
+
+    fn take_u16(buf: &mut &[u8]) -> Option<u16> {
+        take(buf, 2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn take_vec<'a>(buf: &mut &'a [u8], len_bytes: usize) -> Option<&'a [u8]> {
+        let len = take(buf, len_bytes)?
+            .iter()
+            .fold(0, |len, &b| len << 8 | b as usize);
+        take(buf, len)
+    }
+
+    let mut buf = record;
+    // record header: content type (handshake), legacy version
+    if take(&mut buf, 3)?[0] != 0x16 {
+        return None;
+    }
+    let mut handshake = take_vec(&mut buf, 2)?;
+    if take(&mut handshake, 1)?[0] != 0x01 {
+        return None;
+    }
+    let mut hello = take_vec(&mut handshake, 3)?;
+
+    // legacy version, random, session id, cipher suites, compression methods
+    take(&mut hello, 2 + 32)?;
+    take_vec(&mut hello, 1)?;
+    take_vec(&mut hello, 2)?;
+    take_vec(&mut hello, 1)?;
+
+    let mut extensions = take_vec(&mut hello, 2)?;
+    let mut order = Vec::new();
+    while !extensions.is_empty() {
+        order.push(take_u16(&mut extensions)?);
+        take_vec(&mut extensions, 2)?;
+    }
+    Some(order)
+}
+
+/// The groups BoringSSL supports when none are configured.
+pub(crate) const DEFAULT_CURVES: [SslCurve; 3] =
+    [SslCurve::X25519, SslCurve::SECP256R1, SslCurve::SECP384R1];
+
+/// Moves the groups key shares are sent for to the front of the supported
+/// groups, as BoringSSL sends key shares for the first ones.
+fn key_shares_first(
+    key_shares: &[SslCurve],
+    curves: Option<&[SslCurve]>,
+) -> crate::Result<Vec<SslCurve>> {
+    if key_shares.is_empty() || key_shares.len() > u8::MAX as usize {
+        return Err(crate::error::builder(format!(
+            "invalid number of TLS key shares: {}",
+            key_shares.len()
+        )));
+    }
+    for (i, curve) in key_shares.iter().enumerate() {
+        if key_shares[..i].contains(curve) {
+            return Err(crate::error::builder(format!(
+                "duplicate TLS key share: {:?}",
+                curve
+            )));
+        }
+    }
+
+    let mut curves_with_key_shares = key_shares.to_vec();
+    curves_with_key_shares.extend(
+        curves
+            .unwrap_or(&DEFAULT_CURVES)
+            .iter()
+            .filter(|curve| !key_shares.contains(curve)),
+    );
+    Ok(curves_with_key_shares)
+}
+
+/// The signature algorithms BoringSSL can verify, the first ones of which
+/// can also be used with TLS 1.3.
+const SIGNATURE_ALGORITHMS: [SslSignatureAlgorithm; 13] = [
+    SslSignatureAlgorithm::ECDSA_SECP256R1_SHA256,
+    SslSignatureAlgorithm::ECDSA_SECP384R1_SHA384,
+    SslSignatureAlgorithm::ECDSA_SECP521R1_SHA512,
+    SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256,
+    SslSignatureAlgorithm::RSA_PSS_RSAE_SHA384,
+    SslSignatureAlgorithm::RSA_PSS_RSAE_SHA512,
+    SslSignatureAlgorithm::ED25519,
+    SslSignatureAlgorithm::RSA_PKCS1_SHA256,
+    SslSignatureAlgorithm::RSA_PKCS1_SHA384,
+    SslSignatureAlgorithm::RSA_PKCS1_SHA512,
+    SslSignatureAlgorithm::RSA_PKCS1_SHA1,
+    SslSignatureAlgorithm::ECDSA_SHA1,
+    SslSignatureAlgorithm::RSA_PKCS1_MD5_SHA1,
+];
+
+/// The number of [`SIGNATURE_ALGORITHMS`] that can be used with TLS 1.3.
+const TLS13_SIGNATURE_ALGORITHMS: usize = 7;
+
+/// Checks that the offered signature algorithms are known, not repeated, and
+/// that a handshake can complete with one of them.
+fn check_signature_algorithms(
+    algorithms: &[SslSignatureAlgorithm],
+    max_tls_version: Option<TlsVersion>,
+) -> crate::Result<()> {
+    for (i, algorithm) in algorithms.iter().enumerate() {
+        if !SIGNATURE_ALGORITHMS.contains(algorithm) {
+            return Err(crate::error::builder(format!(
+                "unsupported TLS signature algorithm: {:?}",
+                algorithm
+            )));
+        }
+        if algorithms[..i].contains(algorithm) {
+            return Err(crate::error::builder(format!(
+                "duplicate TLS signature algorithm: {:?}",
+                algorithm
+            )));
+        }
+    }
+
+    // TLS 1.2 servers may fall back to any of them, TLS 1.3 ones may not.
+    let usable = match max_tls_version {
+        Some(version) if version != TlsVersion::TLS_1_3 => &SIGNATURE_ALGORITHMS[..],
+        _ => &SIGNATURE_ALGORITHMS[..TLS13_SIGNATURE_ALGORITHMS],
+    };
+    if !algorithms
+        .iter()
+        .any(|algorithm| usable.contains(algorithm))
+    {
+        return Err(crate::error::builder(
+            "no usable TLS signature algorithm is offered",
+        ));
+    }
+    Ok(())
+}
+
+/// Extensions a ClientHello cannot complete a handshake without.
+const REQUIRED_EXTENSIONS: [ExtensionType; 4] = [
+    ExtensionType::SUPPORTED_GROUPS,
+    ExtensionType::SIGNATURE_ALGORITHMS,
+    ExtensionType::KEY_SHARE,
+    ExtensionType::SUPPORTED_VERSIONS,
+];
+
+/// Translates an explicit extension order into BoringSSL permutation indices.
+fn extension_order_indices(order: &[ExtensionType]) -> crate::Result<Vec<u8>> {
+    let mut indices = Vec::with_capacity(order.len());
+    for &extension in order {
+        let index = ExtensionType::index_of(extension).ok_or_else(|| {
+            crate::error::builder(format!(
+                "unsupported TLS extension in extension order: {:?}",
+                extension
+            ))
+        })? as u8;
+        if indices.contains(&index) {
+            return Err(crate::error::builder(format!(
+                "duplicate TLS extension in extension order: {:?}",
+                extension
+            )));
+        }
+        indices.push(index);
+    }
+
+    if let Some(missing) = REQUIRED_EXTENSIONS
+        .iter()
+        .find(|extension| !order.contains(extension))
+    {
+        return Err(crate::error::builder(format!(
+            "TLS extension order is missing required extension: {:?}",
+            missing
+        )));
+    }
+
+    Ok(indices)
 }
 
 /// A TLS protocol version.
@@ -184,13 +412,118 @@ impl AlpsProtos {
 #[derive(Debug, Clone)]
 pub struct TlsInfo {
     pub(crate) peer_certificate: Option<Vec<u8>>,
+    pub(crate) peer_certificate_chain: Option<Vec<Vec<u8>>>,
+    pub(crate) signed_certificate_timestamps: Option<Vec<Vec<u8>>>,
+    pub(crate) ocsp_response: Option<Vec<u8>>,
+    pub(crate) alpn_protocol: Option<Vec<u8>>,
+    pub(crate) early_data_accepted: Arc<AtomicBool>,
 }
 
 impl TlsInfo {
+    /// Collects the information of an established connection, or `None` if the
+    /// peer did not present a certificate.
+    pub(crate) fn from_ssl(ssl: &SslRef) -> Option<TlsInfo> {
+        let peer_certificate = ssl.peer_certificate()?.to_der().ok()?;
+        let peer_certificate_chain = ssl.peer_cert_chain().and_then(|chain| {
+            chain
+                .iter()
+                .map(|cert| cert.to_der().ok())
+                .collect::<Option<Vec<_>>>()
+        });
+        let signed_certificate_timestamps = unsafe {
+            let mut data = std::ptr::null();
+            let mut len = 0;
+            boring_sys2::SSL_get0_signed_cert_timestamp_list(ssl.as_ptr(), &mut data, &mut len);
+            if data.is_null() || len == 0 {
+                None
+            } else {
+                split_sct_list(std::slice::from_raw_parts(data, len))
+            }
+        };
+        Some(TlsInfo {
+            peer_certificate: Some(peer_certificate),
+            peer_certificate_chain,
+            signed_certificate_timestamps,
+            ocsp_response: ssl.ocsp_status().map(<[u8]>::to_vec),
+            alpn_protocol: ssl.selected_alpn_protocol().map(<[u8]>::to_vec),
+            early_data_accepted: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
     /// Get the DER encoded leaf certificate of the peer.
     pub fn peer_certificate(&self) -> Option<&[u8]> {
         self.peer_certificate.as_ref().map(|der| &der[..])
     }
+
+    /// Get the DER encoded certificate chain of the peer.
+    ///
+    /// The certificates are in the order the server presented them, starting
+    /// with the leaf certificate returned by [`TlsInfo::peer_certificate`].
+    pub fn peer_certificate_chain(&self) -> Option<&[Vec<u8>]> {
+        self.peer_certificate_chain.as_deref()
+    }
+
+    /// Get the Signed Certificate Timestamps the server sent in the
+    /// `signed_certificate_timestamp` TLS extension.
+    ///
+    /// Each item is one serialized `SignedCertificateTimestamp` (RFC 6962,
+    /// section 3.2), ready to be verified against the CT log that issued it.
+    /// Returns `None` if the server sent none, which is always the case unless
+    /// `enable_signed_cert_timestamps` is set in the [`TlsSettings`]. SCTs
+    /// embedded in the certificate or in a stapled OCSP response are not
+    /// included.
+    pub fn signed_certificate_timestamps(&self) -> Option<&[Vec<u8>]> {
+        self.signed_certificate_timestamps.as_deref()
+    }
+
+    /// Get the DER encoded OCSP response the server stapled to its certificate,
+    /// in reply to the `status_request` TLS extension.
+    ///
+    /// This is the `OCSPResponse` of RFC 6960, which can be checked to verify
+    /// that the certificate is not revoked. Returns `None` if the server
+    /// stapled none, which is always the case unless `enable_ocsp_stapling` is
+    /// set in the [`TlsSettings`], as it is in every impersonated profile.
+    pub fn ocsp_response(&self) -> Option<&[u8]> {
+        self.ocsp_response.as_deref()
+    }
+
+    /// Get the protocol negotiated with ALPN, such as `h2`, or `None` if the
+    /// server did not select one.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// Whether the server accepted the request sent as TLS 1.3 early data.
+    ///
+    /// This is `false` unless `tls_early_data` is enabled on the
+    /// `ClientBuilder` and the connection resumed a session with early data.
+    /// A rejected request is sent again once the handshake completes.
+    pub fn early_data_accepted(&self) -> bool {
+        self.early_data_accepted.load(Ordering::Acquire)
+    }
+}
+
+/// Splits a `SignedCertificateTimestampList` into its entries.
+///
+/// Both the list and each entry are prefixed with a big-endian `u16` length.
+fn split_sct_list(list: &[u8]) -> Option<Vec<Vec<u8>>> {
+    fn take(buf: &mut &[u8]) -> Option<Vec<u8>> {
+        let (len, rest) = buf.split_first_chunk::<2>()?;
+        let len = u16::from_be_bytes(*len) as usize;
+        if rest.len() < len {
+            return None;
+        }
+        let (item, rest) = rest.split_at(len);
+        *buf = rest;
+        Some(item.to_vec())
+    }
+
+    let mut list = &take(&mut &list[..])?[..];
+    let mut scts = Vec::new();
+    while !list.is_empty() {
+        scts.push(take(&mut list)?);
+    }
+    (!scts.is_empty()).then_some(scts)
 }
 
 /// Configuration settings for TLS connections.
@@ -300,6 +633,11 @@ pub struct TlsSettings {
     pub permute_extensions: Option<bool>,
 
     /// Set's whether the context should enable GREASE.
+    ///
+    /// Like browsers, new GREASE values are drawn for each ClientHello, and
+    /// used consistently across its cipher suites, extensions, supported
+    /// groups and key shares. BoringSSL draws them from its own random number
+    /// generator, so they cannot be seeded.
     #[builder(default, setter(into))]
     pub grease_enabled: Option<bool>,
 
@@ -363,10 +701,24 @@ pub struct TlsSettings {
     #[builder(default, setter(strip_option, into))]
     pub curves: Option<Cow<'static, [SslCurve]>>,
 
+    /// Sets the groups key shares are sent for, in order.
+    ///
+    /// Key shares are sent for the first supported groups, so these groups are
+    /// moved to the front of `curves`, and `key_shares_limit` is ignored.
+    #[builder(default, setter(strip_option, into))]
+    pub key_shares: Option<Cow<'static, [SslCurve]>>,
+
     /// Sets the context's supported signature algorithms.
     #[builder(default, setter(strip_option, into))]
     pub sigalgs_list: Option<Cow<'static, str>>,
 
+    /// Sets the algorithms of the `signature_algorithms` extension, in order.
+    ///
+    /// Takes precedence over the algorithms of `sigalgs_list` that are offered
+    /// to the server.
+    #[builder(default, setter(strip_option, into))]
+    pub signature_algorithms: Option<Cow<'static, [SslSignatureAlgorithm]>>,
+
     /// Certificates in TLS 1.3 can be compressed [RFC 8879](https://datatracker.ietf.org/doc/html/rfc8879).
     #[builder(default, setter(transform = |input: impl IntoCertCompressionAlgorithm| input.into()))]
     pub cert_compression_algorithm: Option<Cow<'static, [CertCompressionAlgorithm]>>,
@@ -374,6 +726,26 @@ pub struct TlsSettings {
     /// Sets the context's extension permutation indices.
     #[builder(default, setter(strip_option, into))]
     pub extension_permutation_indices: Option<Cow<'static, [u8]>>,
+
+    /// Sets the exact order of the ClientHello extensions.
+    ///
+    /// Extensions left out of the list are not sent. Takes precedence over
+    /// `permute_extensions` and `extension_permutation_indices`.
+    #[builder(default, setter(strip_option, into))]
+    pub extension_order: Option<Cow<'static, [ExtensionType]>>,
+
+    /// Sets the client certificate presented when the server requests one.
+    #[builder(default, setter(strip_option))]
+    pub identity: Option<Identity>,
+
+    /// Sends `GET`, `HEAD` and `OPTIONS` requests as **TLS 1.3 early data** (0-RTT) when
+    /// resuming a session that allows it.
+    ///
+    /// Only takes effect with `pre_shared_key`, which caches the sessions.
+    ///
+    /// **Reference:** See [RFC 8446](https://datatracker.ietf.org/doc/html/rfc8446#section-2.3) for details.
+    #[builder(default = false)]
+    pub enable_early_data: bool,
 }
 
 /// ====== impl TlsSettings ======c
@@ -402,14 +774,19 @@ impl_debug!(
         enable_ocsp_stapling,
         renegotiation,
         curves,
+        key_shares,
         sigalgs_list,
+        signature_algorithms,
         cipher_list,
         enable_signed_cert_timestamps,
         cert_compression_algorithm,
         record_size_limit,
         key_shares_limit,
         psk_skip_session_ticket,
-        extension_permutation_indices
+        extension_permutation_indices,
+        extension_order,
+        identity,
+        enable_early_data
     }
 );
 
@@ -482,3 +859,24 @@ impl_into_cert_compression_algorithm_for_arrays!(
     [CertCompressionAlgorithm; N] => |s: [CertCompressionAlgorithm; N]| Some(Cow::Owned(s.to_vec())),
     Option<[CertCompressionAlgorithm; N]> => |s: Option<[CertCompressionAlgorithm; N]>| s.map(|arr| Cow::Owned(arr.to_vec()))
 );
+
+#[cfg(test)]
+mod tests {
+    use super::split_sct_list;
+
+    #[test]
+    fn splits_sct_list() {
+        let list = [0, 9, 0, 2, 0xAA, 0xBB, 0, 3, 1, 2, 3];
+        assert_eq!(
+            split_sct_list(&list),
+            Some(vec![vec![0xAA, 0xBB], vec![1, 2, 3]])
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_sct_list() {
+        assert_eq!(split_sct_list(&[0, 5, 0, 4, 1, 2, 3]), None);
+        assert_eq!(split_sct_list(&[0, 0]), None);
+        assert_eq!(split_sct_list(&[]), None);
+    }
+}
@@ -0,0 +1,106 @@
+//! Public key pinning
+
+use std::collections::HashMap;
+use std::fmt;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use boring2::hash::{hash, MessageDigest};
+use boring2::x509::X509;
+
+/// The SHA-256 digest of a certificate's DER encoded SubjectPublicKeyInfo.
+///
+/// This is the `pin-sha256` value of HPKP (RFC 7469), which can be computed
+/// from a certificate with:
+///
+/// ```text
+/// openssl x509 -in cert.pem -pubkey -noout \
+///   | openssl pkey -pubin -outform der \
+///   | openssl dgst -sha256 -binary | base64
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sha256Pin([u8; 32]);
+
+impl Sha256Pin {
+    /// Creates a pin from a raw SHA-256 digest.
+    pub const fn new(digest: [u8; 32]) -> Sha256Pin {
+        Sha256Pin(digest)
+    }
+
+    /// Creates a pin from a base64 encoded SHA-256 digest.
+    pub fn from_base64(pin: &str) -> crate::Result<Sha256Pin> {
+        let digest = STANDARD.decode(pin).map_err(crate::error::builder)?;
+        digest
+            .try_into()
+            .map(Sha256Pin)
+            .map_err(|_| crate::error::builder("pin must be a 32 byte SHA-256 digest"))
+    }
+
+    /// Computes the pin of a DER encoded SubjectPublicKeyInfo.
+    pub fn from_spki_der(spki: &[u8]) -> crate::Result<Sha256Pin> {
+        let digest = hash(MessageDigest::sha256(), spki).map_err(crate::error::builder)?;
+        Ok(Sha256Pin(
+            digest
+                .as_ref()
+                .try_into()
+                .expect("SHA-256 digest is 32 bytes"),
+        ))
+    }
+
+    fn of_certificate(der: &[u8]) -> Option<Sha256Pin> {
+        let spki = X509::from_der(der)
+            .ok()?
+            .public_key()
+            .ok()?
+            .public_key_to_der()
+            .ok()?;
+        Sha256Pin::from_spki_der(&spki).ok()
+    }
+}
+
+impl fmt::Debug for Sha256Pin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sha256Pin({})", STANDARD.encode(self.0))
+    }
+}
+
+/// The pins configured per host.
+#[derive(Debug, Default)]
+pub(crate) struct PublicKeyPins(HashMap<String, Vec<Sha256Pin>>);
+
+impl PublicKeyPins {
+    pub(crate) fn insert(&mut self, host: &str, pins: Vec<Sha256Pin>) {
+        self.0.entry(normalize(host)).or_default().extend(pins);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Checks that a certificate of `chain` matches one of the pins of `host`.
+    ///
+    /// Hosts without pins always pass.
+    pub(crate) fn verify(
+        &self,
+        host: &str,
+        chain: Option<&[Vec<u8>]>,
+    ) -> Result<(), crate::error::PinMismatch> {
+        let Some(pins) = self.0.get(&normalize(host)) else {
+            return Ok(());
+        };
+        let matched = chain
+            .unwrap_or_default()
+            .iter()
+            .any(|der| Sha256Pin::of_certificate(der).is_some_and(|pin| pins.contains(&pin)));
+        if matched {
+            Ok(())
+        } else {
+            Err(crate::error::PinMismatch(host.to_owned()))
+        }
+    }
+}
+
+fn normalize(host: &str) -> String {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase()
+}
@@ -3,7 +3,7 @@ use std::fmt;
 use std::future::Future;
 use std::io;
 use std::marker::PhantomData;
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{self, Poll};
@@ -68,6 +68,7 @@ pub struct HttpInfo {
 #[derive(Clone)]
 struct Config {
     connect_timeout: Option<Duration>,
+    per_address_connect_timeout: Option<Duration>,
     enforce_http: bool,
     happy_eyeballs_timeout: Option<Duration>,
     tcp_keepalive_config: TcpKeepaliveConfig,
@@ -233,6 +234,7 @@ impl<R> HttpConnector<R> {
         HttpConnector {
             config: Arc::new(Config {
                 connect_timeout: None,
+                per_address_connect_timeout: None,
                 enforce_http: true,
                 happy_eyeballs_timeout: Some(Duration::from_millis(300)),
                 tcp_keepalive_config: TcpKeepaliveConfig::default(),
@@ -352,6 +354,18 @@ impl<R> HttpConnector<R> {
         self.config_mut().connect_timeout = dur;
     }
 
+    /// Set the connect timeout of each address.
+    ///
+    /// Caps every connection attempt, so an address that does not answer
+    /// does not delay the next one by more than `dur`, whatever remains of
+    /// the connect timeout.
+    ///
+    /// Default is `None`.
+    #[inline]
+    pub fn set_per_address_connect_timeout(&mut self, dur: Option<Duration>) {
+        self.config_mut().per_address_connect_timeout = dur;
+    }
+
     /// Set timeout for [RFC 6555 (Happy Eyeballs)][RFC 6555] algorithm.
     ///
     /// If hostname resolves to both IPv4 and IPv6 addresses and connection
@@ -529,10 +543,6 @@ where
 
         let sock = c.connect().await?;
 
-        if let Err(e) = sock.set_nodelay(config.nodelay) {
-            warn!("tcp set_nodelay error: {}", e);
-        }
-
         Ok(TokioIo::new(sock))
     }
 }
@@ -678,23 +688,23 @@ impl<'a> ConnectingTcp<'a> {
                 .split_by_preference(config.local_address_ipv4, config.local_address_ipv6);
             if fallback_addrs.is_empty() {
                 return ConnectingTcp {
-                    preferred: ConnectingTcpRemote::new(preferred_addrs, config.connect_timeout),
+                    preferred: ConnectingTcpRemote::new(preferred_addrs, config),
                     fallback: None,
                     config,
                 };
             }
 
             ConnectingTcp {
-                preferred: ConnectingTcpRemote::new(preferred_addrs, config.connect_timeout),
+                preferred: ConnectingTcpRemote::new(preferred_addrs, config),
                 fallback: Some(ConnectingTcpFallback {
                     delay: tokio::time::sleep(fallback_timeout),
-                    remote: ConnectingTcpRemote::new(fallback_addrs, config.connect_timeout),
+                    remote: ConnectingTcpRemote::new(fallback_addrs, config),
                 }),
                 config,
             }
         } else {
             ConnectingTcp {
-                preferred: ConnectingTcpRemote::new(remote_addrs, config.connect_timeout),
+                preferred: ConnectingTcpRemote::new(remote_addrs, config),
                 fallback: None,
                 config,
             }
@@ -713,8 +723,14 @@ struct ConnectingTcpRemote {
 }
 
 impl ConnectingTcpRemote {
-    fn new(addrs: dns::SocketAddrs, connect_timeout: Option<Duration>) -> Self {
-        let connect_timeout = connect_timeout.and_then(|t| t.checked_div(addrs.len() as u32));
+    fn new(addrs: dns::SocketAddrs, config: &Config) -> Self {
+        let connect_timeout = config
+            .connect_timeout
+            .and_then(|t| t.checked_div(addrs.len() as u32));
+        let connect_timeout = match (connect_timeout, config.per_address_connect_timeout) {
+            (Some(shared), Some(per_address)) => Some(shared.min(per_address)),
+            (shared, per_address) => shared.or(per_address),
+        };
 
         Self {
             addrs,
@@ -755,13 +771,17 @@ fn bind_local_address(
     dst_addr: &SocketAddr,
     local_addr_ipv4: &Option<Ipv4Addr>,
     local_addr_ipv6: &Option<Ipv6Addr>,
+    scope_id: u32,
 ) -> io::Result<()> {
     match (*dst_addr, local_addr_ipv4, local_addr_ipv6) {
         (SocketAddr::V4(_), Some(addr), _) => {
             socket.bind(&SocketAddr::new((*addr).into(), 0).into())?;
         }
         (SocketAddr::V6(_), _, Some(addr)) => {
-            socket.bind(&SocketAddr::new((*addr).into(), 0).into())?;
+            // Link-local addresses are only unique within the zone of their interface.
+            let is_link_local = addr.segments()[0] & 0xffc0 == 0xfe80;
+            let scope_id = if is_link_local { scope_id } else { 0 };
+            socket.bind(&SocketAddrV6::new(*addr, 0, 0, scope_id).into())?;
         }
         _ => {
             if cfg!(windows) {
@@ -804,6 +824,42 @@ fn connect(
         }
     }
 
+    if let Err(e) = socket.set_nodelay(config.nodelay) {
+        warn!("tcp set_nodelay error: {}", e);
+    }
+
+    // The index of the interface, which is also the scope id of its
+    // link-local IPv6 addresses.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "linux",
+        target_os = "ios",
+        target_os = "visionos",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos",
+    ))]
+    let iface_index = match &config.interface {
+        Some(interface) => {
+            let c_interface = std::ffi::CString::new(interface.as_bytes())
+                .map_err(ConnectError::m("tcp bind interface error"))?;
+            std::num::NonZeroU32::new(unsafe { libc::if_nametoindex(c_interface.as_ptr()) })
+        }
+        None => None,
+    };
+    #[cfg(not(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "linux",
+        target_os = "ios",
+        target_os = "visionos",
+        target_os = "macos",
+        target_os = "tvos",
+        target_os = "watchos",
+    )))]
+    let iface_index: Option<std::num::NonZeroU32> = None;
+
     #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
     // That this only works for some socket types, particularly AF_INET sockets.
     if let Some(interface) = &config.interface {
@@ -820,12 +876,7 @@ fn connect(
         target_os = "watchos",
     ))]
     /// That this only supports ios, visionos, macos, tvos, watchos
-    if let Some(interface) = &config.interface {
-        let c_interface = std::ffi::CString::new(interface.as_bytes())
-            .map_err(ConnectError::m("tcp bind interface error"))?;
-        let iface_index =
-            std::num::NonZeroU32::new(unsafe { libc::if_nametoindex(c_interface.as_ptr()) });
-
+    if config.interface.is_some() {
         // If the interface is not found, it will skip the binding
         if iface_index.is_some() {
             if addr.is_ipv4() {
@@ -852,6 +903,7 @@ fn connect(
         addr,
         &config.local_address_ipv4,
         &config.local_address_ipv6,
+        iface_index.map_or(0, |index| index.get()),
     )
     .map_err(ConnectError::m("tcp bind local error"))?;
 
@@ -953,3 +1005,31 @@ fn set_port(addr: &mut SocketAddr, host_port: u16, explicit: bool) {
         addr.set_port(host_port)
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tower_service::Service;
+
+    use super::HttpConnector;
+
+    #[tokio::test]
+    async fn applies_socket_options() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri: http::Uri = format!("http://{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+
+        for nodelay in [true, false] {
+            let mut connector = HttpConnector::new();
+            connector.set_nodelay(nodelay);
+            connector.set_keepalive(Some(Duration::from_secs(60)));
+
+            let io = connector.call(uri.clone()).await.unwrap();
+            let socket = socket2::SockRef::from(io.inner());
+            assert_eq!(socket.nodelay().unwrap(), nodelay);
+            assert!(socket.keepalive().unwrap());
+        }
+    }
+}
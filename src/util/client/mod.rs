@@ -18,6 +18,7 @@ use std::future::Future;
 use std::net::{Ipv4Addr, Ipv6Addr};
 use std::num::NonZeroUsize;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::task::{self, Poll};
 use std::time::Duration;
@@ -37,6 +38,7 @@ use crate::{cfg_bindable_device, cfg_non_bindable_device, AlpnProtos};
 use connect::capture::CaptureConnectionExtension;
 use connect::{Alpn, Connect, Connected, Connection};
 use pool::Ver;
+use request::{insert_header_at, HostPosition};
 
 use common::{lazy as hyper_lazy, timer, Exec, Lazy};
 
@@ -46,6 +48,11 @@ pub use request::InnerRequest;
 
 type BoxSendFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
 
+/// Whether a response was received over a connection taken from the pool,
+/// rather than one established for its request.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ConnectionReused(pub(crate) bool);
+
 /// A Client to make outgoing HTTP requests.
 ///
 /// `Client` is cheap to clone and cloning is the recommended way to share a `Client`. The
@@ -77,6 +84,7 @@ impl<C, B> std::ops::DerefMut for Client<C, B> {
 struct Config {
     retry_canceled_requests: bool,
     set_host: bool,
+    order_host: bool,
     ver: Ver,
 }
 
@@ -122,6 +130,9 @@ struct PoolKey {
     uri: Uri,
     alpn_protos: Option<AlpnProtos>,
     network: NetworkScheme,
+    tag: Option<PoolTag>,
+    server_name: Option<ServerName>,
+    connect_to: Option<ConnectTo>,
 }
 
 impl PoolKey {
@@ -130,10 +141,37 @@ impl PoolKey {
             uri,
             alpn_protos,
             network,
+            tag: None,
+            server_name: None,
+            connect_to: None,
         }
     }
 }
 
+/// A request extension keeping the connections of requests with different
+/// tags apart in the pool.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub(crate) struct PoolTag(pub(crate) u64);
+
+/// A request extension overriding the TLS server name of new connections,
+/// or disabling SNI with `None`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct ServerName(pub(crate) Option<std::borrow::Cow<'static, str>>);
+
+/// A request extension connecting to another endpoint than the host of the
+/// URL, which is still used for the `Host` header, SNI and certificate
+/// verification.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct ConnectTo(pub(crate) http::uri::Authority);
+
+/// A request extension overriding the `:scheme` and `:path` pseudo-headers
+/// of HTTP/2, or the scheme and path of the HTTP/1 request target.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RequestTarget {
+    pub(crate) scheme: Option<Scheme>,
+    pub(crate) path: Option<::http::uri::PathAndQuery>,
+}
+
 /// Destination of the request
 ///
 /// This is used to store the destination of the request, the http version pref, and the pool key.
@@ -198,6 +236,18 @@ impl Dst {
         self.inner.alpn_protos
     }
 
+    /// Get the TLS server name override
+    #[inline(always)]
+    pub(crate) fn server_name(&self) -> Option<ServerName> {
+        self.inner.server_name.clone()
+    }
+
+    /// Get the endpoint to connect to instead of the host of the URI
+    #[inline(always)]
+    pub(crate) fn connect_to(&self) -> Option<&ConnectTo> {
+        self.inner.connect_to.as_ref()
+    }
+
     #[inline(always)]
     pub(crate) fn is_h2(&self) -> bool {
         self.inner.alpn_protos == Some(AlpnProtos::Http2)
@@ -344,12 +394,28 @@ where
             other => return ResponseFuture::error_version(other),
         };
 
-        let ctx = match Dst::new(req.uri_mut(), is_http_connect, network_scheme, alpn_protos) {
+        let mut ctx = match Dst::new(req.uri_mut(), is_http_connect, network_scheme, alpn_protos) {
             Ok(s) => s,
             Err(err) => {
                 return ResponseFuture::new(future::err(err));
             }
         };
+        if let Some(tag) = req.extensions_mut().remove::<PoolTag>() {
+            Arc::make_mut(&mut ctx.inner).tag = Some(tag);
+        }
+        if let Some(server_name) = req.extensions_mut().remove::<ServerName>() {
+            Arc::make_mut(&mut ctx.inner).server_name = Some(server_name);
+        }
+        if let Some(connect_to) = req.extensions_mut().remove::<ConnectTo>() {
+            Arc::make_mut(&mut ctx.inner).connect_to = Some(connect_to);
+        }
+
+        if let Some(pinned) = req.extensions_mut().remove::<PinnedConnection<B>>() {
+            let (pinned_key, key) = (pinned.0.dst.pool_key(), ctx.pool_key());
+            if pinned_key.uri == key.uri && pinned_key.network == key.network {
+                return ResponseFuture::new(self.clone().send_pinned(req, pinned));
+            }
+        }
 
         ResponseFuture::new(self.clone().send_request(req, ctx))
     }
@@ -399,43 +465,8 @@ where
             // it returns an error, there's not much else to retry
             .map_err(TrySendError::Nope)?;
 
-        if let Some(conn) = req.extensions_mut().get_mut::<CaptureConnectionExtension>() {
-            conn.set(&pooled.conn_info)
-        }
-
-        if pooled.is_http1() {
-            if req.version() == Version::HTTP_2 {
-                warn!("Connection is HTTP/1, but request requires HTTP/2");
-                return Err(TrySendError::Nope(
-                    e!(UserUnsupportedVersion).with_connect_info(pooled.conn_info.clone()),
-                ));
-            }
-
-            if self.config.set_host {
-                let uri = req.uri().clone();
-                req.headers_mut().entry(HOST).or_insert_with(|| {
-                    let hostname = uri.host().expect("authority implies host");
-                    if let Some(port) = get_non_default_port(&uri) {
-                        let s = format!("{}:{}", hostname, port);
-                        HeaderValue::from_str(&s)
-                    } else {
-                        HeaderValue::from_str(hostname)
-                    }
-                    .expect("uri host is valid header value")
-                });
-            }
-
-            // CONNECT always sends authority-form, so check it first...
-            if req.method() == Method::CONNECT {
-                authority_form(req.uri_mut());
-            } else if pooled.conn_info.is_proxied {
-                absolute_form(req.uri_mut());
-            } else {
-                origin_form(req.uri_mut());
-            }
-        } else if req.method() == Method::CONNECT {
-            authority_form(req.uri_mut());
-        }
+        self.prepare_request(&mut req, &pooled)
+            .map_err(TrySendError::Nope)?;
 
         let mut res = match pooled.try_send_request(req).await {
             Ok(res) => res,
@@ -460,6 +491,8 @@ where
         if let Some(extra) = &pooled.conn_info.extra {
             extra.set(res.extensions_mut());
         }
+        res.extensions_mut()
+            .insert(ConnectionReused(pooled.is_reused()));
 
         // If pooled is HTTP/2, we can toss this reference immediately.
         //
@@ -494,6 +527,123 @@ where
         Ok(res)
     }
 
+    /// Checks out a connection to `uri`, for [`PinnedConnection`].
+    pub(crate) async fn pin(
+        &self,
+        mut uri: Uri,
+        network_scheme: NetworkScheme,
+    ) -> Result<PinnedConnection<B>, Error> {
+        let dst = Dst::new(&mut uri, false, network_scheme, None)?;
+        let pooled = self.connection_for(dst.clone()).await?;
+        Ok(PinnedConnection(Arc::new(Pinned {
+            dst,
+            used: AtomicBool::new(pooled.is_reused()),
+            pooled: tokio::sync::Mutex::new(Some(pooled)),
+            exec: self.exec.clone(),
+        })))
+    }
+
+    /// Connects to `uri`, or checks out an idle connection to it, and puts
+    /// the connection back in the pool once it is ready for a request.
+    pub(crate) async fn warmup(
+        &self,
+        mut uri: Uri,
+        network_scheme: NetworkScheme,
+        server_name: Option<ServerName>,
+    ) -> Result<(), Error> {
+        let mut dst = Dst::new(&mut uri, false, network_scheme, None)?;
+        if let Some(server_name) = server_name {
+            Arc::make_mut(&mut dst.inner).server_name = Some(server_name);
+        }
+        let mut pooled = self.connection_for(dst).await?;
+        future::poll_fn(|cx| pooled.poll_ready(cx))
+            .await
+            .map_err(|err| err.with_connect_info(pooled.conn_info.clone()))?;
+        Ok(())
+    }
+
+    /// Sends `req` on the pinned connection, waiting for the requests sent on
+    /// it before to finish.
+    async fn send_pinned(
+        self,
+        mut req: Request<B>,
+        pinned: PinnedConnection<B>,
+    ) -> Result<Response<hyper2::body::Incoming>, Error> {
+        let mut pooled = pinned.0.pooled.lock().await;
+        let pooled = pooled
+            .as_mut()
+            .expect("pinned connection is only taken on drop");
+
+        future::poll_fn(|cx| pooled.poll_ready(cx))
+            .await
+            .map_err(|err| err.with_connect_info(pooled.conn_info.clone()))?;
+        self.prepare_request(&mut req, pooled)?;
+
+        let reused = pinned.0.used.swap(true, Ordering::Relaxed);
+        let mut res = pooled.try_send_request(req).await.map_err(|err| {
+            e!(SendRequest, err.into_error()).with_connect_info(pooled.conn_info.clone())
+        })?;
+
+        if let Some(extra) = &pooled.conn_info.extra {
+            extra.set(res.extensions_mut());
+        }
+        res.extensions_mut().insert(ConnectionReused(reused));
+        Ok(res)
+    }
+
+    fn prepare_request(&self, req: &mut Request<B>, pooled: &PoolClient<B>) -> Result<(), Error> {
+        if let Some(conn) = req.extensions_mut().get_mut::<CaptureConnectionExtension>() {
+            conn.set(&pooled.conn_info)
+        }
+
+        if pooled.is_http1() {
+            if req.version() == Version::HTTP_2 {
+                warn!("Connection is HTTP/1, but request requires HTTP/2");
+                return Err(e!(UserUnsupportedVersion).with_connect_info(pooled.conn_info.clone()));
+            }
+
+            if self.config.set_host && !req.headers().contains_key(HOST) {
+                let uri = req.uri();
+                let hostname = uri.host().expect("authority implies host");
+                let host = if let Some(port) = get_non_default_port(uri) {
+                    let s = format!("{}:{}", hostname, port);
+                    HeaderValue::from_str(&s)
+                } else {
+                    HeaderValue::from_str(hostname)
+                }
+                .expect("uri host is valid header value");
+                if self.config.order_host {
+                    let index = req
+                        .extensions()
+                        .get::<HostPosition>()
+                        .map_or(0, |position| position.0);
+                    insert_header_at(req.headers_mut(), index, HOST, host);
+                } else {
+                    req.headers_mut().insert(HOST, host);
+                }
+            }
+
+            // CONNECT always sends authority-form, so check it first...
+            if req.method() == Method::CONNECT {
+                authority_form(req.uri_mut());
+            } else if pooled.conn_info.is_proxied {
+                absolute_form(req.uri_mut());
+            } else {
+                origin_form(req.uri_mut());
+            }
+        } else if req.method() == Method::CONNECT {
+            authority_form(req.uri_mut());
+        }
+
+        if req.method() != Method::CONNECT {
+            if let Some(target) = req.extensions().get::<RequestTarget>().cloned() {
+                set_request_target(req.uri_mut(), target);
+            }
+        }
+
+        Ok(())
+    }
+
     async fn connection_for(
         &self,
         dst: Dst,
@@ -738,6 +888,11 @@ where
     {
         builder(&mut self.h2_builder);
     }
+
+    /// Closes the connection pool, see [`pool::Pool::close`].
+    pub(crate) fn close_pool(&self) {
+        self.pool.close();
+    }
 }
 
 impl<C, B> tower_service::Service<InnerRequest<B>> for Client<C, B>
@@ -832,6 +987,44 @@ impl Future for ResponseFuture {
     }
 }
 
+/// A connection checked out of the pool, which requests carrying it in their
+/// extensions are sent on instead of one the pool selects.
+///
+/// Requests to another origin are sent on a pooled connection as usual. The
+/// connection goes back to the pool once the last clone is dropped.
+pub(crate) struct PinnedConnection<B: Send + 'static>(Arc<Pinned<B>>);
+
+struct Pinned<B: Send + 'static> {
+    dst: Dst,
+    /// Whether a request was sent on the connection, here or before it was
+    /// pinned.
+    used: AtomicBool,
+    pooled: tokio::sync::Mutex<Option<pool::Pooled<PoolClient<B>, PoolKey>>>,
+    exec: Exec,
+}
+
+impl<B: Send + 'static> Clone for PinnedConnection<B> {
+    fn clone(&self) -> Self {
+        PinnedConnection(self.0.clone())
+    }
+}
+
+impl<B: Send + 'static> Drop for Pinned<B> {
+    fn drop(&mut self) {
+        let Some(mut pooled) = self.pooled.get_mut().take() else {
+            return;
+        };
+
+        // like after a request, only go back to the pool once idle
+        if pooled.is_http2() || pooled.is_ready() {
+            drop(pooled);
+        } else {
+            let on_idle = future::poll_fn(move |cx| pooled.poll_ready(cx)).map(|_| ());
+            self.exec.execute(on_idle);
+        }
+    }
+}
+
 // ===== impl PoolClient =====
 
 // FIXME: allow() required due to `impl Trait` leaking types to this lint
@@ -999,6 +1192,18 @@ fn set_scheme(uri: &mut Uri, scheme: Scheme) {
     *uri = Uri::from_parts(parts).expect("scheme is valid");
 }
 
+/// Replaces the path, and the scheme if the request target has one.
+fn set_request_target(uri: &mut Uri, target: RequestTarget) {
+    let mut parts: ::http::uri::Parts = std::mem::take(uri).into();
+    if let Some(scheme) = target.scheme.filter(|_| parts.scheme.is_some()) {
+        parts.scheme = Some(scheme);
+    }
+    if let Some(path) = target.path {
+        parts.path_and_query = Some(path);
+    }
+    *uri = Uri::from_parts(parts).expect("request target is valid uri");
+}
+
 fn get_non_default_port(uri: &Uri) -> Option<http::uri::Port<&str>> {
     match (uri.port().map(|p| p.as_u16()), is_schema_secure(uri)) {
         (Some(443), true) => None,
@@ -1056,6 +1261,7 @@ impl Builder {
             client_config: Config {
                 retry_canceled_requests: true,
                 set_host: true,
+                order_host: false,
                 ver: Ver::Auto,
             },
             exec: exec.clone(),
@@ -1206,6 +1412,18 @@ impl Builder {
         self
     }
 
+    /// Set whether the automatically added `Host` header is placed by the
+    /// headers order of the request, rather than appended.
+    ///
+    /// If true, it is sent first unless the headers order lists `Host`.
+    ///
+    /// Default is `false`.
+    #[inline]
+    pub fn order_host(&mut self, val: bool) -> &mut Self {
+        self.client_config.order_host = val;
+        self
+    }
+
     /// Combine the configuration of this builder with a connector to create a `Client`.
     pub fn build<C, B>(&self, connector: C) -> Client<C, B>
     where
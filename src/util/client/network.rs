@@ -230,7 +230,14 @@ impl NetworkSchemeBuilder {
         where
             I: Into<std::borrow::Cow<'static, str>>,
         {
-            self.interface = Some(interface.into());
+            let interface = interface.into();
+            match split_scoped_ipv6(&interface) {
+                Some((addr, zone)) => {
+                    self.addresses.1 = Some(addr);
+                    self.interface = Some(zone.to_owned().into());
+                }
+                None => self.interface = Some(interface),
+            }
             self
         }
     }
@@ -270,3 +277,37 @@ impl NetworkSchemeBuilder {
         }
     }
 }
+
+cfg_bindable_device! {
+    /// Splits an IPv6 address with a zone, such as `fe80::1%eth0`, into the
+    /// address and the name of the interface.
+    fn split_scoped_ipv6(s: &str) -> Option<(Ipv6Addr, &str)> {
+        let (addr, zone) = s.split_once('%')?;
+        let addr = addr.parse().ok()?;
+        (!zone.is_empty()).then_some((addr, zone))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    cfg_bindable_device! {
+        #[test]
+        fn interface_with_ipv6_zone() {
+            let mut builder = NetworkScheme::builder();
+            builder.interface("fe80::1%eth0");
+            assert_eq!(builder.interface.as_deref(), Some("eth0"));
+            assert_eq!(builder.addresses.1, Some("fe80::1".parse().unwrap()));
+
+            let mut builder = NetworkScheme::builder();
+            builder.interface("eth0");
+            assert_eq!(builder.interface.as_deref(), Some("eth0"));
+            assert_eq!(builder.addresses.1, None);
+
+            let mut builder = NetworkScheme::builder();
+            builder.interface("fe80::1%");
+            assert_eq!(builder.interface.as_deref(), Some("fe80::1%"));
+        }
+    }
+}
@@ -84,6 +84,9 @@ struct PoolInner<T, K: Eq + Hash> {
     // state, waiting to receive a new Request to send on the socket.
     idle: LruCache<K, Vec<Idle<T>>>,
     max_idle_per_host: usize,
+    // Set once the pool is closed, after which released connections are
+    // dropped rather than kept idle.
+    closed: bool,
     // These are outstanding Checkouts that are waiting for a socket to be
     // able to send a Request one. This is used when "racing" for a new
     // connection.
@@ -115,7 +118,7 @@ pub struct Config {
 
 impl Config {
     pub fn is_enabled(&self) -> bool {
-        self.max_idle_per_host > 0
+        self.max_idle_per_host > 0 && self.idle_timeout != Some(Duration::ZERO)
     }
 }
 
@@ -137,6 +140,7 @@ impl<T, K: Key> Pool<T, K> {
                 idle,
                 idle_interval_ref: None,
                 max_idle_per_host: config.max_idle_per_host,
+                closed: false,
                 waiters: HashMap::new(),
                 exec,
                 timer,
@@ -165,6 +169,18 @@ impl<T: Poolable, K: Key> Pool<T, K> {
         }
     }
 
+    /// Drops the idle connections, and the connections released from now on.
+    ///
+    /// Connections still serve the requests waiting for them, and close once
+    /// their requests complete.
+    pub fn close(&self) {
+        if let Some(ref enabled) = self.inner {
+            let mut inner = enabled.lock();
+            inner.closed = true;
+            inner.idle.clear();
+        }
+    }
+
     /// Ensure that there is only ever 1 connecting task for HTTP/2
     /// connections. This does nothing for HTTP/1.
     pub fn connecting(&self, key: &K, ver: Ver) -> Option<Connecting<T, K>> {
@@ -366,6 +382,9 @@ impl<T: Poolable, K: Key> PoolInner<T, K> {
         }
 
         match value {
+            Some(_) if self.closed => {
+                trace!("pool closed, dropping connection for {:?}", key);
+            }
             Some(value) => {
                 // borrow-check scope...
                 {
@@ -1,10 +1,11 @@
 #![allow(missing_debug_implementations)]
 
-use super::NetworkScheme;
+use super::{ConnectTo, NetworkScheme, PinnedConnection, PoolTag, RequestTarget, ServerName};
 use crate::{error::BoxError, AlpnProtos};
 use http::{
-    header::CONTENT_LENGTH, request::Builder, Error, HeaderMap, HeaderName, HeaderValue, Method,
-    Request, Uri, Version,
+    header::{CONTENT_LENGTH, HOST},
+    request::Builder,
+    Error, HeaderMap, HeaderName, HeaderValue, Method, Request, Uri, Version,
 };
 use http_body::Body;
 use std::marker::PhantomData;
@@ -36,6 +37,22 @@ where
         }
     }
 
+    pub fn from_pieces(
+        request: Request<B>,
+        network_scheme: NetworkScheme,
+        alpn_protos: Option<AlpnProtos>,
+    ) -> InnerRequest<B> {
+        InnerRequest {
+            request,
+            alpn_protos,
+            network_scheme,
+        }
+    }
+
+    pub fn request(&self) -> &Request<B> {
+        &self.request
+    }
+
     pub fn pieces(self) -> (Request<B>, NetworkScheme, Option<AlpnProtos>) {
         (self.request, self.network_scheme, self.alpn_protos)
     }
@@ -108,6 +125,51 @@ where
         self
     }
 
+    /// Set the connection to send the request on, if any.
+    #[inline]
+    pub(crate) fn pinned(mut self, pinned: Option<PinnedConnection<B>>) -> Self {
+        if let Some(pinned) = pinned {
+            self.builder = self.builder.extension(pinned);
+        }
+        self
+    }
+
+    /// Set the tag partitioning the connection pool, if any.
+    #[inline]
+    pub(crate) fn pool_tag(mut self, tag: Option<u64>) -> Self {
+        if let Some(tag) = tag {
+            self.builder = self.builder.extension(PoolTag(tag));
+        }
+        self
+    }
+
+    /// Set the TLS server name of new connections, if overridden.
+    #[inline]
+    pub(crate) fn server_name(mut self, server_name: Option<ServerName>) -> Self {
+        if let Some(server_name) = server_name {
+            self.builder = self.builder.extension(server_name);
+        }
+        self
+    }
+
+    /// Set the endpoint new connections are made to, if overridden.
+    #[inline]
+    pub(crate) fn connect_to(mut self, connect_to: Option<ConnectTo>) -> Self {
+        if let Some(connect_to) = connect_to {
+            self.builder = self.builder.extension(connect_to);
+        }
+        self
+    }
+
+    /// Set the overridden scheme and path of the request target, if any.
+    #[inline]
+    pub(crate) fn request_target(mut self, target: Option<RequestTarget>) -> Self {
+        if let Some(target) = target {
+            self.builder = self.builder.extension(target);
+        }
+        self
+    }
+
     /// Set the body for the request.
     #[inline]
     pub fn body(mut self, body: B) -> Result<InnerRequest<B>, Error> {
@@ -121,6 +183,10 @@ where
             sort_headers(headers, order);
         }
 
+        if let Some(position) = self.host_position() {
+            self.builder = self.builder.extension(position);
+        }
+
         self.builder.body(body).map(|request| InnerRequest {
             request,
             alpn_protos: self.alpn_protos,
@@ -129,6 +195,36 @@ where
     }
 }
 
+impl<B> InnerRequestBuilder<'_, B>
+where
+    B: Body + Send + Unpin + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    /// Where the `Host` header goes once the connection adds it, if the
+    /// headers order places it.
+    fn host_position(&self) -> Option<HostPosition> {
+        let order = self.headers_order?;
+        let headers = self.builder.headers_ref()?;
+        if headers.contains_key(HOST) {
+            return None;
+        }
+        let index = order.iter().position(|name| name == HOST)?;
+        let before = order[..index]
+            .iter()
+            .filter(|name| headers.contains_key(*name))
+            .count();
+        Some(HostPosition(before))
+    }
+}
+
+/// The index among the request headers at which an HTTP/1 connection
+/// inserts the `Host` header, when it orders it.
+///
+/// Without it, `Host` is sent first, as browsers do.
+#[derive(Clone, Copy)]
+pub(crate) struct HostPosition(pub(crate) usize);
+
 /// Map the HTTP version to the ALPN protocols.
 fn map_alpn_protos(version: Version) -> Option<AlpnProtos> {
     match version {
@@ -189,3 +285,33 @@ fn sort_headers(headers: &mut HeaderMap, headers_order: &[HeaderName]) {
 
     std::mem::swap(headers, &mut sorted_headers);
 }
+
+/// Insert a header that is not yet present at `index` among the header names.
+pub(super) fn insert_header_at(
+    headers: &mut HeaderMap,
+    index: usize,
+    name: HeaderName,
+    value: HeaderValue,
+) {
+    let mut reordered = HeaderMap::with_capacity(headers.keys_len() + 1);
+    let mut pending = Some((name, value));
+    let mut current = None;
+    for (key, value) in headers.drain() {
+        if let Some(key) = key {
+            if reordered.keys_len() == index {
+                if let Some((name, value)) = pending.take() {
+                    reordered.insert(name, value);
+                }
+            }
+            current = Some(key);
+        }
+        if let Some(key) = &current {
+            reordered.append(key, value);
+        }
+    }
+    if let Some((name, value)) = pending {
+        reordered.insert(name, value);
+    }
+
+    std::mem::swap(headers, &mut reordered);
+}
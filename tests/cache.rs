@@ -0,0 +1,169 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use rquest::cache::CacheConfig;
+use rquest::Client;
+
+fn client() -> Client {
+    Client::builder()
+        .no_proxy()
+        .cache(CacheConfig::new())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn fresh_response_is_served_from_cache() {
+    let _ = env_logger::try_init();
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let server = server::http({
+        let hits = hits.clone();
+        move |_req| {
+            hits.fetch_add(1, Ordering::SeqCst);
+            async {
+                http::Response::builder()
+                    .header("cache-control", "max-age=60")
+                    .body("hello".into())
+                    .unwrap()
+            }
+        }
+    });
+    let url = format!("http://{}/fresh", server.addr());
+    let client = client();
+
+    let res = client.get(&url).send().await.unwrap();
+    assert!(!res.from_cache());
+    assert_eq!(res.text().await.unwrap(), "hello");
+
+    let res = client.get(&url).send().await.unwrap();
+    assert!(res.from_cache());
+    assert_eq!(res.headers()["cache-control"], "max-age=60");
+    assert_eq!(res.text().await.unwrap(), "hello");
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    // A request with `no-cache` goes to the server.
+    let res = client
+        .get(&url)
+        .header("cache-control", "no-cache")
+        .send()
+        .await
+        .unwrap();
+    assert!(!res.from_cache());
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn stale_response_is_revalidated() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        if req
+            .headers()
+            .get("if-none-match")
+            .is_some_and(|v| v == "\"v1\"")
+        {
+            return http::Response::builder()
+                .status(304)
+                .header("etag", "\"v1\"")
+                .header("x-revalidated", "yes")
+                .body(Default::default())
+                .unwrap();
+        }
+        http::Response::builder()
+            .header("cache-control", "no-cache")
+            .header("etag", "\"v1\"")
+            .body("hello".into())
+            .unwrap()
+    });
+    let url = format!("http://{}/etag", server.addr());
+    let client = client();
+
+    let res = client.get(&url).send().await.unwrap();
+    assert!(!res.from_cache());
+    assert!(res.headers().get("x-revalidated").is_none());
+    assert_eq!(res.text().await.unwrap(), "hello");
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    assert!(res.from_cache());
+    assert_eq!(res.headers()["x-revalidated"], "yes");
+    assert_eq!(res.text().await.unwrap(), "hello");
+
+    // Conditional requests of the caller are left to the caller.
+    let res = client
+        .get(&url)
+        .header("if-none-match", "\"v1\"")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 304);
+    assert!(!res.from_cache());
+}
+
+#[tokio::test]
+async fn uncacheable_responses_are_not_kept() {
+    let _ = env_logger::try_init();
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let server = server::http({
+        let hits = hits.clone();
+        move |req| {
+            hits.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let cache_control = match req.uri().path() {
+                    "/no-store" => "no-store, max-age=60",
+                    _ => "max-age=60",
+                };
+                http::Response::builder()
+                    .header("cache-control", cache_control)
+                    .body("hello".into())
+                    .unwrap()
+            }
+        }
+    });
+    let client = client();
+
+    let url = format!("http://{}/no-store", server.addr());
+    for _ in 0..2 {
+        let res = client.get(&url).send().await.unwrap();
+        assert!(!res.from_cache());
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    // A POST evicts the response of its URL.
+    let url = format!("http://{}/posted", server.addr());
+    client.get(&url).send().await.unwrap();
+    assert!(client.get(&url).send().await.unwrap().from_cache());
+    client.post(&url).send().await.unwrap();
+    assert!(!client.get(&url).send().await.unwrap().from_cache());
+    assert_eq!(hits.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn vary_must_match() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let lang = req.headers()["accept-language"].clone();
+        http::Response::builder()
+            .header("cache-control", "max-age=60")
+            .header("vary", "accept-language")
+            .body(lang.to_str().unwrap().to_owned().into())
+            .unwrap()
+    });
+    let url = format!("http://{}/vary", server.addr());
+    let client = client();
+
+    let get = |lang: &'static str| client.get(&url).header("accept-language", lang).send();
+    assert!(!get("en").await.unwrap().from_cache());
+    assert!(get("en").await.unwrap().from_cache());
+
+    let res = get("de").await.unwrap();
+    assert!(!res.from_cache());
+    assert_eq!(res.text().await.unwrap(), "de");
+}
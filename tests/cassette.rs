@@ -0,0 +1,141 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use rquest::cassette::Mode;
+use rquest::Client;
+
+fn cassette_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "rquest-cassette-{}-{}.json",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[tokio::test]
+async fn record_then_replay() {
+    let _ = env_logger::try_init();
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let server = server::http({
+        let hits = hits.clone();
+        move |_req| {
+            hits.fetch_add(1, Ordering::SeqCst);
+            async {
+                http::Response::builder()
+                    .status(201)
+                    .header("x-recorded", "yes")
+                    .body("hello".into())
+                    .unwrap()
+            }
+        }
+    });
+    let url = format!("http://{}/recorded", server.addr());
+    let path = cassette_path("record-then-replay");
+
+    let client = Client::builder()
+        .no_proxy()
+        .cassette(&path, Mode::RecordOrReplay)
+        .build()
+        .unwrap();
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.status(), 201);
+    assert_eq!(res.text().await.unwrap(), "hello");
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    // The same client replays what it just recorded.
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    drop(server);
+
+    let client = Client::builder()
+        .no_proxy()
+        .cassette(&path, Mode::Replay)
+        .build()
+        .unwrap();
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.status(), 201);
+    assert_eq!(res.headers()["x-recorded"], "yes");
+    assert_eq!(res.text().await.unwrap(), "hello");
+
+    let err = client.post(&url).send().await.unwrap_err();
+    assert!(err.is_request());
+    assert!(err.to_string().contains("cassette"), "{err}");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn cassette_matches_selected_headers() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let user = req.headers()["x-user"].to_str().unwrap().to_owned();
+        http::Response::new(user.into())
+    });
+    let url = format!("http://{}/whoami", server.addr());
+    let path = cassette_path("match-headers");
+
+    let client = Client::builder()
+        .no_proxy()
+        .cassette(&path, Mode::Record)
+        .cassette_match_headers([http::HeaderName::from_static("x-user")])
+        .build()
+        .unwrap();
+    for user in ["alice", "bob"] {
+        let res = client
+            .get(&url)
+            .header("x-user", user)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), user);
+    }
+    drop(server);
+
+    let recorded = std::fs::read_to_string(&path).unwrap();
+    assert!(recorded.contains("\"x-user\""), "{recorded}");
+
+    let client = Client::builder()
+        .no_proxy()
+        .cassette(&path, Mode::Replay)
+        .cassette_match_headers([http::HeaderName::from_static("x-user")])
+        .build()
+        .unwrap();
+    for user in ["bob", "alice"] {
+        let res = client
+            .get(&url)
+            .header("x-user", user)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), user);
+    }
+    let err = client
+        .get(&url)
+        .header("x-user", "carol")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_request());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn replay_requires_cassette() {
+    let err = Client::builder()
+        .cassette(cassette_path("missing"), Mode::Replay)
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+}
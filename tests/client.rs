@@ -63,6 +63,127 @@ async fn auto_headers() {
     assert_eq!(res.remote_addr(), Some(server.addr()));
 }
 
+#[tokio::test]
+async fn default_headers_mode_strict() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers().get("accept"), None);
+        assert_eq!(req.headers().get("accept-encoding"), None);
+        assert_eq!(req.headers()["x-custom"], "1");
+        http::Response::default()
+    });
+
+    let res = rquest::Client::builder()
+        .default_headers_mode(rquest::DefaultHeadersMode::Strict)
+        .build()
+        .unwrap()
+        .get(format!("http://{}/", server.addr()))
+        .header("x-custom", "1")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn default_headers_mode_accept_encoding() {
+    use rquest::DefaultHeadersMode;
+
+    let server = server::http(move |req| async move {
+        let accept_encoding = req
+            .headers()
+            .get("accept-encoding")
+            .map(|value| value.to_str().unwrap().to_owned());
+        http::Response::new(accept_encoding.unwrap_or_default().into())
+    });
+
+    for (mode, sent) in [
+        (DefaultHeadersMode::Auto, true),
+        (DefaultHeadersMode::Strict, false),
+    ] {
+        let accept_encoding = rquest::Client::builder()
+            .default_headers_mode(mode)
+            .build()
+            .unwrap()
+            .get(format!("http://{}/", server.addr()))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(accept_encoding.contains("gzip"), sent, "{mode:?}");
+        assert_eq!(accept_encoding.is_empty(), !sent, "{mode:?}");
+    }
+}
+
+#[tokio::test]
+async fn response_local_addr() {
+    let server = server::http(move |_| async move { http::Response::default() });
+
+    let res = rquest::Client::builder()
+        .local_address(std::net::IpAddr::from([127, 0, 0, 1]))
+        .build()
+        .unwrap()
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap();
+
+    let local_addr = res.local_addr().unwrap();
+    assert_eq!(local_addr.ip(), std::net::IpAddr::from([127, 0, 0, 1]));
+    assert_ne!(local_addr.port(), 0);
+    assert_ne!(Some(local_addr), res.remote_addr());
+}
+
+#[test]
+fn request_to_curl() {
+    let client = Client::new();
+
+    let req = client.get("http://example.com/a?b=c").build().unwrap();
+    assert_eq!(req.to_curl(), "curl 'http://example.com/a?b=c'");
+
+    let req = client
+        .put("https://example.com/")
+        .version(Version::HTTP_2)
+        .header("x-b", "it's")
+        .header("x-a", "")
+        .header_append("x-b", "2")
+        .headers_order(vec![rquest::header::HeaderName::from_static("x-a")])
+        .body(&b"line\n\xff"[..])
+        .build()
+        .unwrap();
+    assert_eq!(
+        req.to_curl(),
+        r"curl -X PUT --http2 -H 'x-a;' -H 'x-b: it'\''s' -H 'x-b: 2' --data-raw $'line\x0a\xff' 'https://example.com/'"
+    );
+
+    let req = client.head("http://example.com/").build().unwrap();
+    assert_eq!(req.to_curl(), "curl --head 'http://example.com/'");
+
+    // a body is never read from a file, and doesn't turn a GET into a POST
+    let req = client
+        .get("http://example.com/")
+        .body("@/etc/passwd")
+        .build()
+        .unwrap();
+    assert_eq!(
+        req.to_curl(),
+        "curl -X GET --data-raw '@/etc/passwd' 'http://example.com/'"
+    );
+
+    let req = client
+        .get("http://example.com/")
+        .version(Version::HTTP_2)
+        .build()
+        .unwrap();
+    assert_eq!(
+        req.to_curl(),
+        "curl --http2-prior-knowledge 'http://example.com/'"
+    );
+}
+
 #[tokio::test]
 async fn donot_set_content_length_0_if_have_no_body() {
     let server = server::http(move |req| async move {
@@ -143,6 +264,35 @@ async fn response_bytes() {
     assert_eq!("Hello", bytes);
 }
 
+#[tokio::test]
+async fn response_into_http_parts() {
+    use http_body_util::BodyExt;
+
+    let server = server::http(move |_req| async {
+        http::Response::builder()
+            .status(http::StatusCode::CREATED)
+            .header("x-test", "parts")
+            .body("Hello".into())
+            .unwrap()
+    });
+    let url = format!("http://{}/parts", server.addr());
+    let client = Client::new();
+
+    let res = client.get(&url).send().await.unwrap();
+    let res: http::Response<rquest::Body> = res.into();
+    assert_eq!(res.status(), http::StatusCode::CREATED);
+    assert_eq!(res.headers()["x-test"], "parts");
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "Hello");
+
+    let res = client.get(&url).send().await.unwrap();
+    let (status, headers, version, body) = res.into_parts();
+    assert_eq!(status, http::StatusCode::CREATED);
+    assert_eq!(headers["x-test"], "parts");
+    assert_eq!(version, Version::HTTP_11);
+    assert_eq!(body.collect().await.unwrap().to_bytes(), "Hello");
+}
+
 #[tokio::test]
 #[cfg(feature = "json")]
 async fn response_json() {
@@ -162,245 +312,3272 @@ async fn response_json() {
 }
 
 #[tokio::test]
-async fn body_pipe_response() {
-    use http_body_util::BodyExt;
-    let _ = env_logger::try_init();
-
-    let server = server::http(move |req| async move {
-        if req.uri() == "/get" {
-            http::Response::new("pipe me".into())
-        } else {
-            assert_eq!(req.uri(), "/pipe");
-            assert_eq!(req.headers()["content-length"], "7");
-
-            let full: Vec<u8> = req
-                .into_body()
-                .collect()
-                .await
-                .expect("must succeed")
-                .to_bytes()
-                .to_vec();
+#[cfg(all(feature = "json", feature = "stream"))]
+async fn response_json_stream() {
+    use futures_util::StreamExt;
 
-            assert_eq!(full, b"pipe me");
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Item {
+        n: u32,
+    }
 
-            http::Response::default()
-        }
+    let server = server::http(move |_req| async {
+        let chunks = futures_util::stream::iter(
+            ["{\"n\":1}\n{\"n\"", ":2}\r\n\n", "not json\n", "{\"n\":3}"]
+                .map(Ok::<_, std::convert::Infallible>),
+        );
+        http::Response::new(rquest::Body::wrap_stream(chunks))
     });
 
-    let client = Client::new();
-
-    let res1 = client
-        .get(format!("http://{}/get", server.addr()))
+    let mut stream = Client::new()
+        .get(format!("http://{}/ndjson", server.addr()))
         .send()
         .await
-        .expect("get1");
+        .unwrap()
+        .json_stream::<Item>();
+    let mut items = Vec::new();
+    while let Some(item) = stream.next().await {
+        items.push(item);
+    }
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0].as_ref().unwrap(), &Item { n: 1 });
+    assert_eq!(items[1].as_ref().unwrap(), &Item { n: 2 });
+    assert!(items[2].as_ref().unwrap_err().is_decode());
+    assert_eq!(items[3].as_ref().unwrap(), &Item { n: 3 });
+}
 
-    assert_eq!(res1.status(), rquest::StatusCode::OK);
-    assert_eq!(res1.content_length(), Some(7));
+#[tokio::test]
+#[cfg(all(feature = "json", feature = "charset"))]
+async fn response_json_with_charset() {
+    let server = server::http(move |_req| async {
+        // "Café" in ISO-8859-1.
+        http::Response::builder()
+            .header("content-type", "application/json; charset=iso-8859-1")
+            .body(b"\"Caf\xe9\"".to_vec().into())
+            .unwrap()
+    });
 
-    // and now ensure we can "pipe" the response to another request
-    let res2 = client
-        .post(format!("http://{}/pipe", server.addr()))
-        .body(res1)
+    let text = Client::new()
+        .get(format!("http://{}/json", server.addr()))
         .send()
         .await
-        .expect("res2");
-
-    assert_eq!(res2.status(), rquest::StatusCode::OK);
+        .unwrap()
+        .json::<String>()
+        .await
+        .unwrap();
+    assert_eq!(text, "Café");
 }
 
+#[cfg(not(feature = "zstd"))]
 #[tokio::test]
-async fn overridden_dns_resolution_with_gai() {
-    let _ = env_logger::builder().is_test(true).try_init();
-    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+async fn advertised_zstd_without_feature_is_passed_through() {
+    let content = "advertised but not decoded";
+    let compressed = zstd::encode_all(content.as_bytes(), 3).unwrap();
+    let raw = compressed.clone();
 
-    let overridden_domain = "rust-lang.org";
-    let url = format!(
-        "http://{overridden_domain}:{}/domain_override",
-        server.addr().port()
-    );
-    let client = rquest::Client::builder()
+    let server = server::http(move |req| {
+        let compressed = compressed.clone();
+        async move {
+            assert_eq!(req.headers()["accept-encoding"], "gzip, deflate, br, zstd");
+            http::Response::builder()
+                .header("content-encoding", "zstd")
+                .header("content-length", compressed.len())
+                .body(compressed.into())
+                .unwrap()
+        }
+    });
+
+    let res = rquest::Client::builder()
         .no_proxy()
-        .resolve(overridden_domain, server.addr())
+        .accept_encoding("gzip, deflate, br, zstd")
         .build()
-        .expect("client builder");
-    let req = client.get(&url);
-    let res = req.send().await.expect("request");
+        .unwrap()
+        .get(format!("http://{}/zstd", server.addr()))
+        .send()
+        .await
+        .unwrap();
 
-    assert_eq!(res.status(), rquest::StatusCode::OK);
-    let text = res.text().await.expect("Failed to get text");
-    assert_eq!("Hello", text);
+    assert_eq!(res.headers()["content-encoding"], "zstd");
+    let body = res.bytes().await.unwrap();
+    assert_eq!(&body[..], &raw[..]);
 }
 
 #[tokio::test]
-async fn overridden_dns_resolution_with_gai_multiple() {
-    let _ = env_logger::builder().is_test(true).try_init();
-    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+async fn bytes_limit() {
+    // Three 16 byte chunks, without a Content-Length to check against.
+    const CHUNKED: &[u8] = b"HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n\
+        10\r\naaaaaaaaaaaaaaaa\r\n\
+        10\r\nbbbbbbbbbbbbbbbb\r\n\
+        10\r\ncccccccccccccccc\r\n\
+        0\r\n\r\n";
 
-    let overridden_domain = "rust-lang.org";
-    let url = format!(
-        "http://{overridden_domain}:{}/domain_override",
-        server.addr().port()
-    );
-    // the server runs on IPv4 localhost, so provide both IPv4 and IPv6 and let the happy eyeballs
-    // algorithm decide which address to use.
-    let client = rquest::Client::builder()
-        .no_proxy()
-        .resolve_to_addrs(
-            overridden_domain,
-            &[
-                std::net::SocketAddr::new(
-                    std::net::IpAddr::V6(std::net::Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)),
-                    server.addr().port(),
-                ),
-                server.addr(),
-            ],
-        )
-        .build()
-        .expect("client builder");
-    let req = client.get(&url);
-    let res = req.send().await.expect("request");
+    let server = server::low_level_with_response(move |raw_request, client_socket| {
+        Box::new(async move {
+            use tokio::io::AsyncWriteExt;
+            let response: &[u8] = if raw_request.starts_with(b"GET /chunked") {
+                CHUNKED
+            } else {
+                b"HTTP/1.1 200 OK\r\ncontent-length: 48\r\n\r\n"
+            };
+            client_socket.write_all(response).await.unwrap();
+            client_socket.flush().await.unwrap();
+        })
+    });
+    let client = Client::builder().bytes_limit(40).build().unwrap();
+    let url = |path| format!("http://{}/{}", server.addr(), path);
 
-    assert_eq!(res.status(), rquest::StatusCode::OK);
-    let text = res.text().await.expect("Failed to get text");
-    assert_eq!("Hello", text);
+    let err = client
+        .get(url("chunked"))
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap_err();
+    assert!(err.is_body_too_large(), "{err:?}");
+
+    // The announced length is rejected before the body is read.
+    let err = client
+        .get(url("length"))
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap_err();
+    assert!(err.is_body_too_large(), "{err:?}");
+
+    let body = client
+        .get(url("chunked"))
+        .bytes_limit(48)
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    assert_eq!(body.len(), 48);
 }
 
-#[cfg(feature = "hickory-dns")]
 #[tokio::test]
-async fn overridden_dns_resolution_with_hickory_dns() {
-    let _ = env_logger::builder().is_test(true).try_init();
-    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+async fn http1_host_header_order() {
+    use http::header::{HeaderMap, HeaderValue, ACCEPT, HOST, USER_AGENT};
 
-    let overridden_domain = "rust-lang.org";
-    let url = format!(
-        "http://{overridden_domain}:{}/domain_override",
-        server.addr().port()
-    );
-    let client = rquest::Client::builder()
-        .no_proxy()
-        .resolve(overridden_domain, server.addr())
-        .build()
-        .expect("client builder");
-    let req = client.get(&url);
-    let res = req.send().await.expect("request");
+    // Answers with the request header names, in the order they were sent.
+    let server = server::low_level_with_response(|raw_request, client_socket| {
+        Box::new(async move {
+            use tokio::io::AsyncWriteExt;
+            let head = String::from_utf8_lossy(raw_request);
+            let names = head
+                .split("\r\n")
+                .skip(1)
+                .take_while(|line| !line.is_empty())
+                .map(|line| line.split(':').next().unwrap().to_ascii_lowercase())
+                .collect::<Vec<_>>()
+                .join(",");
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{names}",
+                names.len()
+            );
+            client_socket.write_all(response.as_bytes()).await.unwrap();
+            client_socket.flush().await.unwrap();
+        })
+    });
+    let url = format!("http://{}/", server.addr());
+    let names = |client: Client| {
+        let url = url.clone();
+        async move { client.get(url).send().await.unwrap().text().await.unwrap() }
+    };
 
-    assert_eq!(res.status(), rquest::StatusCode::OK);
-    let text = res.text().await.expect("Failed to get text");
-    assert_eq!("Hello", text);
-}
+    let chrome = |order_host| {
+        Client::builder()
+            .impersonate(Impersonate::Chrome131)
+            .http1_order_host_header(order_host)
+            .build()
+            .unwrap()
+    };
+    let sent = names(chrome(true)).await;
+    assert!(sent.starts_with("host,"), "{sent}");
+    assert!(sent.contains(",user-agent"), "{sent}");
 
-#[cfg(feature = "hickory-dns")]
-#[tokio::test]
-async fn overridden_dns_resolution_with_hickory_dns_multiple() {
-    let _ = env_logger::builder().is_test(true).try_init();
-    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+    // appended unless asked for
+    let sent = names(chrome(false)).await;
+    assert!(sent.ends_with(",host"), "{sent}");
 
-    let overridden_domain = "rust-lang.org";
-    let url = format!(
-        "http://{overridden_domain}:{}/domain_override",
-        server.addr().port()
-    );
-    // the server runs on IPv4 localhost, so provide both IPv4 and IPv6 and let the happy eyeballs
-    // algorithm decide which address to use.
-    let client = rquest::Client::builder()
-        .no_proxy()
-        .resolve_to_addrs(
-            overridden_domain,
-            &[
-                std::net::SocketAddr::new(
-                    std::net::IpAddr::V6(std::net::Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)),
-                    server.addr().port(),
-                ),
-                server.addr(),
-            ],
-        )
+    let ordered = Client::builder()
+        .default_headers(HeaderMap::from_iter([
+            (USER_AGENT, HeaderValue::from_static("test")),
+            (ACCEPT, HeaderValue::from_static("*/*")),
+        ]))
+        .headers_order(vec![USER_AGENT, HOST, ACCEPT])
+        .http1_order_host_header(true)
         .build()
-        .expect("client builder");
-    let req = client.get(&url);
-    let res = req.send().await.expect("request");
+        .unwrap();
+    assert_eq!(names(ordered).await, "user-agent,host,accept");
 
-    assert_eq!(res.status(), rquest::StatusCode::OK);
-    let text = res.text().await.expect("Failed to get text");
-    assert_eq!("Hello", text);
+    // Listed headers that are not sent don't count.
+    let ordered = Client::builder()
+        .default_headers(HeaderMap::from_iter([(
+            ACCEPT,
+            HeaderValue::from_static("*/*"),
+        )]))
+        .headers_order(vec![USER_AGENT, ACCEPT, HOST])
+        .http1_order_host_header(true)
+        .build()
+        .unwrap();
+    assert_eq!(names(ordered).await, "accept,host");
 }
 
 #[tokio::test]
-#[ignore = "Needs TLS support in the test server"]
-async fn http2_upgrade() {
-    let server = server::http(move |_| async move { http::Response::default() });
+async fn preserve_header_order() {
+    use http::header::{HeaderMap, HeaderName, HeaderValue};
 
-    let url = format!("https://localhost:{}", server.addr().port());
-    let res = rquest::Client::builder()
-        .danger_accept_invalid_certs(true)
+    // Answers with the request header names, in the order they were sent.
+    let server = server::low_level_with_response(|raw_request, client_socket| {
+        Box::new(async move {
+            use tokio::io::AsyncWriteExt;
+            let head = String::from_utf8_lossy(raw_request);
+            let names = head
+                .split("\r\n")
+                .skip(1)
+                .take_while(|line| !line.is_empty())
+                .map(|line| line.split(':').next().unwrap().to_ascii_lowercase())
+                .collect::<Vec<_>>()
+                .join(",");
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{names}",
+                names.len()
+            );
+            client_socket.write_all(response.as_bytes()).await.unwrap();
+            client_socket.flush().await.unwrap();
+        })
+    });
+
+    let client = Client::builder()
+        .default_headers(HeaderMap::from_iter([
+            (
+                HeaderName::from_static("x-default"),
+                HeaderValue::from_static("1"),
+            ),
+            (
+                HeaderName::from_static("x-removed"),
+                HeaderValue::from_static("1"),
+            ),
+            (
+                HeaderName::from_static("x-last"),
+                HeaderValue::from_static("1"),
+            ),
+        ]))
+        .default_headers_mode(rquest::DefaultHeadersMode::Strict)
+        .preserve_header_order(true)
         .build()
-        .expect("client builder")
-        .get(&url)
+        .unwrap();
+    let sent = client
+        .get(format!("http://{}/", server.addr()))
+        .header("x-a", "1")
+        .header("x-b", "1")
+        .header("x-c", "1")
+        .header("x-d", "1")
+        .remove_header("x-b")
+        .remove_header("x-removed")
         .send()
         .await
-        .expect("request");
-
-    assert_eq!(res.status(), rquest::StatusCode::OK);
-    assert_eq!(res.version(), rquest::Version::HTTP_2);
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(sent, "host,x-a,x-c,x-d,x-default,x-last");
 }
 
-#[test]
-#[cfg(feature = "json")]
-fn add_json_default_content_type_if_not_set_manually() {
-    let mut map = HashMap::new();
-    map.insert("body", "json");
-    let content_type = http::HeaderValue::from_static("application/vnd.api+json");
+#[tokio::test]
+async fn bytes_sent_and_received() {
+    // Answers with the length of the request head.
+    let server = server::low_level_with_response(|raw_request, client_socket| {
+        Box::new(async move {
+            use tokio::io::AsyncWriteExt;
+            let len = raw_request.len().to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{len}",
+                len.len()
+            );
+            client_socket.write_all(response.as_bytes()).await.unwrap();
+            client_socket.flush().await.unwrap();
+        })
+    });
+
+    let client = Client::builder().count_bytes(true).build().unwrap();
+    assert_eq!(client.bytes_sent(), 0);
+    assert_eq!(client.bytes_received(), 0);
+
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    let sent = res.text().await.unwrap();
+    let received = format!(
+        "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{sent}",
+        sent.len()
+    );
+
+    assert_eq!(client.bytes_sent(), sent.parse::<u64>().unwrap());
+    assert_eq!(client.bytes_received(), received.len() as u64);
+}
+
+async fn raw_response(
+    response: &'static [u8],
+    defensive: bool,
+) -> Result<rquest::Response, rquest::Error> {
+    let server = server::low_level_with_response(move |_raw_request, client_socket| {
+        Box::new(async move {
+            use tokio::io::AsyncWriteExt;
+            client_socket.write_all(response).await.unwrap();
+            client_socket.flush().await.unwrap();
+        })
+    });
+
+    rquest::Client::builder()
+        .defensive_parsing(defensive)
+        .build()
+        .unwrap()
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+}
+
+#[tokio::test]
+async fn response_reason() {
+    for (response, reason) in [
+        (&b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n"[..], "OK"),
+        (
+            b"HTTP/1.1 200 Quota  left: 3\r\nContent-Length: 0\r\n\r\n",
+            "Quota  left: 3",
+        ),
+        (
+            b"HTTP/1.1 404 not found\r\nContent-Length: 0\r\n\r\n",
+            "not found",
+        ),
+        (b"HTTP/1.1 599 \r\nContent-Length: 0\r\n\r\n", ""),
+    ] {
+        let res = raw_response(response, false).await.unwrap();
+        assert_eq!(res.reason(), Some(reason));
+    }
+}
+
+#[tokio::test]
+async fn defensive_parsing_accepts_well_formed_response() {
+    let res = raw_response(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello", true)
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+}
+
+#[tokio::test]
+async fn defensive_parsing_rejects_bare_lf() {
+    const RESPONSE: &[u8] = b"HTTP/1.1 200 OK\nContent-Length: 5\n\nhello";
+
+    assert!(raw_response(RESPONSE, false).await.is_ok());
+    assert!(raw_response(RESPONSE, true).await.is_err());
+}
+
+#[tokio::test]
+async fn defensive_parsing_rejects_nul() {
+    const RESPONSE: &[u8] = b"HTTP/1.1 200 OK\r\nX-Foo: b\0ar\r\nContent-Length: 0\r\n\r\n";
+
+    assert!(raw_response(RESPONSE, true).await.is_err());
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn early_response_during_upload() {
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn read_until(io: &mut tokio::net::TcpStream, request: &mut Vec<u8>, end: &[u8]) {
+        let mut buf = [0; 1024];
+        while !request.windows(end.len()).any(|w| w == end) {
+            let n = io.read(&mut buf).await.unwrap();
+            assert_ne!(n, 0);
+            request.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    // neither checked nor counted as a response head
+    let body = format!("to\0\n{}", "a".repeat(100));
+
+    for client in [
+        rquest::Client::builder().defensive_parsing(true),
+        rquest::Client::builder().max_response_header_size(64),
+    ] {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn({
+            let body = body.clone();
+            async move {
+                let (mut io, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+
+                // answer before the upload is done, as for a 413
+                read_until(&mut io, &mut request, b"\r\n\r\n").await;
+                let head = format!(
+                    "HTTP/1.1 413 Payload Too Large\r\nContent-Length: {}\r\n\r\n",
+                    body.len()
+                );
+                io.write_all(head.as_bytes()).await.unwrap();
+
+                read_until(&mut io, &mut request, b"0\r\n\r\n").await;
+                io.write_all(body.as_bytes()).await.unwrap();
+            }
+        });
+
+        let chunks = futures_util::stream::unfold(0, |n| async move {
+            match n {
+                0 => Some((Ok::<_, std::convert::Infallible>("first"), 1)),
+                1 => {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    Some((Ok("second"), 2))
+                }
+                _ => None,
+            }
+        });
+        let res = client
+            .build()
+            .unwrap()
+            .post(format!("http://{addr}/"))
+            .body(rquest::Body::wrap_stream(chunks))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), rquest::StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(res.text().await.unwrap(), body);
+
+        server.await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn max_response_header_size() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .header("x-large", "a".repeat(2000))
+            .body(Default::default())
+            .unwrap()
+    });
+
+    let get = |max: usize, version| {
+        rquest::Client::builder()
+            .max_response_header_size(max)
+            .build()
+            .unwrap()
+            .get(format!("http://{}/", server.addr()))
+            .version(version)
+            .send()
+    };
+
+    let err = get(1024, Version::HTTP_11).await.unwrap_err();
+    assert!(err.is_header_too_large(), "{err:?}");
+    assert!(err.is_request());
+
+    let res = get(4096, Version::HTTP_11).await.unwrap();
+    assert_eq!(res.headers()["x-large"].len(), 2000);
+
+    // left to the HTTP/2 settings
+    let res = get(1024, Version::HTTP_2).await.unwrap();
+    assert_eq!(res.headers()["x-large"].len(), 2000);
+
+    // other errors are not mistaken for it
+    let err = raw_response(b"HTTP/1.1 200 OK\nContent-Length: 0\n\n", true)
+        .await
+        .unwrap_err();
+    assert!(!err.is_header_too_large());
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn stream_body_without_length() {
+    use http_body_util::BodyExt;
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers().get("content-length"), None);
+        if req.version() == http::Version::HTTP_11 {
+            assert_eq!(req.headers()["transfer-encoding"], "chunked");
+        } else {
+            assert_eq!(req.headers().get("transfer-encoding"), None);
+        }
+
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "hello world");
+        http::Response::default()
+    });
+
+    for version in [Version::HTTP_11, Version::HTTP_2] {
+        let chunks = futures_util::stream::iter(
+            ["hello", " ", "world"].map(Ok::<_, std::convert::Infallible>),
+        );
+        let res = Client::new()
+            .post(format!("http://{}/", server.addr()))
+            .version(version)
+            .body(rquest::Body::wrap_stream(chunks))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+        assert_eq!(res.version(), version);
+    }
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn body_from_file_sends_length() {
+    use http_body_util::BodyExt;
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["content-length"], "11");
+        assert_eq!(req.headers().get("transfer-encoding"), None);
+
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "hello world");
+        http::Response::default()
+    });
+
+    let path = std::env::temp_dir().join(format!("rquest-upload-{}", std::process::id()));
+    std::fs::write(&path, "hello world").unwrap();
+
+    for version in [Version::HTTP_11, Version::HTTP_2] {
+        let res = Client::new()
+            .post(format!("http://{}/", server.addr()))
+            .version(version)
+            .body_from_file(&path)
+            .await
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    let err = Client::new()
+        .post(format!("http://{}/", server.addr()))
+        .body_from_file(&path)
+        .await
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn response_save_to_file() {
+    use rquest::SaveOptions;
+    use tokio::io::AsyncWriteExt;
+
+    let server = server::http(move |_req| async move { http::Response::new("hello world".into()) });
+    let url = format!("http://{}/", server.addr());
+    let dir = std::env::temp_dir().join(format!("rquest-save-{}", std::process::id()));
+    let path = dir.join("nested").join("file.txt");
+
+    // the parent directory is only created when asked to
+    let res = rquest::get(&url).await.unwrap();
+    let err = res.save_to_file(&path).await.unwrap_err();
+    assert!(err.is_write(), "{err:?}");
+    assert!(!err.is_decode());
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::NotFound));
+
+    let res = rquest::get(&url).await.unwrap();
+    let options = SaveOptions::new().create_dirs(true);
+    assert_eq!(res.save_to_file_with(&path, options).await.unwrap(), 11);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello world");
+
+    // a body cut short leaves the file as it was, unless asked to keep it
+    let files = || std::fs::read_dir(path.parent().unwrap()).unwrap().count();
+    let server = server::low_level_with_response(|_raw_request, client_socket| {
+        Box::new(async move {
+            let _ = client_socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 100\r\n\r\npartial")
+                .await;
+            let _ = client_socket.shutdown().await;
+        })
+    });
+    let url = format!("http://{}/", server.addr());
+
+    let res = rquest::get(&url).await.unwrap();
+    assert!(res.save_to_file(&path).await.is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello world");
+    assert_eq!(files(), 1);
+
+    let res = rquest::get(&url).await.unwrap();
+    let options = SaveOptions::new().keep_partial(true);
+    assert!(res.save_to_file_with(&path, options).await.is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "partial");
+    assert_eq!(files(), 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn body_from_reader_with_len() {
+    use http_body_util::BodyExt;
+
+    let server = server::http(move |req| async move {
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "hello");
+        http::Response::default()
+    });
+
+    // A reader longer than the length is cut off at the length.
+    let res = Client::new()
+        .post(format!("http://{}/", server.addr()))
+        .body(rquest::Body::from_reader_with_len(&b"hello world"[..], 5))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+
+    // A reader shorter than the length fails the request.
+    let err = Client::new()
+        .post(format!("http://{}/", server.addr()))
+        .body(rquest::Body::from_reader_with_len(&b"hi"[..], 5))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_request(), "{err:?}");
+}
+
+#[tokio::test]
+async fn body_pipe_response() {
+    use http_body_util::BodyExt;
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        if req.uri() == "/get" {
+            http::Response::new("pipe me".into())
+        } else {
+            assert_eq!(req.uri(), "/pipe");
+            assert_eq!(req.headers()["content-length"], "7");
+
+            let full: Vec<u8> = req
+                .into_body()
+                .collect()
+                .await
+                .expect("must succeed")
+                .to_bytes()
+                .to_vec();
+
+            assert_eq!(full, b"pipe me");
+
+            http::Response::default()
+        }
+    });
+
+    let client = Client::new();
+
+    let res1 = client
+        .get(format!("http://{}/get", server.addr()))
+        .send()
+        .await
+        .expect("get1");
+
+    assert_eq!(res1.status(), rquest::StatusCode::OK);
+    assert_eq!(res1.content_length(), Some(7));
+
+    // and now ensure we can "pipe" the response to another request
+    let res2 = client
+        .post(format!("http://{}/pipe", server.addr()))
+        .body(res1)
+        .send()
+        .await
+        .expect("res2");
+
+    assert_eq!(res2.status(), rquest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn reload_cache_headers() {
+    use rquest::ReloadKind;
+
+    let server = server::http(move |req| async move {
+        let cache_headers = req
+            .headers()
+            .iter()
+            .filter(|(name, _)| *name == "pragma" || *name == "cache-control")
+            .map(|(name, value)| format!("{}: {}", name, value.to_str().unwrap()))
+            .collect::<Vec<_>>();
+        match req.uri().path() {
+            "/normal" => assert!(cache_headers.is_empty()),
+            "/reload" => assert_eq!(cache_headers, ["cache-control: max-age=0"]),
+            "/hard" => assert_eq!(
+                cache_headers,
+                ["pragma: no-cache", "cache-control: no-cache"]
+            ),
+            path => panic!("unexpected path {path}"),
+        }
+        http::Response::default()
+    });
+
+    let client = Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .build()
+        .unwrap();
+    for (path, kind) in [
+        ("normal", ReloadKind::Normal),
+        ("reload", ReloadKind::Reload),
+        ("hard", ReloadKind::HardReload),
+    ] {
+        let res = client
+            .get(format!("http://{}/{}", server.addr(), path))
+            .header("cache-control", "private")
+            .reload(kind)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn fetch_mode_sets_fetch_metadata() {
+    use rquest::FetchMode;
+
+    let server = server::http(move |req| async move {
+        let headers = req.headers();
+        let fetch = [
+            "sec-fetch-site",
+            "sec-fetch-mode",
+            "sec-fetch-dest",
+            "sec-fetch-user",
+        ]
+        .map(|name| headers.get(name).map(|value| value.to_str().unwrap()));
+        let upgrade = headers.contains_key("upgrade-insecure-requests");
+        let accept = headers["accept"].to_str().unwrap();
+        match req.uri().path() {
+            "/navigate" => {
+                assert_eq!(
+                    fetch,
+                    [Some("none"), Some("navigate"), Some("document"), Some("?1")]
+                );
+                assert!(upgrade);
+                assert!(accept.starts_with("text/html"));
+            }
+            "/cors" => {
+                assert_eq!(
+                    fetch,
+                    [Some("same-origin"), Some("cors"), Some("empty"), None]
+                );
+                assert!(!upgrade);
+                assert_eq!(accept, "*/*");
+            }
+            "/no-cors" => {
+                assert_eq!(
+                    fetch,
+                    [Some("cross-site"), Some("no-cors"), Some("empty"), None]
+                );
+                assert_eq!(accept, "image/webp");
+            }
+            path => panic!("unexpected path {path}"),
+        }
+        http::Response::default()
+    });
+
+    let client = Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .build()
+        .unwrap();
+    let url = |path| format!("http://{}/{}", server.addr(), path);
+
+    for req in [
+        client.get(url("navigate")).fetch_mode(FetchMode::Navigate),
+        client.get(url("cors")).fetch_mode(FetchMode::Cors),
+        client
+            .get(url("no-cors"))
+            .header("accept", "image/webp")
+            .fetch_mode(FetchMode::NoCors)
+            .header("sec-fetch-site", "cross-site"),
+    ] {
+        let res = req.send().await.unwrap();
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn client_hints_follow_accept_ch() {
+    let server = server::http(move |req| async move {
+        let headers = req.headers();
+        let hint = |name| headers.get(name).map(|v| v.to_str().unwrap().to_owned());
+        let port = hint("host").unwrap().rsplit_once(':').unwrap().1.to_owned();
+        match req.uri().path() {
+            "/first" => {
+                assert_eq!(hint("sec-ch-ua-platform-version"), None);
+                http::Response::builder()
+                    .header(
+                        "accept-ch",
+                        "Sec-CH-UA-Full-Version-List, Sec-CH-UA-Platform-Version",
+                    )
+                    .header("accept-ch", "Sec-CH-UA-Arch, Viewport-Width")
+                    .body(Default::default())
+                    .unwrap()
+            }
+            "/second" => {
+                // only the major version is known, which the full versions would give away
+                assert_eq!(hint("sec-ch-ua-full-version-list"), None);
+                assert_eq!(hint("sec-ch-ua-full-version"), None);
+                // no profile knows the platform version, so it is not made up
+                assert_eq!(hint("sec-ch-ua-platform-version"), None);
+                assert_eq!(hint("sec-ch-ua-arch").as_deref(), Some("\"arm\""));
+                assert_eq!(hint("sec-ch-ua-model").as_deref(), Some("\"Pixel\""));
+                assert_eq!(hint("viewport-width"), None);
+                http::Response::builder()
+                    .status(302)
+                    .header("location", format!("http://localhost:{port}/other"))
+                    .body(Default::default())
+                    .unwrap()
+            }
+            "/other" => {
+                assert_eq!(hint("sec-ch-ua-full-version-list"), None);
+                assert_eq!(hint("sec-ch-ua-arch"), None);
+                // set by the request, so kept across the redirect
+                assert_eq!(hint("sec-ch-ua-model").as_deref(), Some("\"Pixel\""));
+                http::Response::default()
+            }
+            path => panic!("unexpected path {path}"),
+        }
+    });
+
+    let client = Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .client_hints(true)
+        .redirect(rquest::redirect::Policy::default())
+        .build()
+        .unwrap();
+    let url = |path| format!("http://{}/{}", server.addr(), path);
+
+    let res = client.get(url("first")).send().await.unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    let res = client
+        .get(url("second"))
+        .header("sec-ch-ua-model", "\"Pixel\"")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn remove_header_drops_default_headers() {
+    let server = server::http(move |req| async move {
+        let headers = req.headers();
+        match req.uri().path() {
+            "/default" => {
+                assert!(headers.contains_key("accept-language"));
+                assert!(headers.contains_key("sec-ch-ua"));
+            }
+            "/removed" => {
+                assert!(!headers.contains_key("accept-language"));
+                assert!(!headers.contains_key("sec-ch-ua"));
+                assert!(headers.contains_key("user-agent"));
+            }
+            "/restored" => assert_eq!(headers["accept-language"], "fr"),
+            path => panic!("unexpected path {path}"),
+        }
+        http::Response::default()
+    });
+
+    let client = Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .build()
+        .unwrap();
+    let url = |path| format!("http://{}/{}", server.addr(), path);
+
+    let res = client.get(url("default")).send().await.unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+
+    let res = client
+        .get(url("removed"))
+        .remove_header("accept-language")
+        .remove_header(http::HeaderName::from_static("sec-ch-ua"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+
+    let res = client
+        .get(url("restored"))
+        .remove_header("accept-language")
+        .header("accept-language", "fr")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn request_try_clone_sends_same_request() {
+    use http_body_util::BodyExt;
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.method(), "POST");
+        assert_eq!(req.uri(), "/mirror");
+        assert_eq!(req.headers()["x-retry"], "yes");
+
+        let full = req
+            .into_body()
+            .collect()
+            .await
+            .expect("must succeed")
+            .to_bytes();
+        assert_eq!(full, "fan out");
+
+        http::Response::default()
+    });
+
+    let client = Client::new();
+
+    let req = client
+        .post(format!("http://{}/mirror", server.addr()))
+        .header("x-retry", "yes")
+        .body("fan out")
+        .build()
+        .expect("request");
+
+    let clone = req.try_clone().expect("in-memory body is cloneable");
+    assert_eq!(clone.method(), req.method());
+    assert_eq!(clone.url(), req.url());
+    assert_eq!(clone.headers(), req.headers());
+    assert_eq!(clone.version(), req.version());
+
+    for req in [req, clone] {
+        let res = client.execute(req).await.expect("execute");
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+    }
+
+    let streaming = client
+        .post(format!("http://{}/mirror", server.addr()))
+        .body(rquest::Body::wrap(String::from("fan out")))
+        .build()
+        .expect("request");
+    assert!(streaming.try_clone().is_none());
+}
+
+#[tokio::test]
+async fn paginate_follows_next_links() {
+    use futures_util::StreamExt;
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        match req.uri().to_string().as_str() {
+            "/items?page=1" => http::Response::builder()
+                .header("link", r#"</items?page=2>; rel="next""#)
+                .body("one".into())
+                .unwrap(),
+            "/items?page=2" => http::Response::builder()
+                .header("link", r#"</items?page=1>; rel="prev""#)
+                .body("two".into())
+                .unwrap(),
+            uri => panic!("unexpected request: {uri}"),
+        }
+    });
+
+    let pages = Client::new()
+        .paginate(format!("http://{}/items?page=1", server.addr()))
+        .delay(std::time::Duration::from_millis(10))
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(pages.len(), 2);
+    let mut bodies = Vec::new();
+    for page in pages {
+        bodies.push(page.expect("page").text().await.expect("text"));
+    }
+    assert_eq!(bodies, ["one", "two"]);
+}
+
+#[tokio::test]
+async fn overridden_dns_resolution_with_gai() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let overridden_domain = "rust-lang.org";
+    let url = format!(
+        "http://{overridden_domain}:{}/domain_override",
+        server.addr().port()
+    );
+    let client = rquest::Client::builder()
+        .no_proxy()
+        .resolve(overridden_domain, server.addr())
+        .build()
+        .expect("client builder");
+    let req = client.get(&url);
+    let res = req.send().await.expect("request");
+
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    let text = res.text().await.expect("Failed to get text");
+    assert_eq!("Hello", text);
+}
+
+#[tokio::test]
+async fn overridden_dns_resolution_with_gai_multiple() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let overridden_domain = "rust-lang.org";
+    let url = format!(
+        "http://{overridden_domain}:{}/domain_override",
+        server.addr().port()
+    );
+    // the server runs on IPv4 localhost, so provide both IPv4 and IPv6 and let the happy eyeballs
+    // algorithm decide which address to use.
+    let client = rquest::Client::builder()
+        .no_proxy()
+        .resolve_to_addrs(
+            overridden_domain,
+            &[
+                std::net::SocketAddr::new(
+                    std::net::IpAddr::V6(std::net::Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)),
+                    server.addr().port(),
+                ),
+                server.addr(),
+            ],
+        )
+        .build()
+        .expect("client builder");
+    let req = client.get(&url);
+    let res = req.send().await.expect("request");
+
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    let text = res.text().await.expect("Failed to get text");
+    assert_eq!("Hello", text);
+}
+
+#[tokio::test]
+async fn dns_cache_reuses_and_clears_resolved_addrs() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct CountingResolver {
+        addr: std::net::SocketAddr,
+        calls: AtomicUsize,
+    }
+
+    impl rquest::dns::Resolve for CountingResolver {
+        fn resolve(&self, _name: rquest::dns::Name) -> rquest::dns::Resolving {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let addrs: rquest::dns::Addrs = Box::new(std::iter::once(self.addr));
+            Box::pin(futures_util::future::ready(Ok(addrs)))
+        }
+    }
+
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+    let resolver = Arc::new(CountingResolver {
+        addr: server.addr(),
+        calls: AtomicUsize::new(0),
+    });
+
+    let client = rquest::Client::builder()
+        .no_proxy()
+        .pool_max_idle_per_host(0)
+        .dns_resolver(resolver.clone())
+        .dns_cache(rquest::dns::DnsCacheConfig::default())
+        .build()
+        .expect("client builder");
+
+    let url = format!("http://cached.test:{}/", server.addr().port());
+    for _ in 0..2 {
+        let res = client.get(&url).send().await.expect("request");
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+    }
+    assert_eq!(resolver.calls.load(Ordering::SeqCst), 1);
+
+    client.clear_dns_cache();
+    let res = client.get(&url).send().await.expect("request");
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    assert_eq!(resolver.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn dyn_dns_resolver_with_overrides() {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    struct StaticResolver(HashMap<&'static str, std::net::SocketAddr>);
+
+    impl rquest::dns::Resolve for StaticResolver {
+        fn resolve(&self, name: rquest::dns::Name) -> rquest::dns::Resolving {
+            let addrs: rquest::dns::Addrs =
+                Box::new(self.0.get(name.as_str()).copied().into_iter());
+            Box::pin(futures_util::future::ready(Ok(addrs)))
+        }
+    }
+
+    let server = server::http(move |req| async move {
+        http::Response::new(req.headers()["host"].to_str().unwrap().to_owned().into())
+    });
+    let port = server.addr().port();
+
+    let resolver: Arc<dyn rquest::dns::Resolve> = Arc::new(StaticResolver(HashMap::from([(
+        "static.test",
+        server.addr(),
+    )])));
+    let client = rquest::Client::builder()
+        .no_proxy()
+        .dns_resolver(resolver)
+        .resolve("override.test", server.addr())
+        .build()
+        .unwrap();
+
+    for host in ["static.test", "override.test"] {
+        let res = client
+            .get(format!("http://{host}:{port}/"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), format!("{host}:{port}"));
+    }
+
+    // The resolver found no addresses for an unknown name.
+    let err = client
+        .get(format!("http://unknown.test:{port}/"))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connect());
+}
+
+#[tokio::test]
+async fn srv_lookup_connects_to_target_port() {
+    use std::sync::Arc;
+
+    struct SrvResolver {
+        ports: [u16; 2],
+    }
+
+    impl rquest::dns::Resolve for SrvResolver {
+        fn resolve(&self, name: rquest::dns::Name) -> rquest::dns::Resolving {
+            assert_eq!(name.as_str(), "target.test");
+            let addrs: rquest::dns::Addrs = Box::new(std::iter::once(([127, 0, 0, 1], 0).into()));
+            Box::pin(futures_util::future::ready(Ok(addrs)))
+        }
+
+        fn resolve_srv(&self, name: rquest::dns::Name) -> rquest::dns::ResolvingSrv {
+            assert_eq!(name.as_str(), "_http._tcp.svc.test");
+            let records = self
+                .ports
+                .iter()
+                .enumerate()
+                .map(|(priority, &port)| rquest::dns::SrvRecord {
+                    priority: priority as u16,
+                    weight: 1,
+                    port,
+                    target: "target.test.".to_owned(),
+                })
+                .collect();
+            Box::pin(futures_util::future::ready(Ok(records)))
+        }
+    }
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["host"], "svc.test");
+        http::Response::new("Hello".into())
+    });
+    // The preferred target refuses connections, so the next one is tried.
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let closed_port = closed.local_addr().unwrap().port();
+    drop(closed);
+
+    let client = rquest::Client::builder()
+        .no_proxy()
+        .dns_resolver(Arc::new(SrvResolver {
+            ports: [closed_port, server.addr().port()],
+        }))
+        .srv_lookup(true)
+        .build()
+        .expect("client builder");
+
+    let res = client
+        .get("http://svc.test/")
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    assert_eq!(res.text().await.unwrap(), "Hello");
+}
+
+#[cfg(feature = "hickory-dns")]
+#[tokio::test]
+async fn overridden_dns_resolution_with_hickory_dns() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let overridden_domain = "rust-lang.org";
+    let url = format!(
+        "http://{overridden_domain}:{}/domain_override",
+        server.addr().port()
+    );
+    let client = rquest::Client::builder()
+        .no_proxy()
+        .resolve(overridden_domain, server.addr())
+        .build()
+        .expect("client builder");
+    let req = client.get(&url);
+    let res = req.send().await.expect("request");
+
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    let text = res.text().await.expect("Failed to get text");
+    assert_eq!("Hello", text);
+}
+
+#[cfg(feature = "hickory-dns")]
+#[tokio::test]
+async fn overridden_dns_resolution_with_hickory_dns_multiple() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let overridden_domain = "rust-lang.org";
+    let url = format!(
+        "http://{overridden_domain}:{}/domain_override",
+        server.addr().port()
+    );
+    // the server runs on IPv4 localhost, so provide both IPv4 and IPv6 and let the happy eyeballs
+    // algorithm decide which address to use.
+    let client = rquest::Client::builder()
+        .no_proxy()
+        .resolve_to_addrs(
+            overridden_domain,
+            &[
+                std::net::SocketAddr::new(
+                    std::net::IpAddr::V6(std::net::Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)),
+                    server.addr().port(),
+                ),
+                server.addr(),
+            ],
+        )
+        .build()
+        .expect("client builder");
+    let req = client.get(&url);
+    let res = req.send().await.expect("request");
+
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    let text = res.text().await.expect("Failed to get text");
+    assert_eq!("Hello", text);
+}
+
+#[tokio::test]
+#[ignore = "Needs TLS support in the test server"]
+async fn http2_upgrade() {
+    let server = server::http(move |_| async move { http::Response::default() });
+
+    let url = format!("https://localhost:{}", server.addr().port());
+    let res = rquest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .expect("client builder")
+        .get(&url)
+        .send()
+        .await
+        .expect("request");
+
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    assert_eq!(res.version(), rquest::Version::HTTP_2);
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn add_json_default_content_type_if_not_set_manually() {
+    let mut map = HashMap::new();
+    map.insert("body", "json");
+    let content_type = http::HeaderValue::from_static("application/vnd.api+json");
     let req = Client::new()
         .post("https://google.com/")
         .header(CONTENT_TYPE, &content_type)
         .json(&map)
         .build()
-        .expect("request is not valid");
+        .expect("request is not valid");
+
+    assert_eq!(content_type, req.headers().get(CONTENT_TYPE).unwrap());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn update_json_content_type_if_set_manually() {
+    let mut map = HashMap::new();
+    map.insert("body", "json");
+    let req = Client::new()
+        .post("https://google.com/")
+        .json(&map)
+        .build()
+        .expect("request is not valid");
+
+    assert_eq!("application/json", req.headers().get(CONTENT_TYPE).unwrap());
+}
+
+#[tokio::test]
+async fn test_tls_info() {
+    let resp = rquest::Client::builder()
+        .tls_info(true)
+        .build()
+        .expect("client builder")
+        .get("https://google.com")
+        .send()
+        .await
+        .expect("response");
+    let tls_info = resp.extensions().get::<rquest::TlsInfo>();
+    assert!(tls_info.is_some());
+    let tls_info = tls_info.unwrap();
+    let peer_certificate = tls_info.peer_certificate();
+    assert!(peer_certificate.is_some());
+    let der = peer_certificate.unwrap();
+    assert_eq!(der[0], 0x30); // ASN.1 SEQUENCE
+
+    let resp = rquest::Client::builder()
+        .build()
+        .expect("client builder")
+        .get("https://google.com")
+        .send()
+        .await
+        .expect("response");
+    let tls_info = resp.extensions().get::<rquest::TlsInfo>();
+    assert!(tls_info.is_none());
+}
+
+/// A TLS acceptor serving the test server certificate.
+fn tls_acceptor() -> boring2::ssl::SslAcceptorBuilder {
+    use boring2::ssl::{SslAcceptor, SslFiletype, SslMethod};
+
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor
+        .set_certificate_file("tests/support/server.cert", SslFiletype::ASN1)
+        .unwrap();
+    acceptor
+        .set_private_key_file("tests/support/server.key", SslFiletype::ASN1)
+        .unwrap();
+    acceptor
+}
+
+#[tokio::test]
+async fn tls_info_signed_certificate_timestamps() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // A SignedCertificateTimestampList holding two opaque entries.
+    const SCT_LIST: &[u8] = &[0, 10, 0, 3, 0, 1, 2, 0, 3, 3, 4, 5];
+
+    let acceptor = tls_acceptor();
+    let ret = unsafe {
+        boring_sys2::SSL_CTX_set_signed_cert_timestamp_list(
+            acceptor.as_ptr(),
+            SCT_LIST.as_ptr(),
+            SCT_LIST.len(),
+        )
+    };
+    assert_eq!(ret, 1);
+    let acceptor = acceptor.build();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (io, _) = listener.accept().await.unwrap();
+            let Ok(mut tls) = tokio_boring2::accept(&acceptor, io).await else {
+                continue;
+            };
+            let mut buf = [0; 4096];
+            let _ = tls.read(&mut buf).await;
+            let _ = tls
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .await;
+        }
+    });
+
+    let get = |impersonate: Option<Impersonate>| async move {
+        let mut builder = rquest::Client::builder();
+        if let Some(impersonate) = impersonate {
+            builder = builder.impersonate(impersonate);
+        }
+        let res = builder
+            .tls_info(true)
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap()
+            .get(format!("https://localhost:{}", addr.port()))
+            .send()
+            .await
+            .unwrap();
+        res.extensions().get::<rquest::TlsInfo>().cloned().unwrap()
+    };
+
+    // Chrome asks for SCTs.
+    let tls_info = get(Some(Impersonate::Chrome131)).await;
+    assert_eq!(
+        tls_info.signed_certificate_timestamps(),
+        Some(&[vec![0, 1, 2], vec![3, 4, 5]][..])
+    );
+
+    // The default client doesn't, so the server sends none.
+    let tls_info = get(None).await;
+    assert!(tls_info.peer_certificate().is_some());
+    assert_eq!(tls_info.signed_certificate_timestamps(), None);
+}
+
+#[tokio::test]
+async fn tls_info_ocsp_response() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Any bytes are stapled as is, the client does not parse them.
+    const OCSP_RESPONSE: &[u8] = &[0x30, 0x03, 0x0a, 0x01, 0x00];
+
+    let acceptor = tls_acceptor();
+    let ret = unsafe {
+        boring_sys2::SSL_CTX_set_ocsp_response(
+            acceptor.as_ptr(),
+            OCSP_RESPONSE.as_ptr(),
+            OCSP_RESPONSE.len(),
+        )
+    };
+    assert_eq!(ret, 1);
+    let acceptor = acceptor.build();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (io, _) = listener.accept().await.unwrap();
+            let Ok(mut tls) = tokio_boring2::accept(&acceptor, io).await else {
+                continue;
+            };
+            let mut buf = [0; 4096];
+            let _ = tls.read(&mut buf).await;
+            let _ = tls
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .await;
+        }
+    });
+
+    let get = |impersonate: Option<Impersonate>| async move {
+        let mut builder = rquest::Client::builder();
+        if let Some(impersonate) = impersonate {
+            builder = builder.impersonate(impersonate);
+        }
+        let res = builder
+            .tls_info(true)
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap()
+            .get(format!("https://localhost:{}", addr.port()))
+            .send()
+            .await
+            .unwrap();
+        res.extensions().get::<rquest::TlsInfo>().cloned().unwrap()
+    };
+
+    // Browsers send `status_request`, so the server staples its response.
+    for impersonate in [
+        Impersonate::Chrome131,
+        Impersonate::Firefox133,
+        Impersonate::Safari18,
+        Impersonate::OkHttp5,
+    ] {
+        let tls_info = get(Some(impersonate)).await;
+        assert_eq!(tls_info.ocsp_response(), Some(OCSP_RESPONSE));
+    }
+
+    // The default client doesn't ask for it.
+    let tls_info = get(None).await;
+    assert_eq!(tls_info.ocsp_response(), None);
+}
+
+#[tokio::test]
+async fn on_connect_reports_connections() {
+    use boring2::ssl::{select_next_proto, AlpnError};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut acceptor = tls_acceptor();
+    acceptor.set_alpn_select_callback(|_, client| {
+        select_next_proto(b"\x08http/1.1", client).ok_or(AlpnError::NOACK)
+    });
+    let acceptor = acceptor.build();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let tls_addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (io, _) = listener.accept().await.unwrap();
+            let Ok(mut tls) = tokio_boring2::accept(&acceptor, io).await else {
+                continue;
+            };
+            tokio::spawn(async move {
+                let mut buf = [0; 4096];
+                while let Ok(1..) = tls.read(&mut buf).await {
+                    let _ = tls
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                        .await;
+                }
+            });
+        }
+    });
+    let server = server::http(move |_req| async { http::Response::default() });
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let client = {
+        let events = events.clone();
+        rquest::Client::builder()
+            .on_connect(move |info| events.lock().unwrap().push(info))
+            .danger_accept_invalid_certs(true)
+            .no_proxy()
+            .build()
+            .unwrap()
+    };
+
+    for url in [
+        format!("https://localhost:{}", tls_addr.port()),
+        format!("https://localhost:{}", tls_addr.port()),
+        format!("http://{}", server.addr()),
+    ] {
+        let res = client.get(url).send().await.unwrap();
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+    }
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 2, "one event per connection");
+    assert_eq!(events[0].remote_addr(), Some(tls_addr));
+    assert!(events[0].is_tls());
+    assert_eq!(events[0].alpn_protocol(), Some(&b"http/1.1"[..]));
+    assert!(events[0].handshake_duration() > std::time::Duration::ZERO);
+    assert_eq!(events[1].remote_addr(), Some(server.addr()));
+    assert!(!events[1].is_tls());
+    assert_eq!(events[1].alpn_protocol(), None);
+}
+
+/// Serves each connection opened by the client on one half of an in-memory pipe,
+/// echoing the request's path and `user-agent`.
+fn duplex_transport(
+    uri: http::Uri,
+) -> impl std::future::Future<Output = std::io::Result<tokio::io::DuplexStream>> {
+    let (client, server) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(async move {
+        let svc =
+            hyper::service::service_fn(|req: http::Request<hyper::body::Incoming>| async move {
+                let ua = req.headers()[http::header::USER_AGENT]
+                    .to_str()
+                    .unwrap()
+                    .to_owned();
+                Ok::<_, std::convert::Infallible>(http::Response::new(format!(
+                    "{} {}",
+                    req.uri().path(),
+                    ua
+                )))
+            });
+        let builder =
+            hyper_util::server::conn::auto::Builder::new(hyper_util::rt::TokioExecutor::new());
+        let _ = builder
+            .serve_connection(hyper_util::rt::TokioIo::new(server), svc)
+            .await;
+    });
+    assert_eq!(uri.host(), Some("pipe.test"));
+    std::future::ready(Ok(client))
+}
+
+#[tokio::test]
+async fn stream_buffer_watermarks() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const CHUNK: usize = 1024;
+    const CHUNKS: usize = 256;
+    const LOW: usize = 8 * CHUNK;
+    const HIGH: usize = 32 * CHUNK;
+
+    let sent = Arc::new(AtomicUsize::new(0));
+    let server_sent = sent.clone();
+    let client = Client::builder()
+        .stream_buffer_watermarks(LOW, HIGH)
+        .connect_over(move |_| {
+            let sent = server_sent.clone();
+            // A small pipe, so the server only gets ahead as far as the
+            // client reads.
+            let (client, mut server) = tokio::io::duplex(CHUNK);
+            tokio::spawn(async move {
+                let mut req = [0; CHUNK];
+                let _ = server.read(&mut req).await?;
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n",
+                    CHUNK * CHUNKS
+                );
+                server.write_all(head.as_bytes()).await?;
+                for _ in 0..CHUNKS {
+                    server.write_all(&[b'x'; CHUNK]).await?;
+                    sent.fetch_add(CHUNK, Ordering::SeqCst);
+                }
+                std::io::Result::Ok(())
+            });
+            std::future::ready(Ok(client))
+        })
+        .build()
+        .unwrap();
+
+    let mut res = client.get("http://pipe.test/").send().await.unwrap();
+    assert_eq!(res.content_length(), Some((CHUNK * CHUNKS) as u64));
+    let mut received = 0;
+    let mut max_ahead = 0;
+    while let Some(chunk) = res.chunk().await.unwrap() {
+        received += chunk.len();
+        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        let ahead = sent.load(Ordering::SeqCst) - received;
+        // Whatever sits in the pipe and in hyper comes on top of the buffer.
+        assert!(ahead <= HIGH + 4 * CHUNK, "{ahead} bytes buffered");
+        max_ahead = max_ahead.max(ahead);
+    }
+    assert_eq!(received, CHUNK * CHUNKS);
+    assert!(max_ahead > LOW, "body was not read ahead");
+
+    let err = Client::builder()
+        .stream_buffer_watermarks(HIGH, LOW)
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn stream_buffer_stops_when_dropped() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::mpsc;
+
+    let (closed_tx, mut closed_rx) = mpsc::unbounded_channel();
+    let client = Client::builder()
+        .stream_buffer_watermarks(1024, 4096)
+        .connect_over(move |_| {
+            let closed_tx = closed_tx.clone();
+            let (client, mut server) = tokio::io::duplex(1024);
+            tokio::spawn(async move {
+                let mut req = [0; 1024];
+                let _ = server.read(&mut req).await?;
+                server
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 1000000\r\n\r\nxxxx")
+                    .await?;
+                // the rest of the body never comes, wait for the client to hang up
+                while server.read(&mut req).await? > 0 {}
+                let _ = closed_tx.send(());
+                std::io::Result::Ok(())
+            });
+            std::future::ready(Ok(client))
+        })
+        .build()
+        .unwrap();
+
+    let res = client.get("http://pipe.test/").send().await.unwrap();
+    drop(res);
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), closed_rx.recv())
+        .await
+        .expect("the connection is still read after the body was dropped");
+}
+
+#[tokio::test]
+async fn connect_over_duplex() {
+    let client = Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .connect_over(duplex_transport)
+        .build()
+        .unwrap();
+
+    let res = client.get("http://pipe.test/hello").send().await.unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    assert_eq!(res.remote_addr(), None);
+    let body = res.text().await.unwrap();
+    assert!(body.starts_with("/hello Mozilla/5.0"), "{body}");
+    assert!(body.contains("Chrome/131"), "{body}");
+}
+
+/// Like [`duplex_transport`], with the server half behind TLS.
+fn tls_pipe(
+    acceptor: boring2::ssl::SslAcceptor,
+) -> impl Fn(http::Uri) -> std::future::Ready<std::io::Result<tokio::io::DuplexStream>> {
+    let acceptor = std::sync::Arc::new(acceptor);
+    move |_| {
+        let acceptor = acceptor.clone();
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            let Ok(tls) = tokio_boring2::accept(&acceptor, server).await else {
+                return;
+            };
+            let svc = hyper::service::service_fn(
+                |req: http::Request<hyper::body::Incoming>| async move {
+                    Ok::<_, std::convert::Infallible>(http::Response::new(
+                        req.uri().path().to_owned(),
+                    ))
+                },
+            );
+            let _ = hyper::server::conn::http1::Builder::new()
+                .serve_connection(hyper_util::rt::TokioIo::new(tls), svc)
+                .await;
+        });
+        std::future::ready(Ok(client))
+    }
+}
+
+#[tokio::test]
+async fn connect_over_duplex_with_tls() {
+    let acceptor = tls_acceptor().build();
+
+    let client = Client::builder()
+        .tls_info(true)
+        .danger_accept_invalid_certs(true)
+        .connect_over(tls_pipe(acceptor))
+        .build()
+        .unwrap();
+
+    let res = client.get("https://localhost/").send().await.unwrap();
+    let tls_info = res.extensions().get::<rquest::TlsInfo>().unwrap();
+    assert!(tls_info.peer_certificate().is_some());
+    assert_eq!(res.text().await.unwrap(), "/");
+}
+
+/// Generates a certificate for `cn`, issued by `issuer` or else self-signed as a CA.
+fn test_certificate(
+    cn: &str,
+    issuer: Option<&(
+        boring2::x509::X509,
+        boring2::pkey::PKey<boring2::pkey::Private>,
+    )>,
+) -> (
+    boring2::x509::X509,
+    boring2::pkey::PKey<boring2::pkey::Private>,
+) {
+    use boring2::{
+        asn1::Asn1Time,
+        ec::{EcGroup, EcKey},
+        hash::MessageDigest,
+        nid::Nid,
+        pkey::PKey,
+        x509::{
+            extension::{BasicConstraints, SubjectAlternativeName},
+            X509Builder, X509NameBuilder,
+        },
+    };
+
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", cn).unwrap();
+    let name = name.build();
+
+    let mut cert = X509Builder::new().unwrap();
+    cert.set_version(2).unwrap();
+    cert.set_subject_name(&name).unwrap();
+    cert.set_pubkey(&key).unwrap();
+    cert.set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    cert.set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    let signer = match issuer {
+        Some((issuer, issuer_key)) => {
+            cert.set_issuer_name(issuer.subject_name()).unwrap();
+            let san = SubjectAlternativeName::new()
+                .dns(cn)
+                .build(&cert.x509v3_context(Some(issuer), None))
+                .unwrap();
+            cert.append_extension(san).unwrap();
+            issuer_key
+        }
+        None => {
+            cert.set_issuer_name(&name).unwrap();
+            let ca = BasicConstraints::new().critical().ca().build().unwrap();
+            cert.append_extension(ca).unwrap();
+            &key
+        }
+    };
+    cert.sign(signer, MessageDigest::sha256()).unwrap();
+    (cert.build(), key)
+}
+
+#[tokio::test]
+async fn sni_override() {
+    use boring2::ssl::{NameType, SslAcceptor, SslMethod};
+    use std::sync::{Arc, Mutex};
+
+    let ca = test_certificate("rquest test root", None);
+    let (leaf, key) = test_certificate("front.example", Some(&ca));
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor.set_certificate(&leaf).unwrap();
+    acceptor.set_private_key(&key).unwrap();
+    acceptor.set_servername_callback({
+        let names = names.clone();
+        move |ssl, _| {
+            let name = ssl.servername(NameType::HOST_NAME).map(str::to_owned);
+            names.lock().unwrap().push(name);
+            Ok(())
+        }
+    });
+    let acceptor = acceptor.build();
+    let root = rquest::Certificate::from_der(&ca.0.to_der().unwrap()).unwrap();
+    let builder = || {
+        Client::builder()
+            .add_root_certificate(root.clone())
+            .connect_over(tls_pipe(acceptor.clone()))
+    };
+    let last_name = || names.lock().unwrap().pop().unwrap();
+
+    let client = builder().build().unwrap();
+    let res = client
+        .get("https://real.example/")
+        .sni(Some("front.example"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "/");
+    assert_eq!(last_name().as_deref(), Some("front.example"));
+
+    // the certificate is verified against the server name, not the host
+    let err = client
+        .get("https://real.example/")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connect());
+    assert_eq!(last_name().as_deref(), Some("real.example"));
+    let err = client
+        .get("https://front.example/")
+        .sni(Some("other.example"))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connect());
+    assert_eq!(last_name().as_deref(), Some("other.example"));
+
+    // without SNI, the certificate is still verified against the host
+    let res = client
+        .get("https://front.example/")
+        .sni(None)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    assert_eq!(last_name(), None);
+
+    let client = builder().sni(Some("front.example")).build().unwrap();
+    let res = client.get("https://real.example/").send().await.unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    assert_eq!(last_name().as_deref(), Some("front.example"));
+}
+
+#[tokio::test]
+async fn sni_override_through_https_proxy() {
+    use boring2::ssl::{NameType, SslAcceptor, SslMethod};
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+
+    async fn read_head<S: AsyncRead + Unpin>(stream: &mut S) -> Vec<u8> {
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        head
+    }
+
+    let ca = test_certificate("rquest test root", None);
+    let acceptor = |cn: &str| {
+        let (leaf, key) = test_certificate(cn, Some(&ca));
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        acceptor.set_certificate(&leaf).unwrap();
+        acceptor.set_private_key(&key).unwrap();
+        acceptor.build()
+    };
+    let proxy_acceptor = acceptor("localhost");
+    let origin_acceptor = acceptor("front.example");
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = tokio::spawn(async move {
+        let (tcp, _) = listener.accept().await.unwrap();
+        let Ok(mut proxy) = tokio_boring2::accept(&proxy_acceptor, tcp).await else {
+            panic!("proxy handshake failed");
+        };
+        let proxy_name = proxy
+            .ssl()
+            .servername(NameType::HOST_NAME)
+            .map(str::to_owned);
+
+        let head = read_head(&mut proxy).await;
+        assert!(
+            head.starts_with(b"CONNECT real.example:443 HTTP/1.1\r\n"),
+            "{}",
+            String::from_utf8_lossy(&head)
+        );
+        proxy.write_all(b"HTTP/1.1 200 OK\r\n\r\n").await.unwrap();
+
+        let Ok(mut origin) = tokio_boring2::accept(&origin_acceptor, proxy).await else {
+            panic!("origin handshake failed");
+        };
+        let origin_name = origin
+            .ssl()
+            .servername(NameType::HOST_NAME)
+            .map(str::to_owned);
+
+        read_head(&mut origin).await;
+        origin
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        (proxy_name, origin_name)
+    });
+
+    let root = rquest::Certificate::from_der(&ca.0.to_der().unwrap()).unwrap();
+    let client = Client::builder()
+        .add_root_certificate(root)
+        .proxy(rquest::Proxy::all(format!("https://localhost:{port}")).unwrap())
+        .build()
+        .unwrap();
+    let res = client
+        .get("https://real.example/")
+        .sni(Some("front.example"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+
+    // the proxy is verified against its own name, the origin against the override
+    let (proxy_name, origin_name) = server.await.unwrap();
+    assert_eq!(proxy_name.as_deref(), Some("localhost"));
+    assert_eq!(origin_name.as_deref(), Some("front.example"));
+}
+
+#[tokio::test]
+async fn connect_to_endpoint() {
+    use boring2::ssl::{NameType, SslAcceptor, SslMethod};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let server = server::http(move |req| async move {
+        let host = req.headers()["host"].to_str().unwrap().to_owned();
+        http::Response::new(host.into())
+    });
+    let port = server.addr().port();
+    let client = Client::new();
+
+    let res = client
+        .get("http://edge.example/")
+        .connect_to(&format!("127.0.0.1:{port}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "edge.example");
+
+    // without a port, the port of the URL is used
+    let res = client
+        .get(format!("http://edge.example:{port}/"))
+        .connect_to("127.0.0.1")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), format!("edge.example:{port}"));
+
+    // the host of the URL is sent in SNI and verified against
+    let ca = test_certificate("rquest test root", None);
+    let (leaf, key) = test_certificate("front.example", Some(&ca));
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor.set_certificate(&leaf).unwrap();
+    acceptor.set_private_key(&key).unwrap();
+    acceptor.set_servername_callback({
+        let names = names.clone();
+        move |ssl, _| {
+            let name = ssl.servername(NameType::HOST_NAME).map(str::to_owned);
+            names.lock().unwrap().push(name);
+            Ok(())
+        }
+    });
+    let acceptor = acceptor.build();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            let (io, _) = listener.accept().await.unwrap();
+            let Ok(mut tls) = tokio_boring2::accept(&acceptor, io).await else {
+                continue;
+            };
+            let mut buf = [0; 4096];
+            let _ = tls.read(&mut buf).await;
+            let _ = tls
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .await;
+        }
+    });
+
+    let root = rquest::Certificate::from_der(&ca.0.to_der().unwrap()).unwrap();
+    let client = Client::builder()
+        .add_root_certificate(root)
+        .build()
+        .unwrap();
+    let res = client
+        .get(format!("https://front.example:{port}/"))
+        .connect_to("127.0.0.1")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    assert_eq!(
+        names.lock().unwrap().pop().unwrap().as_deref(),
+        Some("front.example")
+    );
+
+    let err = client
+        .get(format!("https://other.example:{port}/"))
+        .connect_to("127.0.0.1")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connect());
+
+    let err = client
+        .get("http://edge.example/")
+        .connect_to("not an endpoint")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn add_root_certificate() {
+    use boring2::ssl::{SslAcceptor, SslMethod};
+
+    let ca = test_certificate("rquest test root", None);
+    let (leaf, key) = test_certificate("localhost", Some(&ca));
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor.set_certificate(&leaf).unwrap();
+    acceptor.set_private_key(&key).unwrap();
+    let acceptor = acceptor.build();
+
+    let get = |builder: rquest::ClientBuilder| {
+        let client = builder
+            .connect_over(tls_pipe(acceptor.clone()))
+            .build()
+            .unwrap();
+        async move { client.get("https://localhost/").send().await }
+    };
+
+    let err = get(Client::builder()).await.unwrap_err();
+    assert!(err.is_connect());
+
+    let root = rquest::Certificate::from_pem(&ca.0.to_pem().unwrap()).unwrap();
+    let res = get(Client::builder().add_root_certificate(root))
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "/");
+
+    let root = rquest::Certificate::from_der(&ca.0.to_der().unwrap()).unwrap();
+    let res = get(Client::builder()
+        .tls_built_in_root_certs(false)
+        .add_root_certificate(root))
+    .await
+    .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+
+    let err = get(Client::builder().tls_built_in_root_certs(false))
+        .await
+        .unwrap_err();
+    assert!(err.is_connect());
+}
+
+#[test]
+fn certificate_from_pem_bundle() {
+    let (a, _) = test_certificate("a", None);
+    let (b, _) = test_certificate("b", None);
+    let pem = [a.to_pem().unwrap(), b.to_pem().unwrap()].concat();
+    assert_eq!(rquest::Certificate::from_pem_bundle(&pem).unwrap().len(), 2);
+
+    assert!(rquest::Certificate::from_pem(b"not a certificate")
+        .unwrap_err()
+        .is_builder());
+}
+
+#[tokio::test]
+async fn client_identity() {
+    use boring2::ssl::{SslVerifyMode, SslVersion};
+
+    let ca = test_certificate("rquest test client root", None);
+    let (cert, key) = test_certificate("client", Some(&ca));
+
+    let mut acceptor = tls_acceptor();
+    acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    acceptor.add_client_ca(&ca.0).unwrap();
+    acceptor.cert_store_mut().add_cert(ca.0.clone()).unwrap();
+    // The client only learns of a rejected certificate before the
+    // handshake completes with TLS 1.2.
+    acceptor
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let acceptor = acceptor.build();
+
+    let get = |builder: rquest::ClientBuilder| {
+        let client = builder
+            .impersonate(Impersonate::Chrome131)
+            .danger_accept_invalid_certs(true)
+            .connect_over(tls_pipe(acceptor.clone()))
+            .build()
+            .unwrap();
+        async move { client.get("https://localhost/").send().await }
+    };
+
+    let err = get(Client::builder()).await.unwrap_err();
+    assert!(err.is_connect());
+
+    let pem = [
+        key.private_key_to_pem_pkcs8().unwrap(),
+        cert.to_pem().unwrap(),
+    ]
+    .concat();
+    let identity = rquest::Identity::from_pem(&pem).unwrap();
+    let res = get(Client::builder().identity(identity)).await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "/");
+}
+
+#[tokio::test]
+async fn client_identity_kept_across_impersonate() {
+    use boring2::ssl::{SslAcceptor, SslMethod, SslVerifyMode, SslVersion};
+
+    let ca = test_certificate("rquest test root", None);
+    let (server_cert, server_key) = test_certificate("localhost", Some(&ca));
+    let (cert, key) = test_certificate("client", Some(&ca));
+
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor.set_certificate(&server_cert).unwrap();
+    acceptor.set_private_key(&server_key).unwrap();
+    acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    acceptor.add_client_ca(&ca.0).unwrap();
+    acceptor.cert_store_mut().add_cert(ca.0.clone()).unwrap();
+    acceptor
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+
+    let pem = [
+        key.private_key_to_pem_pkcs8().unwrap(),
+        cert.to_pem().unwrap(),
+    ]
+    .concat();
+    let mut client = Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .add_root_certificate(rquest::Certificate::from_der(&ca.0.to_der().unwrap()).unwrap())
+        .identity(rquest::Identity::from_pem(&pem).unwrap())
+        .connect_over(tls_pipe(acceptor.build()))
+        .build()
+        .unwrap();
+
+    // the new profile still trusts the root and presents the identity
+    client.as_mut().impersonate(Impersonate::Firefox128);
+    let res = client.get("https://localhost/").send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "/");
+}
+
+#[test]
+fn identity_key_mismatch() {
+    let (cert, _) = test_certificate("client", None);
+    let (_, other_key) = test_certificate("other", None);
+
+    let err = rquest::Identity::from_pkcs8_pem(
+        &cert.to_pem().unwrap(),
+        &other_key.private_key_to_pem_pkcs8().unwrap(),
+    )
+    .unwrap_err();
+    assert!(err.is_builder());
+    assert!(err.to_string().contains("does not match"), "{err}");
+}
+
+#[tokio::test]
+async fn tls_early_data() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let early_data_acceptor = || {
+        let mut acceptor = tls_acceptor();
+        unsafe { boring_sys2::SSL_CTX_set_early_data_enabled(acceptor.as_ptr(), 1) };
+        // Early data is only sent for a protocol negotiated with ALPN.
+        acceptor.set_alpn_select_callback(|_, client| {
+            boring2::ssl::select_next_proto(b"\x08http/1.1", client)
+                .ok_or(boring2::ssl::AlpnError::NOACK)
+        });
+        acceptor.build()
+    };
+    // The first server issues the session tickets, the second cannot decrypt
+    // them and so rejects the early data.
+    let first = tls_pipe(early_data_acceptor());
+    let second = tls_pipe(early_data_acceptor());
+    let connections = AtomicUsize::new(0);
+
+    let client = Client::builder()
+        .tls_early_data(true)
+        .tls_info(true)
+        .danger_accept_invalid_certs(true)
+        .pool_max_idle_per_host(0)
+        .connect_over(
+            move |uri| match connections.fetch_add(1, Ordering::SeqCst) {
+                0..=3 => first(uri),
+                _ => second(uri),
+            },
+        )
+        .build()
+        .unwrap();
+
+    let send = |req: rquest::RequestBuilder| async move {
+        let res = req.send().await.unwrap();
+        let accepted = res
+            .extensions()
+            .get::<rquest::TlsInfo>()
+            .unwrap()
+            .early_data_accepted();
+        (accepted, res.text().await.unwrap())
+    };
+
+    // A full handshake, which caches the session.
+    assert_eq!(
+        send(client.get("https://localhost/full")).await,
+        (false, "/full".to_owned())
+    );
+    // Resumed, with the request sent as early data.
+    assert_eq!(
+        send(client.get("https://localhost/early")).await,
+        (true, "/early".to_owned())
+    );
+    // Resumed, but a POST waits for the handshake to complete.
+    assert_eq!(
+        send(client.post("https://localhost/post").body("body")).await,
+        (false, "/post".to_owned())
+    );
+    // A PUT is idempotent, but not safe to replay either.
+    assert_eq!(
+        send(client.put("https://localhost/put").body("body")).await,
+        (false, "/put".to_owned())
+    );
+    // Rejected, and sent again after the handshake.
+    assert_eq!(
+        send(client.get("https://localhost/rejected")).await,
+        (false, "/rejected".to_owned())
+    );
+}
+
+#[tokio::test]
+async fn tls_info_peer_certificate_chain() {
+    // A stand-in intermediate, presented after the leaf.
+    let (intermediate, _) = test_certificate("rquest test intermediate", None);
+    let intermediate_der = intermediate.to_der().unwrap();
+
+    let mut acceptor = tls_acceptor();
+    acceptor.add_extra_chain_cert(intermediate).unwrap();
+    let acceptor = acceptor.build();
+
+    let client = Client::builder()
+        .tls_info(true)
+        .danger_accept_invalid_certs(true)
+        .connect_over(tls_pipe(acceptor))
+        .build()
+        .unwrap();
+
+    let res = client.get("https://localhost/").send().await.unwrap();
+    let tls_info = res.extensions().get::<rquest::TlsInfo>().unwrap();
+    let chain = tls_info.peer_certificate_chain().unwrap();
+    assert_eq!(chain.len(), 2);
+    assert_eq!(Some(&chain[0][..]), tls_info.peer_certificate());
+    assert_eq!(chain[1], intermediate_der);
+}
+
+#[tokio::test]
+async fn pin_public_key() {
+    let cert = boring2::x509::X509::from_der(include_bytes!("support/server.cert")).unwrap();
+    let spki = cert.public_key().unwrap().public_key_to_der().unwrap();
+    let pin = rquest::Sha256Pin::from_spki_der(&spki).unwrap();
+    let other = rquest::Sha256Pin::new([7; 32]);
+
+    let get = |pins: Vec<rquest::Sha256Pin>| async move {
+        Client::builder()
+            .danger_accept_invalid_certs(true)
+            .pin_public_key("LOCALHOST", pins)
+            .connect_over(tls_pipe(tls_acceptor().build()))
+            .build()
+            .unwrap()
+            .get("https://localhost/")
+            .send()
+            .await
+    };
+
+    let res = get(vec![other, pin]).await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "/");
+
+    // Pins are enforced even though certificate verification is disabled.
+    let err = get(vec![other]).await.unwrap_err();
+    assert!(err.is_connect());
+    assert!(err.is_pin_mismatch());
+    assert!(!err.is_timeout());
+}
+
+#[test]
+fn sha256_pin_from_base64() {
+    let pin =
+        rquest::Sha256Pin::from_base64("BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=").unwrap();
+    assert_eq!(pin, rquest::Sha256Pin::new([7; 32]));
+
+    assert!(rquest::Sha256Pin::from_base64("BwcH")
+        .unwrap_err()
+        .is_builder());
+    assert!(rquest::Sha256Pin::from_base64("not base64!")
+        .unwrap_err()
+        .is_builder());
+}
+
+/// Accepts one TLS connection and returns its ClientHello record.
+async fn read_client_hello(listener: tokio::net::TcpListener) -> Vec<u8> {
+    use tokio::io::AsyncReadExt;
+
+    let (mut io, _) = listener.accept().await.unwrap();
+    let mut hello = vec![0; 5];
+    io.read_exact(&mut hello).await.unwrap();
+    let len = u16::from_be_bytes([hello[3], hello[4]]) as usize;
+    hello.resize(5 + len, 0);
+    io.read_exact(&mut hello[5..]).await.unwrap();
+    hello
+}
+
+/// Splits a ClientHello record into its cipher suites and its extensions.
+fn parse_client_hello(hello: &[u8]) -> (Vec<u16>, Vec<(u16, &[u8])>) {
+    let u16_at = |pos: usize| u16::from_be_bytes([hello[pos], hello[pos + 1]]);
+    // record header, handshake header, version and random
+    let mut pos = 5 + 4 + 2 + 32;
+    pos += 1 + hello[pos] as usize; // session id
+    let ciphers = (0..u16_at(pos) as usize / 2)
+        .map(|i| u16_at(pos + 2 + 2 * i))
+        .collect();
+    pos += 2 + u16_at(pos) as usize;
+    pos += 1 + hello[pos] as usize; // compression methods
+    let end = pos + 2 + u16_at(pos) as usize;
+    pos += 2;
+
+    let mut extensions = Vec::new();
+    while pos < end {
+        let (ty, len) = (u16_at(pos), u16_at(pos + 2) as usize);
+        extensions.push((ty, &hello[pos + 4..pos + 4 + len]));
+        pos += 4 + len;
+    }
+    (ciphers, extensions)
+}
+
+fn is_grease(value: u16) -> bool {
+    value & 0x0f0f == 0x0a0a
+}
+
+/// Accepts one TLS connection and returns the extension types of its ClientHello,
+/// leaving out GREASE and padding.
+async fn client_hello_extensions(listener: tokio::net::TcpListener) -> Vec<u16> {
+    let hello = read_client_hello(listener).await;
+    parse_client_hello(&hello)
+        .1
+        .into_iter()
+        .map(|(ty, _)| ty)
+        .filter(|&ty| !is_grease(ty) && ty != 21)
+        .collect()
+}
+
+/// Accepts one TLS connection and returns the supported groups of its ClientHello,
+/// and the groups of its key shares, leaving out GREASE.
+async fn client_hello_groups(listener: tokio::net::TcpListener) -> (Vec<u16>, Vec<u16>) {
+    let hello = read_client_hello(listener).await;
+    let (_, extensions) = parse_client_hello(&hello);
+    let extension = |ty: u16| extensions.iter().find(|e| e.0 == ty).unwrap().1;
+    let u16_at = |data: &[u8], pos: usize| u16::from_be_bytes([data[pos], data[pos + 1]]);
+
+    let groups = extension(10);
+    let groups = (2..groups.len())
+        .step_by(2)
+        .map(|pos| u16_at(groups, pos))
+        .filter(|&group| !is_grease(group))
+        .collect();
+
+    let key_share = extension(51);
+    let mut key_shares = Vec::new();
+    let mut pos = 2;
+    while pos < key_share.len() {
+        let group = u16_at(key_share, pos);
+        if !is_grease(group) {
+            key_shares.push(group);
+        }
+        pos += 4 + u16_at(key_share, pos + 2) as usize;
+    }
+    (groups, key_shares)
+}
+
+#[tokio::test]
+async fn tls_extension_order() {
+    use rquest::ExtensionType;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(client_hello_extensions(listener));
+
+    let settings = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .tls_extension_order(vec![
+            ExtensionType::KEY_SHARE,
+            ExtensionType::SUPPORTED_VERSIONS,
+            ExtensionType::SERVER_NAME,
+            ExtensionType::SIGNATURE_ALGORITHMS,
+            ExtensionType::SUPPORTED_GROUPS,
+            ExtensionType::APPLICATION_LAYER_PROTOCOL_NEGOTIATION,
+        ])
+        .build();
+    let client = rquest::Client::builder()
+        .impersonate(settings)
+        .resolve("tls.test", addr)
+        .build()
+        .unwrap();
+
+    let _ = client
+        .get(format!("https://tls.test:{}", addr.port()))
+        .send()
+        .await;
+
+    // key_share, supported_versions, server_name, signature_algorithms,
+    // supported_groups, application_layer_protocol_negotiation
+    assert_eq!(server.await.unwrap(), [51, 43, 0, 13, 10, 16]);
+}
+
+#[tokio::test]
+async fn tls_grease_randomized_per_client_hello() {
+    let client = rquest::Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .build()
+        .unwrap();
 
-    assert_eq!(content_type, req.headers().get(CONTENT_TYPE).unwrap());
+    let mut seen = std::collections::HashSet::new();
+    for _ in 0..8 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let hello = read_client_hello(listener).await;
+            let (ciphers, extensions) = parse_client_hello(&hello);
+            let extension = |ty: u16| extensions.iter().find(|e| e.0 == ty).unwrap().1;
+            let u16_at = |data: &[u8], pos: usize| u16::from_be_bytes([data[pos], data[pos + 1]]);
+
+            let cipher = ciphers.into_iter().find(|&c| is_grease(c)).unwrap();
+            let group = u16_at(extension(10), 2);
+            let key_share = u16_at(extension(51), 2);
+            let version = u16_at(extension(43), 1);
+            let grease_extensions = extensions
+                .iter()
+                .map(|e| e.0)
+                .filter(|&ty| is_grease(ty))
+                .collect::<Vec<_>>();
+            (cipher, group, key_share, version, grease_extensions)
+        });
+
+        let _ = client
+            .get(format!("https://tls.test:{port}"))
+            .connect_to("127.0.0.1")
+            .send()
+            .await;
+
+        let (cipher, group, key_share, version, grease_extensions) = server.await.unwrap();
+        assert!(is_grease(group) && is_grease(version));
+        // the key share of the GREASE group matches the supported groups
+        assert_eq!(key_share, group);
+        assert_eq!(grease_extensions.len(), 2);
+        assert_ne!(grease_extensions[0], grease_extensions[1]);
+        seen.insert(cipher);
+    }
+
+    // 8 draws from 16 values are all the same with a probability of 16^-7
+    assert!(seen.len() > 1, "GREASE values are fixed: {seen:?}");
+}
+
+#[tokio::test]
+async fn tls_supported_groups_and_key_shares() {
+    use rquest::SslCurve;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(client_hello_groups(listener));
+
+    let settings = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .supported_groups(vec![
+            SslCurve::X25519,
+            SslCurve::SECP256R1,
+            SslCurve::SECP384R1,
+            SslCurve::X25519_MLKEM768,
+        ])
+        .key_shares(vec![SslCurve::X25519_MLKEM768, SslCurve::X25519])
+        .build();
+    let client = rquest::Client::builder()
+        .impersonate(settings)
+        .resolve("tls.test", addr)
+        .build()
+        .unwrap();
+
+    let _ = client
+        .get(format!("https://tls.test:{}", addr.port()))
+        .send()
+        .await;
+
+    // X25519MLKEM768, x25519, secp256r1, secp384r1
+    let (groups, key_shares) = server.await.unwrap();
+    assert_eq!(groups, [0x11ec, 0x001d, 0x0017, 0x0018]);
+    assert_eq!(key_shares, [0x11ec, 0x001d]);
+}
+
+#[tokio::test]
+async fn tls_signature_algorithms() {
+    use rquest::SslSignatureAlgorithm;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let hello = read_client_hello(listener).await;
+        let (_, extensions) = parse_client_hello(&hello);
+        let sigalgs = extensions.iter().find(|e| e.0 == 13).unwrap().1;
+        (2..sigalgs.len())
+            .step_by(2)
+            .map(|pos| u16::from_be_bytes([sigalgs[pos], sigalgs[pos + 1]]))
+            .collect::<Vec<_>>()
+    });
+
+    let settings = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .signature_algorithms(vec![
+            SslSignatureAlgorithm::RSA_PSS_RSAE_SHA256,
+            SslSignatureAlgorithm::ECDSA_SECP256R1_SHA256,
+            SslSignatureAlgorithm::RSA_PKCS1_SHA256,
+        ])
+        .build();
+    let client = rquest::Client::builder()
+        .impersonate(settings)
+        .resolve("tls.test", addr)
+        .build()
+        .unwrap();
+
+    let _ = client
+        .get(format!("https://tls.test:{}", addr.port()))
+        .send()
+        .await;
+
+    // rsa_pss_rsae_sha256, ecdsa_secp256r1_sha256, rsa_pkcs1_sha256
+    assert_eq!(server.await.unwrap(), [0x0804, 0x0403, 0x0401]);
+
+    for algorithms in [
+        vec![],
+        vec![SslSignatureAlgorithm::RSA_PKCS1_SHA256],
+        vec![
+            SslSignatureAlgorithm::ED25519,
+            SslSignatureAlgorithm::ED25519,
+        ],
+        vec![SslSignatureAlgorithm::from(0x1234)],
+    ] {
+        let settings = Impersonate::builder()
+            .impersonate(Impersonate::Chrome131)
+            .signature_algorithms(algorithms)
+            .build();
+        let err = rquest::Client::builder()
+            .impersonate(settings)
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+    }
+}
+
+#[tokio::test]
+async fn tls_connector_hook() {
+    use rquest::SslCurve;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(client_hello_groups(listener));
+
+    let client = rquest::Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .tls_connector_hook(|builder| {
+            builder.set_curves(&[SslCurve::SECP384R1]).unwrap();
+        })
+        .resolve("tls.test", addr)
+        .build()
+        .unwrap();
+
+    let _ = client
+        .get(format!("https://tls.test:{}", addr.port()))
+        .send()
+        .await;
+
+    // the hook runs after the profile, so its curves replace Chrome's
+    let (groups, _) = server.await.unwrap();
+    assert_eq!(groups, [0x0018]);
+}
+
+#[tokio::test]
+async fn tls_cert_compression() {
+    use rquest::CertCompressionAlgorithm;
+
+    async fn offered(settings: rquest::ImpersonateSettings) -> Option<Vec<u8>> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let hello = read_client_hello(listener).await;
+            let (_, extensions) = parse_client_hello(&hello);
+            extensions.iter().find(|e| e.0 == 27).map(|e| e.1.to_vec())
+        });
+
+        let client = rquest::Client::builder()
+            .impersonate(settings)
+            .resolve("tls.test", addr)
+            .build()
+            .unwrap();
+        let _ = client
+            .get(format!("https://tls.test:{}", addr.port()))
+            .send()
+            .await;
+        server.await.unwrap()
+    }
+
+    // brotli
+    let settings = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .build();
+    assert_eq!(offered(settings).await.unwrap(), [2, 0, 2]);
+
+    // zstd, zlib
+    let settings = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .cert_compression(vec![
+            CertCompressionAlgorithm::Zstd,
+            CertCompressionAlgorithm::Zlib,
+        ])
+        .build();
+    assert_eq!(offered(settings).await.unwrap(), [4, 0, 3, 0, 1]);
+
+    let settings = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .cert_compression(vec![])
+        .build();
+    assert_eq!(offered(settings).await, None);
+
+    let settings = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .cert_compression(vec![
+            CertCompressionAlgorithm::Zlib,
+            CertCompressionAlgorithm::Zlib,
+        ])
+        .build();
+    assert!(rquest::Client::builder()
+        .impersonate(settings)
+        .build()
+        .is_err());
+}
+
+#[tokio::test]
+async fn tls_post_quantum_toggle() {
+    async fn offered(impersonate: Impersonate, post_quantum: bool) -> (Vec<u16>, Vec<u16>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(client_hello_groups(listener));
+
+        let settings = Impersonate::builder()
+            .impersonate(impersonate)
+            .post_quantum(post_quantum)
+            .build();
+        let client = rquest::Client::builder()
+            .impersonate(settings)
+            .resolve("tls.test", addr)
+            .build()
+            .unwrap();
+
+        let _ = client
+            .get(format!("https://tls.test:{}", addr.port()))
+            .send()
+            .await;
+        server.await.unwrap()
+    }
+
+    // X25519MLKEM768 is dropped from both the groups and the key shares
+    let (groups, key_shares) = offered(Impersonate::Chrome131, false).await;
+    assert_eq!(groups, [0x001d, 0x0017, 0x0018]);
+    assert_eq!(key_shares, [0x001d]);
+
+    // Chrome 120 gains X25519Kyber768Draft00 in front
+    let (groups, key_shares) = offered(Impersonate::Chrome120, true).await;
+    assert_eq!(groups, [0x6399, 0x001d, 0x0017, 0x0018]);
+    assert_eq!(key_shares, [0x6399, 0x001d]);
+
+    // Profiles already offering a hybrid group are left alone
+    let (groups, key_shares) = offered(Impersonate::Chrome131, true).await;
+    assert_eq!(groups, [0x11ec, 0x001d, 0x0017, 0x0018]);
+    assert_eq!(key_shares, [0x11ec, 0x001d]);
 }
 
-#[test]
 #[cfg(feature = "json")]
-fn update_json_content_type_if_set_manually() {
-    let mut map = HashMap::new();
-    map.insert("body", "json");
-    let req = Client::new()
-        .post("https://google.com/")
-        .json(&map)
+#[tokio::test]
+async fn impersonate_profile_json() {
+    use rquest::ImpersonateSettings;
+
+    let json = ImpersonateSettings::from(Impersonate::Chrome131)
+        .to_json()
+        .unwrap();
+    let settings = ImpersonateSettings::from_json(&json).unwrap();
+    assert_eq!(settings.to_json().unwrap(), json);
+    assert_eq!(
+        settings.http2.as_ref().unwrap().headers_pseudo_order,
+        Some([
+            rquest::PseudoOrder::Method,
+            rquest::PseudoOrder::Authority,
+            rquest::PseudoOrder::Scheme,
+            rquest::PseudoOrder::Path,
+        ])
+    );
+
+    // An edited profile is what goes on the wire.
+    let mut profile: serde_json::Value = serde_json::from_str(&json).unwrap();
+    profile["tls"]["curves"] = serde_json::json!(["X25519", "P-256"]);
+    profile["tls"]["key_shares"] = serde_json::json!(["X25519"]);
+    let settings = ImpersonateSettings::from_json(&profile.to_string()).unwrap();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(client_hello_groups(listener));
+    let client = rquest::Client::builder()
+        .impersonate(settings)
+        .resolve("tls.test", addr)
         .build()
-        .expect("request is not valid");
+        .unwrap();
+    let _ = client
+        .get(format!("https://tls.test:{}", addr.port()))
+        .send()
+        .await;
+    assert_eq!(server.await.unwrap(), (vec![0x001d, 0x0017], vec![0x001d]));
 
-    assert_eq!("application/json", req.headers().get(CONTENT_TYPE).unwrap());
+    // Partial profiles fill in the defaults, unknown names are rejected.
+    let settings =
+        ImpersonateSettings::from_json(r#"{"http2": {"initial_stream_window_size": 1048576}}"#)
+            .unwrap();
+    assert_eq!(
+        settings.http2.unwrap().initial_stream_window_size,
+        Some(1048576)
+    );
+    assert!(settings.tls.certs_verification);
+    let err = ImpersonateSettings::from_json(r#"{"tls": {"curves": ["X448"]}}"#).unwrap_err();
+    assert!(err.is_builder());
+
+    // Overridden signature algorithms survive a round trip.
+    let settings = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .signature_algorithms(vec![
+            rquest::SslSignatureAlgorithm::ED25519,
+            rquest::SslSignatureAlgorithm::ECDSA_SECP256R1_SHA256,
+        ])
+        .build();
+    let json = settings.to_json().unwrap();
+    assert!(json.contains(r#""ed25519""#));
+    let loaded = ImpersonateSettings::from_json(&json).unwrap();
+    assert_eq!(
+        loaded.tls.signature_algorithms,
+        settings.tls.signature_algorithms
+    );
+    assert_eq!(loaded.to_json().unwrap(), json);
+
+    // Settings the format can't name are not exported with a different fingerprint.
+    let settings = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .tls_extension_order(vec![
+            rquest::ExtensionType::SERVER_NAME,
+            rquest::ExtensionType::from(0x1234),
+        ])
+        .build();
+    assert!(settings.to_json().unwrap_err().is_builder());
+}
+
+#[test]
+fn tls_key_shares_must_not_repeat() {
+    use rquest::SslCurve;
+
+    let settings = Impersonate::builder()
+        .key_shares(vec![SslCurve::X25519, SslCurve::X25519])
+        .build();
+    let err = rquest::Client::builder()
+        .impersonate(settings)
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[test]
+fn tls_extension_order_must_keep_required_extensions() {
+    use rquest::ExtensionType;
+
+    let settings = Impersonate::builder()
+        .tls_extension_order(vec![
+            ExtensionType::SERVER_NAME,
+            ExtensionType::SUPPORTED_GROUPS,
+            ExtensionType::SIGNATURE_ALGORITHMS,
+            ExtensionType::SUPPORTED_VERSIONS,
+        ])
+        .build();
+    let err = rquest::Client::builder()
+        .impersonate(settings)
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[test]
+fn impersonate_extension_order() {
+    // Chrome 104 sends a fixed order, GREASE first and again just before padding
+    let order = Impersonate::Chrome104.extension_order().unwrap();
+    assert!(is_grease(order[0]));
+    assert!(is_grease(order[order.len() - 2]));
+    let order = order.into_iter().filter(|&ext| !is_grease(ext));
+    assert_eq!(
+        order.collect::<Vec<_>>(),
+        [0, 23, 65281, 10, 11, 35, 16, 5, 13, 18, 51, 45, 43, 27, 17513, 21]
+    );
+
+    // Chrome 131 permutes the extensions between its leading and trailing GREASE
+    let order = Impersonate::Chrome131.extension_order().unwrap();
+    assert!(is_grease(order[0]));
+    assert!(is_grease(order[order.len() - 1]));
+    let mut order = order[1..order.len() - 1].to_vec();
+    order.sort_unstable();
+    assert_eq!(
+        order,
+        [0, 5, 10, 11, 13, 16, 18, 23, 27, 35, 43, 45, 51, 17513, 65037, 65281]
+    );
+}
+
+#[test]
+fn impersonate_random() {
+    let profiles = [Impersonate::Chrome131, Impersonate::Firefox133];
+
+    assert!(Impersonate::ALL.contains(&Impersonate::random()));
+    assert!(profiles.contains(&Impersonate::random_from(&profiles)));
+    assert_eq!(
+        Impersonate::random_from_seed(Impersonate::ALL, 42),
+        Impersonate::random_from_seed(Impersonate::ALL, 42)
+    );
+
+    // every profile can be picked
+    let picked = (0..1000)
+        .map(|seed| Impersonate::random_from_seed(&profiles, seed))
+        .collect::<Vec<_>>();
+    assert!(picked.contains(&Impersonate::Chrome131));
+    assert!(picked.contains(&Impersonate::Firefox133));
 }
 
 #[tokio::test]
-async fn test_tls_info() {
-    let resp = rquest::Client::builder()
-        .tls_info(true)
+async fn impersonate_rotation() {
+    use std::collections::{HashMap, HashSet};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::AsyncWriteExt;
+
+    // the user agents seen on each connection, by client port
+    let seen = Arc::new(Mutex::new(HashMap::<u16, HashSet<String>>::new()));
+
+    let server = server::low_level_with_response({
+        let seen = seen.clone();
+        move |raw_request, client_socket| {
+            let port = client_socket.peer_addr().unwrap().port();
+            let user_agent = String::from_utf8_lossy(raw_request)
+                .lines()
+                .find_map(|line| line.strip_prefix("user-agent: ").map(ToOwned::to_owned))
+                .unwrap();
+            seen.lock()
+                .unwrap()
+                .entry(port)
+                .or_default()
+                .insert(user_agent);
+
+            Box::new(async move {
+                client_socket
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                    .await
+                    .unwrap();
+            })
+        }
+    });
+
+    let client = rquest::Client::builder()
+        .impersonate_rotation([Impersonate::Chrome131, Impersonate::Firefox133])
+        .no_proxy()
         .build()
-        .expect("client builder")
-        .get("https://google.com")
+        .unwrap();
+
+    for _ in 0..40 {
+        let res = client
+            .get(format!("http://{}/", server.addr()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+    }
+
+    let seen = seen.lock().unwrap();
+    // one pooled connection per profile, each keeping its profile
+    assert_eq!(seen.len(), 2, "{seen:?}");
+    assert!(seen.values().all(|user_agents| user_agents.len() == 1));
+    let user_agents = seen.values().flatten().collect::<HashSet<_>>();
+    assert_eq!(user_agents.len(), 2, "{seen:?}");
+}
+
+#[tokio::test]
+async fn acquire_connection() {
+    use tokio::io::AsyncWriteExt;
+
+    // answers with the client port, which tells the connections apart
+    let server = server::low_level_with_response(|_raw_request, client_socket| {
+        Box::new(async move {
+            let port = client_socket.peer_addr().unwrap().port().to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{port}",
+                port.len()
+            );
+            client_socket.write_all(response.as_bytes()).await.unwrap();
+        })
+    });
+    let url = format!("http://{}/", server.addr());
+
+    let client = rquest::Client::builder().no_proxy().build().unwrap();
+    let conn = client.acquire_connection(&url).await.unwrap();
+
+    // only the first request establishes the connection
+    let mut ports = Vec::new();
+    for i in 0..3 {
+        let res = conn.get(&url).send().await.unwrap();
+        assert_eq!(res.connection_reused(), i > 0);
+        ports.push(res.text().await.unwrap());
+    }
+    let req = client.post(&url).build().unwrap();
+    ports.push(conn.execute(req).await.unwrap().text().await.unwrap());
+    assert!(ports.iter().all(|port| *port == ports[0]), "{ports:?}");
+
+    // the pinned connection is not handed out to other requests
+    let other = client.get(&url).send().await.unwrap().text().await.unwrap();
+    assert_ne!(other, ports[0]);
+}
+
+#[tokio::test]
+async fn acquire_connection_must_be_http() {
+    let client = rquest::Client::new();
+    let err = client
+        .acquire_connection("ftp://example.com")
+        .await
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn rate_limit() {
+    use std::time::{Duration, Instant};
+
+    let server = server::http(move |_req| async { http::Response::default() });
+    let url = format!("http://{}/", server.addr());
+
+    let client = rquest::Client::builder()
+        .rate_limit(5, 1)
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    for _ in 0..10 {
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+    }
+    assert!(
+        start.elapsed() >= Duration::from_secs(1),
+        "{:?}",
+        start.elapsed()
+    );
+}
+
+#[tokio::test]
+async fn rate_limit_per_host() {
+    use std::time::{Duration, Instant};
+
+    let server1 = server::http(move |_req| async { http::Response::default() });
+    let server2 = server::http(move |_req| async { http::Response::default() });
+
+    let client = rquest::Client::builder()
+        .rate_limit_per_host(2, 1)
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    // each host waits once, the two waits overlapping
+    let start = Instant::now();
+    let send = |addr| {
+        let client = client.clone();
+        async move {
+            for _ in 0..2 {
+                client.get(format!("http://{addr}/")).send().await.unwrap();
+            }
+        }
+    };
+    tokio::join!(send(server1.addr()), send(server2.addr()));
+
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(500), "{elapsed:?}");
+    assert!(elapsed < Duration::from_millis(1000), "{elapsed:?}");
+}
+
+#[tokio::test]
+async fn rate_limit_max_queue() {
+    use std::future::Future;
+
+    let server = server::http(move |_req| async { http::Response::default() });
+    let url = format!("http://{}/", server.addr());
+
+    let client = rquest::Client::builder()
+        .rate_limit(10, 1)
+        .rate_limit_max_queue(1)
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    // the first is sent right away, the second waits, the third is turned away
+    let mut first = Box::pin(client.get(&url).send());
+    let mut second = Box::pin(client.get(&url).send());
+    futures_util::future::poll_fn(|cx| {
+        assert!(first.as_mut().poll(cx).is_pending());
+        assert!(second.as_mut().poll(cx).is_pending());
+        std::task::Poll::Ready(())
+    })
+    .await;
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(err.is_rate_limited(), "{err:?}");
+    assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
+
+    assert!(first.await.is_ok());
+    assert!(second.await.is_ok());
+    // the queue drains as tokens come in
+    assert!(client.get(&url).send().await.is_ok());
+}
+
+#[tokio::test]
+async fn rate_limit_reserves_when_polled() {
+    let server = server::http(move |_req| async { http::Response::default() });
+    let url = format!("http://{}/", server.addr());
+
+    let client = rquest::Client::builder()
+        .rate_limit(10, 1)
+        .rate_limit_max_queue(0)
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    // requests that are never polled don't take a token
+    drop((client.get(&url).send(), client.get(&url).send()));
+    assert!(client.get(&url).send().await.is_ok());
+}
+
+#[test]
+fn rate_limit_must_allow_requests() {
+    let err = rquest::Client::builder()
+        .rate_limit(0, 1)
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn warmup_pools_a_connection() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    let server = server::http(move |_req| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async { http::Response::default() }
+    });
+    let url = format!("http://{}/", server.addr());
+
+    for client in [
+        rquest::Client::builder().no_proxy().build().unwrap(),
+        rquest::Client::builder()
+            .http2_only()
+            .no_proxy()
+            .build()
+            .unwrap(),
+    ] {
+        let before = requests.load(Ordering::SeqCst);
+        client.warmup(&url).await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), before);
+
+        let res = client.get(&url).send().await.unwrap();
+        assert!(res.connection_reused());
+    }
+
+    let err = Client::new().warmup("ftp://example.com").await.unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn error_io_kind() {
+    use std::io::ErrorKind;
+    use tokio::io::AsyncReadExt;
+
+    // nothing listens on the port anymore
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let client = rquest::Client::builder().no_proxy().build().unwrap();
+    let err = client
+        .get(format!("http://{addr}/"))
         .send()
         .await
-        .expect("response");
-    let tls_info = resp.extensions().get::<rquest::TlsInfo>();
-    assert!(tls_info.is_some());
-    let tls_info = tls_info.unwrap();
-    let peer_certificate = tls_info.peer_certificate();
-    assert!(peer_certificate.is_some());
-    let der = peer_certificate.unwrap();
-    assert_eq!(der[0], 0x30); // ASN.1 SEQUENCE
+        .unwrap_err();
+    assert!(err.is_connect());
+    assert_eq!(err.io_kind(), Some(ErrorKind::ConnectionRefused));
+
+    // resets the connection once the request is read
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 1024];
+        let _ = stream.read(&mut buf).await;
+        socket2::SockRef::from(&stream)
+            .set_linger(Some(std::time::Duration::ZERO))
+            .unwrap();
+    });
+
+    let err = client
+        .get(format!("http://{addr}/"))
+        .send()
+        .await
+        .unwrap_err();
+    assert_eq!(err.io_kind(), Some(ErrorKind::ConnectionReset), "{err:?}");
+
+    let err = client.get("ftp://example.com").send().await.unwrap_err();
+    assert_eq!(err.io_kind(), None);
+}
+
+#[tokio::test]
+async fn connection_reused() {
+    let server = server::http(move |_req| async { http::Response::default() });
+    let url = format!("http://{}/", server.addr());
+
+    for client in [
+        rquest::Client::builder().no_proxy().build().unwrap(),
+        rquest::Client::builder()
+            .http2_only()
+            .no_proxy()
+            .build()
+            .unwrap(),
+    ] {
+        let res = client.get(&url).send().await.unwrap();
+        assert!(!res.connection_reused());
+        res.bytes().await.unwrap();
+
+        let res = client.get(&url).send().await.unwrap();
+        assert!(res.connection_reused());
+    }
+
+    let client = rquest::Client::builder()
+        .pool_max_idle_per_host(0)
+        .no_proxy()
+        .build()
+        .unwrap();
+    for _ in 0..2 {
+        let res = client.get(&url).send().await.unwrap();
+        assert!(!res.connection_reused());
+        res.bytes().await.unwrap();
+    }
+}
+
+/// A server whose first response takes a second, and whose next ones are immediate.
+fn slow_then_fast_server() -> (
+    server::Server,
+    std::sync::Arc<std::sync::atomic::AtomicUsize>,
+) {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    let server = server::http(move |_req| {
+        let first = counter.fetch_add(1, Ordering::SeqCst) == 0;
+        async move {
+            if first {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                http::Response::new("slow".into())
+            } else {
+                http::Response::new("fast".into())
+            }
+        }
+    });
+    (server, requests)
+}
+
+#[tokio::test]
+async fn hedged_request_uses_first_response() {
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    let (server, requests) = slow_then_fast_server();
+    let client = rquest::Client::builder().no_proxy().build().unwrap();
+
+    let start = std::time::Instant::now();
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .hedge(Duration::from_millis(50))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.text().await.unwrap(), "fast");
+    assert!(start.elapsed() < Duration::from_millis(500));
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn hedged_request_uses_its_own_connection() {
+    use std::time::Duration;
+
+    let (server, _requests) = slow_then_fast_server();
+    let client = rquest::Client::builder()
+        .http2_only()
+        .no_proxy()
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    let res = client
+        .get(&url)
+        .hedge(Duration::from_millis(50))
+        .send()
+        .await
+        .unwrap();
+    let hedge_addr = res.local_addr();
+    assert_eq!(res.text().await.unwrap(), "fast");
+
+    // the HTTP/2 connection of the first request is not the one of the hedge
+    let res = client.get(&url).send().await.unwrap();
+    assert!(res.local_addr().is_some());
+    assert_ne!(res.local_addr(), hedge_addr);
+}
+
+#[tokio::test]
+async fn hedge_skips_non_idempotent_requests() {
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
 
-    let resp = rquest::Client::builder()
-        .build()
-        .expect("client builder")
-        .get("https://google.com")
+    let (server, requests) = slow_then_fast_server();
+    let client = rquest::Client::builder().no_proxy().build().unwrap();
+
+    let res = client
+        .post(format!("http://{}/", server.addr()))
+        .hedge(Duration::from_millis(50))
         .send()
         .await
-        .expect("response");
-    let tls_info = resp.extensions().get::<rquest::TlsInfo>();
-    assert!(tls_info.is_none());
+        .unwrap();
+
+    assert_eq!(res.text().await.unwrap(), "slow");
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
 }
 
 // NOTE: using the default "current_thread" runtime here would cause the test to
@@ -530,6 +3707,109 @@ async fn test_client_os_spoofing() {
     assert_eq!(res.status(), rquest::StatusCode::OK);
 }
 
+#[tokio::test]
+async fn test_client_locale() {
+    let server = server::http(move |req| async move {
+        if req.uri().path() == "/chrome" {
+            assert_eq!(req.headers()["accept-language"], "de-DE,de;q=0.9");
+            assert_eq!(req.headers()["sec-ch-ua-platform"], "\"Windows\"");
+        } else {
+            assert_eq!(req.headers()["accept-language"], "de-DE,de;q=0.5");
+        }
+        http::Response::default()
+    });
+
+    for (impersonate, path) in [
+        (Impersonate::Chrome131, "chrome"),
+        (Impersonate::Firefox133, "firefox"),
+    ] {
+        let res = Client::builder()
+            .impersonate(
+                Impersonate::builder()
+                    .impersonate(impersonate)
+                    .impersonate_os(ImpersonateOS::Windows)
+                    .locale("de-DE")
+                    .build(),
+            )
+            .build()
+            .unwrap()
+            .get(format!("http://{}/{path}", server.addr()))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn test_client_user_agent_suffix() {
+    let server = server::http(move |req| async move {
+        assert_eq!(
+            req.headers()["user-agent"],
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) \
+             Chrome/131.0.0.0 Safari/537.36 MyBot/1.0"
+        );
+        assert_eq!(
+            req.headers()["sec-ch-ua"],
+            "\"Google Chrome\";v=\"131\", \"Chromium\";v=\"131\", \"Not_A Brand\";v=\"24\""
+        );
+        http::Response::default()
+    });
+
+    let res = Client::builder()
+        .impersonate(
+            Impersonate::builder()
+                .impersonate(Impersonate::Chrome131)
+                .impersonate_os(ImpersonateOS::Windows)
+                .user_agent_suffix("MyBot/1.0")
+                .build(),
+        )
+        .build()
+        .unwrap()
+        .get(format!("http://{}", server.addr()))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_client_priority_header() {
+    let server = server::http(move |req| async move {
+        assert_eq!(
+            req.headers()["priority"],
+            req.headers()["expected-priority"]
+        );
+        http::Response::default()
+    });
+
+    for (impersonate, urgency, incremental, expected) in [
+        (Impersonate::Chrome131, 0, true, "u=0, i"),
+        (Impersonate::Chrome131, 3, false, "u=3"),
+        (Impersonate::Chrome120, 9, true, "u=7, i"),
+        (Impersonate::OkHttp5, 1, false, "u=1"),
+    ] {
+        let res = Client::builder()
+            .impersonate(
+                Impersonate::builder()
+                    .impersonate(impersonate)
+                    .priority_header(urgency, incremental)
+                    .build(),
+            )
+            .build()
+            .unwrap()
+            .get(format!("http://{}", server.addr()))
+            .header("expected-priority", expected)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+    }
+}
+
 #[tokio::test]
 async fn http1_version() {
     let server = server::http(move |_| async move { http::Response::default() });
@@ -546,6 +3826,56 @@ async fn http1_version() {
     assert_eq!(resp.version(), rquest::Version::HTTP_11);
 }
 
+#[tokio::test]
+async fn http10_version() {
+    let server = server::low_level_with_response(|raw_request, client_socket| {
+        Box::new(async move {
+            use tokio::io::AsyncWriteExt;
+            let head = String::from_utf8_lossy(raw_request);
+            let request_line = head.lines().next().unwrap().to_owned();
+            if head.to_ascii_lowercase().contains("connection: keep-alive") {
+                let response = format!(
+                    "HTTP/1.0 200 OK\r\nconnection: keep-alive\r\ncontent-length: {}\r\n\r\n{request_line}",
+                    request_line.len()
+                );
+                client_socket.write_all(response.as_bytes()).await.unwrap();
+            } else {
+                // No length, the body ends when the connection is closed.
+                let response = format!("HTTP/1.0 200 OK\r\n\r\n{request_line}");
+                client_socket.write_all(response.as_bytes()).await.unwrap();
+                client_socket.shutdown().await.unwrap();
+            }
+        })
+    });
+    let url = format!("http://{}/", server.addr());
+    let client = Client::new();
+
+    for _ in 0..2 {
+        let res = client
+            .get(&url)
+            .version(Version::HTTP_10)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.version(), Version::HTTP_10);
+        assert!(!res.connection_reused());
+        assert_eq!(res.text().await.unwrap(), "GET / HTTP/1.0");
+    }
+
+    let send = || {
+        client
+            .get(&url)
+            .version(Version::HTTP_10)
+            .header("connection", "keep-alive")
+            .send()
+    };
+    let res = send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "GET / HTTP/1.0");
+    let res = send().await.unwrap();
+    assert!(res.connection_reused());
+    assert_eq!(res.text().await.unwrap(), "GET / HTTP/1.0");
+}
+
 #[tokio::test]
 async fn http2_version() {
     let server = server::http(move |_| async move { http::Response::default() });
@@ -560,6 +3890,552 @@ async fn http2_version() {
         .unwrap();
 
     assert_eq!(resp.version(), rquest::Version::HTTP_2);
+    assert_eq!(resp.reason(), None);
+}
+
+#[tokio::test]
+async fn danger_raw_path_and_scheme() {
+    let server = server::http(move |req| async move {
+        let target = match req.version() {
+            http::Version::HTTP_2 => req.uri().to_string(),
+            _ => req.uri().path_and_query().unwrap().to_string(),
+        };
+        http::Response::new(target.into())
+    });
+    let client = Client::new();
+    let url = format!("http://{}/a/../b?c", server.addr());
+
+    // The URL is normalized, the raw path is not.
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "/b?c");
+
+    let res = client
+        .get(&url)
+        .danger_raw_path("/a/../b?c")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.version(), Version::HTTP_11);
+    assert_eq!(res.text().await.unwrap(), "/a/../b?c");
+
+    let res = client
+        .get(&url)
+        .version(Version::HTTP_2)
+        .danger_raw_path("//x")
+        .danger_raw_scheme("https")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.version(), Version::HTTP_2);
+    assert_eq!(
+        res.text().await.unwrap(),
+        format!("https://{}//x", server.addr())
+    );
+
+    for path in ["x", "?c", "x/../b"] {
+        let err = client.get(&url).danger_raw_path(path).build().unwrap_err();
+        assert!(err.is_builder(), "{path}");
+    }
+}
+
+#[tokio::test]
+async fn http2_authority_keeps_non_default_port() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.version(), http::Version::HTTP_2);
+        http::Response::new(req.uri().authority().unwrap().to_string().into())
+    });
+    let port = server.addr().port();
+
+    let client = rquest::Client::builder()
+        .resolve("authority.test", server.addr())
+        .build()
+        .unwrap();
+
+    // `resolve` keeps the overridden port when the URL has none, so the default
+    // port can be exercised without binding to it.
+    let authority = client
+        .get("http://authority.test:80/")
+        .version(Version::HTTP_2)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(authority, "authority.test");
+
+    let authority = client
+        .get(format!("http://authority.test:{port}/"))
+        .version(Version::HTTP_2)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(authority, format!("authority.test:{port}"));
+}
+
+/// Accepts one HTTP/2 connection and returns the frames its client sent, as
+/// type, flags, stream and payload, up to the HEADERS frame of the first request.
+async fn http2_frames(listener: tokio::net::TcpListener) -> Vec<(u8, u8, u32, Vec<u8>)> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (mut io, _) = listener.accept().await.unwrap();
+    let mut preface = [0u8; 24];
+    io.read_exact(&mut preface).await.unwrap();
+    io.write_all(&[0, 0, 0, 4, 0, 0, 0, 0, 0]).await.unwrap();
+
+    let mut frames = Vec::new();
+    loop {
+        let mut head = [0u8; 9];
+        io.read_exact(&mut head).await.unwrap();
+        let len = u32::from_be_bytes([0, head[0], head[1], head[2]]) as usize;
+        let stream = u32::from_be_bytes([head[5], head[6], head[7], head[8]]) & 0x7fff_ffff;
+        let mut payload = vec![0u8; len];
+        io.read_exact(&mut payload).await.unwrap();
+        frames.push((head[3], head[4], stream, payload));
+        if head[3] == 1 {
+            return frames;
+        }
+    }
+}
+
+/// Accepts one HTTP/2 connection and returns the pseudo-headers of its first
+/// request, in the order they were sent, as `m`, `s`, `a` and `p`.
+async fn http2_pseudo_order(listener: tokio::net::TcpListener) -> String {
+    let (_, flags, _, payload) = http2_frames(listener).await.pop().unwrap();
+    let (mut start, mut end) = (0, payload.len());
+    if flags & 0x08 != 0 {
+        start += 1;
+        end -= payload[0] as usize;
+    }
+    if flags & 0x20 != 0 {
+        start += 5;
+    }
+    let block = &payload[start..end];
+
+    // Reads an HPACK integer with an `n`-bit prefix.
+    fn integer(block: &[u8], pos: &mut usize, n: u32) -> usize {
+        let mask = (1 << n) - 1;
+        let mut value = (block[*pos] & mask) as usize;
+        *pos += 1;
+        if value == mask as usize {
+            let mut shift = 0;
+            loop {
+                let byte = block[*pos];
+                *pos += 1;
+                value += ((byte & 0x7f) as usize) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+        }
+        value
+    }
+
+    // A fresh dynamic table holds none of the pseudo-headers, so the name
+    // indices all point into the static table.
+    let mut order = String::new();
+    let mut pos = 0;
+    while order.len() < 4 {
+        let byte = block[pos];
+        let (index, literal) = match byte {
+            _ if byte & 0x80 != 0 => (integer(block, &mut pos, 7), false),
+            _ if byte & 0x40 != 0 => (integer(block, &mut pos, 6), true),
+            _ if byte & 0x20 != 0 => {
+                integer(block, &mut pos, 5);
+                continue;
+            }
+            _ => (integer(block, &mut pos, 4), true),
+        };
+        if literal {
+            let len = integer(block, &mut pos, 7);
+            pos += len;
+        }
+        order.push(match index {
+            1 => 'a',
+            2 | 3 => 'm',
+            4 | 5 => 'p',
+            6 | 7 => 's',
+            _ => break,
+        });
+    }
+    order
+}
+
+#[tokio::test]
+async fn http2_pseudo_header_order() {
+    use rquest::PseudoOrder;
+
+    async fn sent(settings: rquest::ImpersonateSettings) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(http2_pseudo_order(listener));
+
+        let client = rquest::Client::builder()
+            .impersonate(settings)
+            .timeout(std::time::Duration::from_millis(500))
+            .build()
+            .unwrap();
+        let _ = client
+            .get(format!("http://{addr}/"))
+            .version(Version::HTTP_2)
+            .send()
+            .await;
+        server.await.unwrap()
+    }
+
+    let chrome = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .build();
+    assert_eq!(sent(chrome).await, "masp");
+
+    let custom = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .pseudo_header_order(vec![
+            PseudoOrder::Path,
+            PseudoOrder::Authority,
+            PseudoOrder::Scheme,
+            PseudoOrder::Method,
+        ])
+        .build();
+    assert_eq!(sent(custom).await, "pasm");
+
+    // Repeated pseudo-headers are sent once and missing ones are added.
+    let partial = Impersonate::builder()
+        .impersonate(Impersonate::Chrome131)
+        .pseudo_header_order([PseudoOrder::Path, PseudoOrder::Path, PseudoOrder::Authority])
+        .build();
+    assert_eq!(sent(partial).await, "pams");
+}
+
+#[tokio::test]
+async fn http2_window_sizes() {
+    /// Returns the initial stream window size the client advertised, and the
+    /// size of its connection window.
+    async fn windows(builder: rquest::ClientBuilder) -> (Option<u32>, u32) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(http2_frames(listener));
+
+        let client = builder
+            .timeout(std::time::Duration::from_millis(500))
+            .build()
+            .unwrap();
+        let _ = client
+            .get(format!("http://{addr}/"))
+            .version(Version::HTTP_2)
+            .send()
+            .await;
+
+        let (mut stream_window, mut connection_window) = (None, 65_535);
+        for (kind, flags, stream, payload) in server.await.unwrap() {
+            match kind {
+                // SETTINGS, other than acknowledgements
+                4 if flags & 0x1 == 0 => {
+                    for setting in payload.chunks(6) {
+                        if setting[..2] == [0, 4] {
+                            let value = [setting[2], setting[3], setting[4], setting[5]];
+                            stream_window = Some(u32::from_be_bytes(value));
+                        }
+                    }
+                }
+                // WINDOW_UPDATE of the connection
+                8 if stream == 0 => {
+                    let increment = [payload[0], payload[1], payload[2], payload[3]];
+                    connection_window += u32::from_be_bytes(increment);
+                }
+                _ => {}
+            }
+        }
+        (stream_window, connection_window)
+    }
+
+    let chrome = rquest::Client::builder().impersonate(Impersonate::Chrome131);
+    assert_eq!(windows(chrome).await, (Some(6_291_456), 15_728_640));
+
+    let tuned = rquest::Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .http2_initial_stream_window_size(1 << 20)
+        .http2_initial_connection_window_size(1 << 24);
+    assert_eq!(windows(tuned).await, (Some(1 << 20), 1 << 24));
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_in_flight_requests() {
+    let server = server::http(move |_req| async {
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        http::Response::new("slow".into())
+    });
+    let url = format!("http://{}/", server.addr());
+    let client = rquest::Client::builder().no_proxy().build().unwrap();
+
+    let in_flight = tokio::spawn(client.get(&url).send());
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let start = std::time::Instant::now();
+    client.shutdown(std::time::Duration::from_secs(5)).await;
+    assert!(start.elapsed() >= std::time::Duration::from_millis(100));
+
+    let res = in_flight.await.unwrap().unwrap();
+    assert_eq!(res.text().await.unwrap(), "slow");
+
+    // requests sent after the shutdown fail, clones included
+    for client in [client.clone(), client] {
+        let err = client.get(&url).send().await.unwrap_err();
+        assert!(err.is_request());
+        assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
+    }
+}
+
+#[tokio::test]
+async fn shutdown_closes_idle_http2_connections() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (io, _) = listener.accept().await.unwrap();
+        let mut conn = h2::server::handshake(io).await.unwrap();
+        let (_req, mut respond) = conn.accept().await.unwrap().unwrap();
+        respond
+            .send_response(http::Response::new(()), true)
+            .unwrap();
+        // the connection ends once the client goes away
+        conn.accept().await.map(|res| res.map(|_| ()))
+    });
+
+    let client = rquest::Client::builder().http2_only().build().unwrap();
+    let res = client.get(format!("http://{addr}/")).send().await.unwrap();
+    assert_eq!(res.version(), rquest::Version::HTTP_2);
+    drop(res);
+
+    client.shutdown(std::time::Duration::from_secs(5)).await;
+    let end = tokio::time::timeout(std::time::Duration::from_secs(5), server)
+        .await
+        .expect("connection closed")
+        .unwrap();
+    assert!(end.is_none(), "connection closed gracefully: {end:?}");
+}
+
+/// Serves HTTP/2 connections that reset every stream with `reason`.
+async fn http2_reset_server(reason: h2::Reason) -> std::net::SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (io, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut conn = h2::server::handshake(io).await.unwrap();
+                while let Some(Ok((_req, mut respond))) = conn.accept().await {
+                    respond.send_reset(reason);
+                }
+            });
+        }
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn http2_error_reason() {
+    let client = rquest::Client::builder().http2_only().build().unwrap();
+
+    for (reason, expected) in [
+        (
+            h2::Reason::ENHANCE_YOUR_CALM,
+            rquest::Reason::ENHANCE_YOUR_CALM,
+        ),
+        (h2::Reason::REFUSED_STREAM, rquest::Reason::REFUSED_STREAM),
+    ] {
+        let addr = http2_reset_server(reason).await;
+        let err = client
+            .get(format!("http://{addr}"))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.h2_reason(), Some(expected));
+    }
+
+    let err = client.get("http://127.0.0.1:1").send().await.unwrap_err();
+    assert_eq!(err.h2_reason(), None);
+}
+
+/// Serves HTTP/2 connections, closing the first one with a GOAWAY frame
+/// carrying `reason` before any request is processed.
+async fn http2_goaway_server(reason: h2::Reason) -> std::net::SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (io, _) = listener.accept().await.unwrap();
+        tokio::spawn(async move {
+            let mut conn = h2::server::handshake(io).await.unwrap();
+            conn.abrupt_shutdown(reason);
+            while let Some(Ok(_)) = conn.accept().await {}
+        });
+
+        loop {
+            let (io, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut conn = h2::server::handshake(io).await.unwrap();
+                while let Some(Ok((_req, mut respond))) = conn.accept().await {
+                    respond
+                        .send_response(http::Response::new(()), true)
+                        .unwrap();
+                }
+            });
+        }
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn http2_goaway_retries_idempotent_requests() {
+    let client = rquest::Client::builder().http2_only().build().unwrap();
+
+    let addr = http2_goaway_server(h2::Reason::ENHANCE_YOUR_CALM).await;
+    let res = client.get(format!("http://{addr}")).send().await.unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    assert_eq!(client.http2_retry_count(), 1);
+
+    let addr = http2_goaway_server(h2::Reason::ENHANCE_YOUR_CALM).await;
+    let err = client
+        .post(format!("http://{addr}"))
+        .body("data")
+        .send()
+        .await
+        .unwrap_err();
+    assert_eq!(err.h2_reason(), Some(rquest::Reason::ENHANCE_YOUR_CALM));
+    assert_eq!(client.http2_retry_count(), 1);
+
+    let addr = http2_goaway_server(h2::Reason::NO_ERROR).await;
+    let res = client
+        .post(format!("http://{addr}"))
+        .body("data")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    assert_eq!(client.http2_retry_count(), 2);
+}
+
+#[tokio::test]
+async fn http2_keep_alive_pings_idle_connections() {
+    let server = server::http(move |_| async move { http::Response::default() });
+    let url = format!("http://{}", server.addr());
+
+    let idle_bytes_sent = |client: Client| {
+        let url = url.clone();
+        async move {
+            client.get(url).send().await.unwrap();
+            let sent = client.bytes_sent();
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            client.bytes_sent() - sent
+        }
+    };
+
+    let client = Client::builder()
+        .http2_only()
+        .count_bytes(true)
+        .build()
+        .unwrap();
+    assert_eq!(idle_bytes_sent(client).await, 0);
+
+    let client = Client::builder()
+        .http2_only()
+        .count_bytes(true)
+        .http2_keep_alive_interval(std::time::Duration::from_millis(50))
+        .http2_keep_alive_while_idle(true)
+        .build()
+        .unwrap();
+    assert!(idle_bytes_sent(client).await > 0);
+}
+
+/// Serves a single HTTP/2 connection that tries to push `/pushed` alongside the
+/// first response.
+///
+/// The server task resolves to `None` if the client refused push via
+/// `SETTINGS_ENABLE_PUSH`, otherwise to the reason the client reset the pushed stream.
+async fn http2_push_server() -> (
+    std::net::SocketAddr,
+    tokio::task::JoinHandle<Option<h2::Reason>>,
+) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = tokio::spawn(async move {
+        let (io, _) = listener.accept().await.unwrap();
+        let mut conn = h2::server::handshake(io).await.unwrap();
+        let (_req, mut respond) = conn.accept().await.unwrap().unwrap();
+        tokio::spawn(async move { while conn.accept().await.is_some() {} });
+
+        let promise = http::Request::get(format!("http://{addr}/pushed"))
+            .body(())
+            .unwrap();
+        let reason = match respond.push_request(promise) {
+            Ok(mut pushed) => {
+                let mut stream = pushed
+                    .send_response(http::Response::new(()), false)
+                    .unwrap();
+                respond
+                    .send_response(http::Response::new(()), true)
+                    .unwrap();
+                let reason = futures_util::future::poll_fn(|cx| stream.poll_reset(cx));
+                Some(reason.await.unwrap())
+            }
+            Err(_) => {
+                respond
+                    .send_response(http::Response::new(()), true)
+                    .unwrap();
+                None
+            }
+        };
+        reason
+    });
+
+    (addr, server)
+}
+
+#[tokio::test]
+async fn http2_push_disabled_by_impersonation() {
+    let (addr, server) = http2_push_server().await;
+
+    let resp = rquest::Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .build()
+        .unwrap()
+        .get(format!("http://{addr}"))
+        .version(Version::HTTP_2)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), rquest::StatusCode::OK);
+    assert_eq!(server.await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn http2_pushed_streams_are_reset() {
+    let (addr, server) = http2_push_server().await;
+
+    let resp = rquest::Client::builder()
+        .with_http2_builder(|builder| {
+            builder.enable_push(true);
+        })
+        .build()
+        .unwrap()
+        .get(format!("http://{addr}"))
+        .version(Version::HTTP_2)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), rquest::StatusCode::OK);
+    drop(resp);
+    assert_eq!(server.await.unwrap(), Some(h2::Reason::CANCEL));
 }
 
 #[tokio::test]
@@ -597,3 +4473,102 @@ async fn pool_cache() {
     assert_eq!(resp.status(), rquest::StatusCode::OK);
     assert_eq!(resp.version(), http::Version::HTTP_2);
 }
+
+#[tokio::test]
+async fn no_pool() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn connections(builder: rquest::ClientBuilder) -> usize {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buf = [0; 4096];
+                    while let Ok(n @ 1..) = stream.read(&mut buf).await {
+                        if buf[..n].ends_with(b"\r\n\r\n") {
+                            let response = b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok";
+                            stream.write_all(response).await.unwrap();
+                        }
+                    }
+                });
+            }
+        });
+
+        let client = builder.no_proxy().build().unwrap();
+        for _ in 0..3 {
+            let res = client.get(format!("http://{addr}/")).send().await.unwrap();
+            assert_eq!(res.text().await.unwrap(), "ok");
+        }
+        accepted.load(Ordering::SeqCst)
+    }
+
+    assert_eq!(connections(rquest::Client::builder()).await, 1);
+    assert_eq!(connections(rquest::Client::builder().no_pool()).await, 3);
+    assert_eq!(
+        connections(rquest::Client::builder().pool_idle_timeout(std::time::Duration::ZERO)).await,
+        3
+    );
+}
+
+#[tokio::test]
+async fn head_response_content_length() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.method(), "HEAD");
+        http::Response::builder()
+            .header("content-length", "1234")
+            .header("content-type", "application/octet-stream")
+            .body(Default::default())
+            .unwrap()
+    });
+    let url = format!("http://{}/head", server.addr());
+
+    for client in [
+        rquest::Client::builder().http1_only().build().unwrap(),
+        rquest::Client::builder().http2_only().build().unwrap(),
+    ] {
+        let res = tokio::time::timeout(std::time::Duration::from_secs(5), client.head(&url).send())
+            .await
+            .expect("head request timed out")
+            .unwrap();
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+        assert_eq!(res.content_length(), Some(1234));
+        assert_eq!(res.headers()["content-type"], "application/octet-stream");
+
+        let body = tokio::time::timeout(std::time::Duration::from_secs(5), res.bytes())
+            .await
+            .expect("head body timed out")
+            .unwrap();
+        assert!(body.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn pool_key_tag() {
+    let server = server::http(move |_req| async move { http::Response::new("ok".into()) });
+    let url = format!("http://{}/", server.addr());
+    let client = rquest::Client::builder().no_proxy().build().unwrap();
+
+    for (tag, reused) in [
+        (Some("alice"), false),
+        (Some("alice"), true),
+        (Some("bob"), false),
+        (None, false),
+        (None, true),
+        (Some("bob"), true),
+    ] {
+        let mut req = client.get(&url);
+        if let Some(tag) = tag {
+            req = req.pool_key_tag(tag);
+        }
+        let res = req.send().await.unwrap();
+        assert_eq!(res.connection_reused(), reused, "{tag:?}");
+        assert_eq!(res.text().await.unwrap(), "ok");
+    }
+}
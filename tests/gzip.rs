@@ -37,6 +37,250 @@ async fn test_gzip_empty_body() {
     assert_eq!(body, "");
 }
 
+#[tokio::test]
+async fn gzip_bytes_limit_applies_to_decoded_body() {
+    let compressed = gzip_compress(&[b'a'; 64 * 1024]);
+    let compressed_len = compressed.len();
+    let server = server::http(move |_req| {
+        let compressed = compressed.clone();
+        async move {
+            http::Response::builder()
+                .header("content-encoding", "gzip")
+                .header("content-length", compressed.len())
+                .body(compressed.into())
+                .unwrap()
+        }
+    });
+
+    let err = rquest::Client::new()
+        .get(format!("http://{}/gzip", server.addr()))
+        .bytes_limit(compressed_len * 2)
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap_err();
+    assert!(err.is_body_too_large());
+}
+
+#[tokio::test]
+async fn gzip_encoded_length() {
+    use futures_util::StreamExt;
+
+    let compressed = gzip_compress(RESPONSE_CONTENT.as_bytes());
+    let compressed_len = compressed.len() as u64;
+    let server = server::http(move |req| {
+        let compressed = compressed.clone();
+        async move {
+            match req.uri().path() {
+                "/plain" => http::Response::new(RESPONSE_CONTENT.into()),
+                "/chunked" => {
+                    let chunks = compressed
+                        .chunks(4)
+                        .map(|chunk| chunk.to_vec())
+                        .collect::<Vec<_>>();
+                    let stream =
+                        futures_util::stream::iter(chunks).map(Ok::<_, std::convert::Infallible>);
+                    http::Response::builder()
+                        .header("content-encoding", "gzip")
+                        .body(rquest::Body::wrap_stream(stream))
+                        .unwrap()
+                }
+                _ => http::Response::builder()
+                    .header("content-encoding", "gzip")
+                    .header("content-length", compressed.len())
+                    .body(compressed.into())
+                    .unwrap(),
+            }
+        }
+    });
+    let client = rquest::Client::new();
+
+    let read_to_end = |mut res: rquest::Response| async move {
+        let mut body = Vec::new();
+        while let Some(chunk) = res.chunk().await.unwrap() {
+            body.extend_from_slice(&chunk);
+        }
+        (res.encoded_length(), String::from_utf8(body).unwrap())
+    };
+
+    for path in ["gzip", "chunked"] {
+        let res = client
+            .get(format!("http://{}/{}", server.addr(), path))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.content_length(), None);
+        assert_eq!(res.encoded_length(), 0);
+        assert_eq!(
+            read_to_end(res).await,
+            (compressed_len, RESPONSE_CONTENT.to_owned())
+        );
+    }
+
+    let res = client
+        .get(format!("http://{}/plain", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    let len = RESPONSE_CONTENT.len() as u64;
+    assert_eq!(res.content_length(), Some(len));
+    assert_eq!(read_to_end(res).await, (len, RESPONSE_CONTENT.to_owned()));
+}
+
+#[tokio::test]
+async fn gzip_encoding_token_case_and_parameters() {
+    let compressed = gzip_compress(RESPONSE_CONTENT.as_bytes());
+    let server = server::http(move |req| {
+        let compressed = compressed.clone();
+        async move {
+            let encoding = req.headers()["x-encoding"].clone();
+            http::Response::builder()
+                .header("content-encoding", encoding)
+                .header("content-length", compressed.len())
+                .body(compressed.into())
+                .unwrap()
+        }
+    });
+    let client = rquest::Client::new();
+
+    for encoding in [
+        "GZIP",
+        "Gzip",
+        "gzip; q=1",
+        " gzip ;level=9 ",
+        "identity, GZIP",
+    ] {
+        let res = client
+            .get(format!("http://{}/gzip", server.addr()))
+            .header("x-encoding", encoding)
+            .send()
+            .await
+            .unwrap();
+        assert!(res.headers().get("content-encoding").is_none());
+        assert_eq!(res.text().await.unwrap(), RESPONSE_CONTENT, "{encoding}");
+    }
+}
+
+#[tokio::test]
+async fn gzip_no_decompress() {
+    let compressed = gzip_compress(RESPONSE_CONTENT.as_bytes());
+    let expected = compressed.clone();
+    let server = server::http(move |req| {
+        let compressed = compressed.clone();
+        async move {
+            assert!(req.headers()["accept-encoding"]
+                .to_str()
+                .unwrap()
+                .contains("gzip"));
+            http::Response::builder()
+                .header("content-encoding", "gzip")
+                .header("content-length", compressed.len())
+                .body(compressed.into())
+                .unwrap()
+        }
+    });
+
+    let res = rquest::Client::new()
+        .get(format!("http://{}/gzip", server.addr()))
+        .no_decompress()
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+    assert_eq!(res.content_length(), Some(expected.len() as u64));
+    assert_eq!(res.bytes().await.unwrap(), expected);
+}
+
+#[tokio::test]
+async fn gzip_into_async_read() {
+    let content = [b'a'; 64 * 1024];
+    let compressed = gzip_compress(&content);
+    let server = server::http(move |_req| {
+        let compressed = compressed.clone();
+        async move {
+            http::Response::builder()
+                .header("content-encoding", "gzip")
+                .body(compressed.into())
+                .unwrap()
+        }
+    });
+    let url = format!("http://{}/gzip", server.addr());
+    let client = rquest::Client::new();
+
+    let mut body = Vec::new();
+    let mut reader = client.get(&url).send().await.unwrap().into_async_read();
+    tokio::io::copy(&mut reader, &mut body).await.unwrap();
+    assert_eq!(body, content);
+
+    let mut reader = client
+        .get(&url)
+        .bytes_limit(1024)
+        .send()
+        .await
+        .unwrap()
+        .into_async_read();
+    let err = tokio::io::copy(&mut reader, &mut tokio::io::sink())
+        .await
+        .unwrap_err();
+    let err = err
+        .into_inner()
+        .unwrap()
+        .downcast::<rquest::Error>()
+        .unwrap();
+    assert!(err.is_body_too_large(), "{err:?}");
+}
+
+#[tokio::test]
+async fn gzip_copy_to() {
+    let content = [b'a'; 64 * 1024];
+    let compressed = gzip_compress(&content);
+    let server = server::http(move |_req| {
+        let compressed = compressed.clone();
+        async move {
+            http::Response::builder()
+                .header("content-encoding", "gzip")
+                .body(compressed.into())
+                .unwrap()
+        }
+    });
+    let url = format!("http://{}/gzip", server.addr());
+    let client = rquest::Client::new();
+
+    let mut body = Vec::new();
+    let mut res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.copy_to(&mut body).await.unwrap(), content.len() as u64);
+    assert_eq!(body, content);
+
+    let mut body = Vec::new();
+    let mut res = client.get(&url).send().await.unwrap();
+    let written = res.copy_to_async(&mut body).await.unwrap();
+    assert_eq!(written, content.len() as u64);
+    assert_eq!(body, content);
+
+    let mut res = client.get(&url).bytes_limit(1024).send().await.unwrap();
+    let err = res.copy_to(&mut std::io::sink()).await.unwrap_err();
+    assert!(err.is_body_too_large(), "{err:?}");
+
+    // a writer running out of room is not a decoding error
+    let mut buf = [0; 1024];
+    let mut res = client.get(&url).send().await.unwrap();
+    let err = res
+        .copy_to(&mut std::io::Cursor::new(&mut buf[..]))
+        .await
+        .unwrap_err();
+    assert!(err.is_write() && !err.is_decode(), "{err:?}");
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::WriteZero));
+
+    let mut res = client.get(&url).send().await.unwrap();
+    let err = res
+        .copy_to_async(&mut std::io::Cursor::new(&mut buf[..]))
+        .await
+        .unwrap_err();
+    assert!(err.is_write() && !err.is_decode(), "{err:?}");
+}
+
 #[tokio::test]
 async fn test_accept_header_is_not_changed_if_set() {
     let server = server::http(move |req| async move {
@@ -362,3 +606,108 @@ async fn test_chunked_fragmented_response_with_extra_bytes() {
     assert!(err.is_decode());
     assert!(start.elapsed() >= DELAY_BETWEEN_RESPONSE_PARTS - DELAY_MARGIN);
 }
+
+#[tokio::test]
+async fn gzip_request_body() {
+    use http_body_util::BodyExt;
+    use std::io::Read;
+
+    let content = "compress me ".repeat(1_000);
+    let expected = content.clone();
+    let server = server::http(move |req| {
+        let expected = expected.clone();
+        async move {
+            assert_eq!(req.headers()["content-encoding"], "gzip");
+            let body = req.into_body().collect().await.unwrap().to_bytes();
+            assert!(body.len() < expected.len());
+
+            let mut decoder = libflate::gzip::Decoder::new(&body[..]).unwrap();
+            let mut decoded = String::new();
+            decoder.read_to_string(&mut decoded).unwrap();
+            assert_eq!(decoded, expected);
+            http::Response::default()
+        }
+    });
+
+    let res = rquest::Client::new()
+        .post(format!("http://{}/compressed", server.addr()))
+        .body_compressed(content, rquest::ContentEncoding::Gzip)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn gzip_request_body_from_file() {
+    use http_body_util::BodyExt;
+    use std::io::Read;
+
+    let content = "compress me ".repeat(1_000);
+    let expected = content.clone();
+    let server = server::http(move |req| {
+        let expected = expected.clone();
+        async move {
+            assert_eq!(req.headers()["content-encoding"], "gzip");
+            // the length of the file is not the length of the body
+            assert_eq!(req.headers().get("content-length"), None);
+            let body = req.into_body().collect().await.unwrap().to_bytes();
+
+            let mut decoder = libflate::gzip::Decoder::new(&body[..]).unwrap();
+            let mut decoded = String::new();
+            decoder.read_to_string(&mut decoded).unwrap();
+            assert_eq!(decoded, expected);
+            http::Response::default()
+        }
+    });
+
+    let path = std::env::temp_dir().join(format!("rquest-gzip-upload-{}", std::process::id()));
+    std::fs::write(&path, &content).unwrap();
+
+    let res = rquest::Client::new()
+        .post(format!("http://{}/compressed", server.addr()))
+        .body_from_file(&path)
+        .await
+        .body_compressed(content, rquest::ContentEncoding::Gzip)
+        .send()
+        .await
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn gzip_request_body_skips_small_and_compressed_bodies() {
+    use http_body_util::BodyExt;
+
+    let server = server::http(move |req| async move {
+        assert!(req.headers().get("content-encoding").is_none());
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        if body.len() > 100 {
+            assert!(body.iter().all(|&b| b == 0xAB));
+        } else {
+            assert_eq!(body, "tiny");
+        }
+        http::Response::default()
+    });
+
+    let client = rquest::Client::new();
+    let url = format!("http://{}/compressed", server.addr());
+    let res = client
+        .post(&url)
+        .body_compressed("tiny", rquest::ContentEncoding::Gzip)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+
+    let res = client
+        .post(&url)
+        .header("content-type", "image/png")
+        .body_compressed(vec![0xAB; 4096], rquest::ContentEncoding::Gzip)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}
@@ -142,6 +142,101 @@ async fn test_redirect_307_and_308_tries_to_post_again() {
     }
 }
 
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn test_redirect_307_streaming_body_modes() {
+    use rquest::redirect::RedirectBodyMode;
+
+    let _ = env_logger::try_init();
+    let server = server::http(move |req| async move {
+        let method = req.method().clone();
+        let uri = req.uri().clone();
+        let data = req.into_body().collect().await.unwrap().to_bytes();
+        if uri == "/307" {
+            http::Response::builder()
+                .status(307)
+                .header("location", "/dst")
+                .body(Body::default())
+                .unwrap()
+        } else {
+            let echo = format!("{method} {}", String::from_utf8_lossy(&data));
+            http::Response::new(echo.into())
+        }
+    });
+    let url = format!("http://{}/307", server.addr());
+    let client = rquest::Client::new();
+    let send_with = |policy: Policy| {
+        let chunks: Vec<Result<_, std::io::Error>> = vec![Ok("Hello"), Ok(" world")];
+        client
+            .post(&url)
+            .redirect(policy)
+            .body(Body::wrap_stream(futures_util::stream::iter(chunks)))
+            .send()
+    };
+    let send = |mode| send_with(Policy::default().redirect_body(mode));
+
+    // by default the redirect response is returned
+    let res = send(RedirectBodyMode::Stop).await.unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::TEMPORARY_REDIRECT);
+
+    let err = send(RedirectBodyMode::Error).await.unwrap_err();
+    assert!(err.is_redirect(), "{err:?}");
+
+    let res = send(RedirectBodyMode::SwitchToGet).await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "GET ");
+
+    let res = send(RedirectBodyMode::Buffer(1024)).await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "POST Hello world");
+
+    // the body doesn't fit in the buffer
+    let err = send(RedirectBodyMode::Buffer(8)).await.unwrap_err();
+    assert!(err.is_redirect(), "{err:?}");
+
+    // the body is not needed when the policy doesn't follow the redirect
+    let res = send_with(Policy::none().redirect_body(RedirectBodyMode::Error))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::TEMPORARY_REDIRECT);
+}
+
+#[tokio::test]
+async fn test_redirect_301_and_302_preserve_method_when_enabled() {
+    let _ = env_logger::try_init();
+    let client = rquest::Client::new();
+    for code in [301u16, 302] {
+        let redirect = server::http(move |req| async move {
+            assert_eq!(req.method(), "POST");
+            assert_eq!(req.headers()["content-type"], "text/plain");
+            let uri = req.uri().clone();
+            let data = req.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(&*data, b"Hello");
+
+            if uri == *format!("/{code}") {
+                http::Response::builder()
+                    .status(code)
+                    .header("location", "/dst")
+                    .body(Body::default())
+                    .unwrap()
+            } else {
+                assert_eq!(uri, "/dst");
+                http::Response::default()
+            }
+        });
+
+        let url = format!("http://{}/{}", redirect.addr(), code);
+        let res = client
+            .post(&url)
+            .redirect(Policy::default().preserve_method(true))
+            .header("content-type", "text/plain")
+            .body("Hello")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.url().path(), "/dst");
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+    }
+}
+
 #[tokio::test]
 async fn test_redirect_removes_sensitive_headers() {
     use tokio::sync::watch;
@@ -234,6 +329,7 @@ async fn test_redirect_policy_can_stop_redirects_without_an_error() {
         .unwrap();
 
     assert_eq!(res.url().as_str(), url);
+    assert_eq!(res.original_url().as_str(), url);
     assert_eq!(res.status(), rquest::StatusCode::FOUND);
 }
 
@@ -264,6 +360,50 @@ async fn test_referer_is_not_set_if_disabled() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_referer_follows_referrer_policy() {
+    let server = server::http(move |req| async move {
+        let referer = req
+            .headers()
+            .get("referer")
+            .map(|value| value.to_str().unwrap().to_owned());
+        let host = req.headers()["host"].to_str().unwrap().to_owned();
+        let (expected, location, policy) = match req.uri().path() {
+            "/a" => (None, "/b?q=1", Some("origin")),
+            // only the origin of /b is sent
+            "/b" => (
+                Some(format!("http://{host}/")),
+                "/c",
+                Some("bogus, no-referrer"),
+            ),
+            // the policy of /b still applies
+            "/c" => (None, "/d", None),
+            "/d" => (None, "", None),
+            path => panic!("unexpected path {path}"),
+        };
+        assert_eq!(referer, expected, "{}", req.uri());
+
+        let mut res = http::Response::builder();
+        if !location.is_empty() {
+            res = res.status(302).header("location", location);
+        }
+        if let Some(policy) = policy {
+            res = res.header("referrer-policy", policy);
+        }
+        res.body(Body::default()).unwrap()
+    });
+
+    let res = rquest::Client::builder()
+        .redirect(Policy::default())
+        .build()
+        .unwrap()
+        .get(format!("http://{}/a", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.url().path(), "/d");
+}
+
 #[tokio::test]
 async fn test_invalid_location_stops_redirect_gh484() {
     let server = server::http(move |_req| async move {
@@ -335,3 +475,238 @@ async fn test_redirect_302_with_set_cookies() {
     assert_eq!(res.url().as_str(), dst);
     assert_eq!(res.status(), rquest::StatusCode::OK);
 }
+
+#[cfg(feature = "cookies")]
+#[tokio::test]
+async fn test_redirect_cookies_match_each_hop() {
+    let server = server::http(move |req| async move {
+        let cookie = req.headers().get("cookie");
+        let (expected, next) = match req.uri().path() {
+            "/login" => (None, "/auth/check"),
+            "/auth/check" => (Some("step=login"), "/public"),
+            // No cookie matches, the ones of the previous hop are not sent.
+            "/public" => (None, "/account"),
+            "/account" => {
+                assert_eq!(cookie.unwrap(), "session=abc");
+                return http::Response::default();
+            }
+            _ => unreachable!(),
+        };
+        assert_eq!(cookie.map(|v| v.to_str().unwrap()), expected);
+
+        let mut res = http::Response::builder()
+            .status(302)
+            .header("location", next);
+        if req.uri().path() == "/login" {
+            res = res
+                .header("set-cookie", "session=abc; Path=/account")
+                .header("set-cookie", "step=login; Path=/auth");
+        }
+        res.body(Body::default()).unwrap()
+    });
+
+    let client = rquest::Client::builder()
+        .cookie_store(true)
+        .redirect(Policy::default())
+        .build()
+        .unwrap();
+    let res = client
+        .get(format!("http://{}/login", server.addr()))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.url().path(), "/account");
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}
+
+#[cfg(feature = "cookies")]
+#[tokio::test]
+async fn test_redirect_keeps_caller_cookie_header() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["cookie"], "token=mine");
+        if req.uri() == "/start" {
+            return http::Response::builder()
+                .status(302)
+                .header("location", "/dst")
+                .header("set-cookie", "session=abc")
+                .body(Body::default())
+                .unwrap();
+        }
+        http::Response::default()
+    });
+
+    let client = rquest::Client::builder()
+        .cookie_store(true)
+        .redirect(Policy::default())
+        .build()
+        .unwrap();
+    let res = client
+        .get(format!("http://{}/start", server.addr()))
+        .header("cookie", "token=mine")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.url().path(), "/dst");
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_redirect_policy_observes_chain_and_history() {
+    use std::sync::{Arc, Mutex};
+
+    let server = server::http(move |req| async move {
+        let next = match req.uri().path() {
+            "/a" => "/b",
+            "/b" => "/c",
+            _ => return http::Response::default(),
+        };
+        http::Response::builder()
+            .status(302)
+            .header("location", next)
+            .header("set-cookie", format!("hop={}", &req.uri().path()[1..]))
+            .body(Body::default())
+            .unwrap()
+    });
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let policy = {
+        let seen = seen.clone();
+        Policy::custom(move |attempt| {
+            seen.lock().unwrap().push((
+                attempt.previous().last().unwrap().path().to_owned(),
+                attempt.url().path().to_owned(),
+                attempt.status(),
+                attempt.headers()["set-cookie"].clone(),
+            ));
+            attempt.follow()
+        })
+    };
+
+    let base = format!("http://{}", server.addr());
+    let res = rquest::Client::builder()
+        .redirect(policy)
+        .build()
+        .unwrap()
+        .get(format!("{base}/a"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.url().as_str(), format!("{base}/c"));
+    let history = res
+        .redirect_history()
+        .iter()
+        .map(|url| url.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(history, [format!("{base}/a"), format!("{base}/b")]);
+    assert_eq!(res.original_url().as_str(), format!("{base}/a"));
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(
+        *seen,
+        [
+            (
+                "/a".into(),
+                "/b".into(),
+                rquest::StatusCode::FOUND,
+                "hop=a".parse().unwrap()
+            ),
+            (
+                "/b".into(),
+                "/c".into(),
+                rquest::StatusCode::FOUND,
+                "hop=b".parse().unwrap()
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_redirect_history_excludes_stopped_url() {
+    let server = server::http(move |req| async move {
+        let next = match req.uri().path() {
+            "/a" => "/b",
+            _ => "/c",
+        };
+        http::Response::builder()
+            .status(302)
+            .header("location", next)
+            .body(Body::default())
+            .unwrap()
+    });
+
+    let base = format!("http://{}", server.addr());
+    let res = rquest::Client::builder()
+        .redirect(Policy::custom(|attempt| {
+            if attempt.url().path() == "/c" {
+                attempt.stop()
+            } else {
+                attempt.follow()
+            }
+        }))
+        .build()
+        .unwrap()
+        .get(format!("{base}/a"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.url().as_str(), format!("{base}/b"));
+    assert_eq!(res.status(), rquest::StatusCode::FOUND);
+    assert_eq!(res.redirect_history().len(), 1);
+    assert_eq!(res.redirect_history()[0].as_str(), format!("{base}/a"));
+    assert_eq!(res.original_url().as_str(), format!("{base}/a"));
+}
+
+#[tokio::test]
+async fn test_redirect_cross_origin_strips_authorization() {
+    let server = server::http(move |req| async move {
+        if req.uri() == "/start" {
+            let port = req.headers()["host"].to_str().unwrap().split(':').nth(1);
+            http::Response::builder()
+                .status(302)
+                .header("location", format!("http://b.com:{}/dst", port.unwrap()))
+                .body(Body::default())
+                .unwrap()
+        } else {
+            assert_eq!(
+                req.headers()["host"].to_str().unwrap().split(':').next(),
+                Some("b.com")
+            );
+            let auth = req
+                .headers()
+                .get("authorization")
+                .map(|v| v.as_bytes().to_vec());
+            http::Response::new(Body::from(auth.unwrap_or_default()))
+        }
+    });
+
+    let client = rquest::Client::builder()
+        .redirect(Policy::default())
+        .resolve("a.com", server.addr())
+        .resolve("b.com", server.addr())
+        .build()
+        .unwrap();
+    let url = format!("http://a.com:{}/start", server.addr().port());
+
+    let res = client
+        .get(&url)
+        .header("authorization", "Bearer secret")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.url().host_str(), Some("b.com"));
+    assert_eq!(res.text().await.unwrap(), "");
+
+    let res = client
+        .get(&url)
+        .header("authorization", "Bearer secret")
+        .redirect(Policy::default().strip_sensitive_headers(false))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.url().host_str(), Some("b.com"));
+    assert_eq!(res.text().await.unwrap(), "Bearer secret");
+}
@@ -0,0 +1,91 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+
+use std::time::Duration;
+
+use http::HeaderValue;
+use tower::layer::util::Identity;
+use tower::timeout::TimeoutLayer;
+use tower::util::{MapRequestLayer, MapResponseLayer};
+
+use support::server;
+
+#[tokio::test]
+async fn non_op_layer() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::default() });
+
+    let url = format!("http://{}", server.addr());
+
+    let client = rquest::Client::builder()
+        .layer(Identity::new())
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let res = client.get(url).send().await.unwrap();
+
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn layer_sees_default_headers() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["x-signature"], "sig-of-rquest-test");
+        http::Response::default()
+    });
+
+    let url = format!("http://{}", server.addr());
+
+    let client = rquest::Client::builder()
+        .user_agent("rquest-test")
+        .layer(MapRequestLayer::new(
+            |mut req: http::Request<rquest::Body>| {
+                let user_agent = req.headers()[http::header::USER_AGENT].clone();
+                let signature = format!("sig-of-{}", user_agent.to_str().unwrap());
+                req.headers_mut()
+                    .insert("x-signature", HeaderValue::try_from(signature).unwrap());
+                req
+            },
+        ))
+        .layer(MapResponseLayer::new(
+            |mut res: http::Response<rquest::Body>| {
+                res.headers_mut()
+                    .insert("x-layer", HeaderValue::from_static("seen"));
+                res
+            },
+        ))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let res = client.get(url).send().await.unwrap();
+
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+    assert_eq!(res.headers()["x-layer"], "seen");
+}
+
+#[tokio::test]
+async fn timeout_layer() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        http::Response::default()
+    });
+
+    let url = format!("http://{}", server.addr());
+
+    let client = rquest::Client::builder()
+        .layer(TimeoutLayer::new(Duration::from_millis(100)))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let err = client.get(url).send().await.unwrap_err();
+
+    assert!(err.is_request());
+}
@@ -144,6 +144,153 @@ async fn connect_many_timeout() {
         .await;
 }
 
+/// Returns an address with `port` that accepts no connection, connecting to
+/// which hangs until the connect timeout, along with what keeps it so.
+#[cfg(target_os = "linux")]
+fn unresponsive_addr(
+    port: u16,
+) -> (
+    std::net::SocketAddr,
+    socket2::Socket,
+    Vec<std::net::TcpStream>,
+) {
+    let listener =
+        socket2::Socket::new(socket2::Domain::IPV4, socket2::Type::STREAM, None).unwrap();
+    listener
+        .bind(&std::net::SocketAddr::from(([127, 0, 0, 2], port)).into())
+        .unwrap();
+    listener.listen(0).unwrap();
+    let addr = listener.local_addr().unwrap().as_socket().unwrap();
+    // fills the accept queue, so the SYNs of next connections are dropped
+    let mut queued = Vec::new();
+    while let Ok(stream) = std::net::TcpStream::connect_timeout(&addr, Duration::from_millis(100)) {
+        queued.push(stream);
+    }
+    (addr, listener, queued)
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn per_address_connect_timeout() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::default() });
+    let (unresponsive, _listener, _queued) = unresponsive_addr(server.addr().port());
+
+    let client = rquest::Client::builder()
+        .resolve_to_addrs("many_addrs", &[unresponsive, server.addr()])
+        .connect_timeout(Duration::from_secs(10))
+        .per_address_connect_timeout(Duration::from_millis(100))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://many_addrs:{}/", server.addr().port());
+
+    let start = std::time::Instant::now();
+    let res = client.get(url).send().await.unwrap();
+
+    assert_eq!(res.remote_addr(), Some(server.addr()));
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn connect_timeout_covers_tls_handshake() {
+    let _ = env_logger::try_init();
+
+    // accepts connections but never answers the TLS handshake
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            held.push(stream);
+        }
+    });
+
+    let client = rquest::Client::builder()
+        .connect_timeout(Duration::from_millis(100))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    let err = client
+        .get(format!("https://{addr}/"))
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_connect() && err.is_timeout());
+    assert!(!err.is_handshake_timeout());
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn tls_handshake_timeout() {
+    let _ = env_logger::try_init();
+
+    // accepts connections but never answers the TLS handshake
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            held.push(stream);
+        }
+    });
+
+    let client = rquest::Client::builder()
+        .tls_handshake_timeout(Duration::from_millis(100))
+        .connect_timeout(Duration::from_secs(5))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    let err = client
+        .get(format!("https://{addr}/"))
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_handshake_timeout(), "{err:?}");
+    assert!(err.is_connect() && err.is_timeout());
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    // plain HTTP has no handshake to time out
+    let server = server::http(move |_req| async { http::Response::default() });
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn connect_timeout_allows_slow_response() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        http::Response::default()
+    });
+
+    let client = rquest::Client::builder()
+        .connect_timeout(Duration::from_millis(100))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/slow", server.addr());
+    for reused in [false, true] {
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status(), rquest::StatusCode::OK);
+        assert_eq!(res.connection_reused(), reused);
+    }
+}
+
 #[cfg(feature = "stream")]
 #[tokio::test]
 async fn response_timeout() {
@@ -276,6 +423,51 @@ async fn read_timeout_allows_slow_response_body() {
     assert_eq!(body, "012");
 }
 
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn request_timeout_bounds_slow_response_body() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| {
+        async {
+            // every chunk arrives within the read timeout, but the body as a whole is slow
+            let slow = futures_util::stream::unfold(0, |state| async move {
+                if state < 10 {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    Some((
+                        Ok::<_, std::convert::Infallible>(state.to_string()),
+                        state + 1,
+                    ))
+                } else {
+                    None
+                }
+            });
+            let body = rquest::Body::wrap_stream(slow);
+
+            http::Response::new(body)
+        }
+    });
+
+    let client = rquest::Client::builder()
+        .read_timeout(Duration::from_millis(200))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/slow", server.addr());
+    let res = client
+        .get(&url)
+        .timeout(Duration::from_millis(350))
+        .send()
+        .await
+        .expect("Failed to get");
+    let body = res.text().await;
+
+    let err = body.unwrap_err();
+
+    assert!(err.is_timeout());
+}
+
 #[tokio::test]
 async fn response_body_timeout_forwards_size_hint() {
     let _ = env_logger::try_init();
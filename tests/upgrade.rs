@@ -3,8 +3,9 @@ mod support;
 use support::server;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-#[tokio::test]
-async fn http_upgrade() {
+/// Upgrades a connection to the `foobar` protocol with `client`, over which
+/// the server answers `foo=bar` with `reply`.
+async fn upgrade(client: rquest::Client, reply: &'static [u8]) {
     let server = server::http(move |req| {
         assert_eq!(req.method(), "GET");
         assert_eq!(req.headers()["connection"], "upgrade");
@@ -17,7 +18,7 @@ async fn http_upgrade() {
             upgraded.read_exact(&mut buf).await.unwrap();
             assert_eq!(buf, b"foo=bar");
 
-            upgraded.write_all(b"bar=foo").await.unwrap();
+            upgraded.write_all(reply).await.unwrap();
         });
 
         async {
@@ -30,9 +31,7 @@ async fn http_upgrade() {
         }
     });
 
-    let res = rquest::Client::builder()
-        .build()
-        .unwrap()
+    let res = client
         .get(format!("http://{}", server.addr()))
         .header(http::header::CONNECTION, "upgrade")
         .header(http::header::UPGRADE, "foobar")
@@ -47,5 +46,32 @@ async fn http_upgrade() {
 
     let mut buf = vec![];
     upgraded.read_to_end(&mut buf).await.unwrap();
-    assert_eq!(buf, b"bar=foo");
+    assert_eq!(buf, reply);
+}
+
+#[tokio::test]
+async fn http_upgrade() {
+    upgrade(rquest::Client::builder().build().unwrap(), b"bar=foo").await;
+}
+
+#[tokio::test]
+async fn http_upgrade_with_defensive_parsing() {
+    let client = rquest::Client::builder()
+        .defensive_parsing(true)
+        .build()
+        .unwrap();
+
+    // what follows the 101 is not an HTTP head, so it is not validated
+    upgrade(client, b"\x81\x05bar\0foo\n\r").await;
+}
+
+#[tokio::test]
+async fn http_upgrade_with_max_response_header_size() {
+    let client = rquest::Client::builder()
+        .max_response_header_size(1024)
+        .build()
+        .unwrap();
+
+    // only the 101 head counts towards the limit
+    upgrade(client, &[b'x'; 64 * 1024]).await;
 }
@@ -0,0 +1,186 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use rquest::Impersonate;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+// The sample nonce from RFC 6455 and its accept key.
+const KEY: &str = "dGhlIHNhbXBsZSBub25jZQ==";
+const ACCEPT: &str = "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=";
+
+/// Accepts one handshake, answers it with `extra` response headers and
+/// hands back the raw request head.
+async fn handshake_server(extra: &'static str) -> (String, oneshot::Receiver<String>) {
+    handshake_server_with(move |_| extra.to_owned()).await
+}
+
+/// Like [`handshake_server`], but picks the extra response headers from
+/// the request head.
+async fn handshake_server_with<F>(extra: F) -> (String, oneshot::Receiver<String>)
+where
+    F: FnOnce(&str) -> String + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = Vec::new();
+        while !buf.ends_with(b"\r\n\r\n") {
+            let mut chunk = [0u8; 1024];
+            let n = socket.read(&mut chunk).await.unwrap();
+            assert_ne!(n, 0, "connection closed during handshake");
+            buf.extend_from_slice(&chunk[..n]);
+        }
+
+        let head = String::from_utf8(buf).unwrap();
+        let res = format!(
+            "HTTP/1.1 101 Switching Protocols\r\n\
+             upgrade: websocket\r\n\
+             connection: Upgrade\r\n\
+             sec-websocket-accept: {ACCEPT}\r\n\
+             {}\r\n",
+            extra(&head)
+        );
+        socket.write_all(res.as_bytes()).await.unwrap();
+        let _ = tx.send(head);
+
+        // keep the connection open until the client is done
+        let _ = socket.read(&mut [0u8; 1]).await;
+    });
+
+    (format!("ws://{addr}/chat"), rx)
+}
+
+fn header_names(head: &str) -> Vec<String> {
+    head.split("\r\n")
+        .skip(1)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.split(':').next().unwrap().to_ascii_lowercase())
+        .collect()
+}
+
+#[tokio::test]
+async fn handshake_matches_chrome() {
+    let (url, head) = handshake_server("").await;
+
+    let client = rquest::Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let res = client
+        .websocket(url)
+        .key(KEY)
+        .extensions("permessage-deflate; client_max_window_bits")
+        .with_builder(|builder| {
+            builder
+                .header("origin", "http://example.com")
+                .header("accept-encoding", "gzip, deflate, br, zstd")
+        })
+        .send()
+        .await
+        .unwrap();
+    res.into_websocket().await.unwrap();
+
+    let head = head.await.unwrap();
+    assert!(head.starts_with("GET /chat HTTP/1.1\r\n"), "{head}");
+    assert!(head.contains("connection: Upgrade\r\n"), "{head}");
+    assert!(
+        head.contains("sec-websocket-extensions: permessage-deflate; client_max_window_bits\r\n"),
+        "{head}"
+    );
+
+    let names = header_names(&head);
+    assert_eq!(
+        names[..12],
+        [
+            "host",
+            "connection",
+            "pragma",
+            "cache-control",
+            "user-agent",
+            "upgrade",
+            "origin",
+            "sec-websocket-version",
+            "accept-encoding",
+            "accept-language",
+            "sec-websocket-key",
+            "sec-websocket-extensions",
+        ],
+        "{head}"
+    );
+}
+
+#[tokio::test]
+async fn handshake_without_extensions() {
+    let (url, head) = handshake_server("").await;
+
+    let res = rquest::Client::builder()
+        .no_proxy()
+        .build()
+        .unwrap()
+        .websocket(url)
+        .key(KEY)
+        .no_extensions()
+        .send()
+        .await
+        .unwrap();
+    res.into_websocket().await.unwrap();
+
+    let head = head.await.unwrap();
+    assert!(!head.contains("sec-websocket-extensions"), "{head}");
+}
+
+#[tokio::test]
+async fn no_extensions_offered_by_default() {
+    // Like nginx or Node's `ws`, accept permessage-deflate whenever it's offered.
+    let (url, head) = handshake_server_with(|head| {
+        if head.contains("sec-websocket-extensions: permessage-deflate") {
+            "sec-websocket-extensions: permessage-deflate\r\n".to_owned()
+        } else {
+            String::new()
+        }
+    })
+    .await;
+
+    let res = rquest::Client::builder()
+        .impersonate(Impersonate::Chrome131)
+        .no_proxy()
+        .build()
+        .unwrap()
+        .websocket(url)
+        .key(KEY)
+        .send()
+        .await
+        .unwrap();
+    res.into_websocket().await.unwrap();
+
+    let head = head.await.unwrap();
+    assert!(!head.contains("sec-websocket-extensions"), "{head}");
+}
+
+#[tokio::test]
+async fn negotiated_extension_is_rejected() {
+    let (url, _head) = handshake_server("sec-websocket-extensions: permessage-deflate\r\n").await;
+
+    let res = rquest::Client::builder()
+        .no_proxy()
+        .build()
+        .unwrap()
+        .websocket(url)
+        .key(KEY)
+        .extensions("permessage-deflate")
+        .send()
+        .await
+        .unwrap();
+
+    let err = res.into_websocket().await.unwrap_err();
+    assert!(
+        format!("{err:?}").contains("unsupported extensions"),
+        "{err:?}"
+    );
+}
@@ -350,3 +350,35 @@ async fn test_chunked_fragmented_response_with_extra_bytes() {
     assert!(err.is_decode());
     assert!(start.elapsed() >= DELAY_BETWEEN_RESPONSE_PARTS - DELAY_MARGIN);
 }
+
+#[tokio::test]
+async fn zstd_request_body_is_streamed() {
+    use http_body_util::BodyExt;
+
+    let chunks = vec!["compress me ".repeat(500); 4];
+    let expected = chunks.concat();
+    let server = server::http(move |req| {
+        let expected = expected.clone();
+        async move {
+            assert_eq!(req.headers()["content-encoding"], "zstd");
+            assert!(req.headers().get("content-length").is_none());
+            let body = req.into_body().collect().await.unwrap().to_bytes();
+            let decoded = zstd::stream::decode_all(&body[..]).unwrap();
+            assert_eq!(decoded, expected.as_bytes());
+            http::Response::default()
+        }
+    });
+
+    let stream =
+        futures_util::stream::iter(chunks.into_iter().map(Ok::<_, std::convert::Infallible>));
+    let res = rquest::Client::new()
+        .post(format!("http://{}/compressed", server.addr()))
+        .body_compressed(
+            rquest::Body::wrap_stream(stream),
+            rquest::ContentEncoding::Zstd,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), rquest::StatusCode::OK);
+}